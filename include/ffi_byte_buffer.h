@@ -0,0 +1,195 @@
+// Shared types of ffi-byte-buffer and the functions of its `export` feature.
+#pragma once
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+// Boxed byte slice `Box<[u8]>`, see `ByteBuffer`.
+// An empty buffer has a null `ptr` and a `len` of 0.
+typedef struct ByteBuffer {
+    uint8_t* ptr;
+    size_t len;
+} ByteBuffer;
+
+// Vector `Vec<u8>` with its capacity, see `OwnedVecBuffer`.
+// The bytes `ptr[len..cap]` are uninitialized, a buffer without allocation has a null `ptr`.
+typedef struct OwnedVecBuffer {
+    uint8_t* ptr;
+    size_t len;
+    size_t cap;
+} OwnedVecBuffer;
+
+// Borrowed views of bytes owned by someone else, see `FfiSliceRef` and `FfiSliceMut`.
+// An empty view has a null `ptr` and a `len` of 0.
+typedef struct FfiSliceRef {
+    const uint8_t* ptr;
+    size_t len;
+} FfiSliceRef;
+
+typedef struct FfiSliceMut {
+    uint8_t* ptr;
+    size_t len;
+} FfiSliceMut;
+
+// Copy-on-write buffer, see `FfiCow`. Borrows static or cached data if `owned` is false.
+// An empty buffer has a null `ptr`, a `len` of 0 and `owned` false.
+typedef struct FfiCow {
+    const uint8_t* ptr;
+    size_t len;
+    bool owned;
+} FfiCow;
+
+// Array of byte buffers, see `FfiBufferArray`.
+// The items are views into `backing` if it is not empty.
+typedef struct FfiBufferArray {
+    ByteBuffer* ptr;
+    size_t len;
+    ByteBuffer backing;
+} FfiBufferArray;
+
+// Entry of a key/value map, see `FfiMapEntry`. The `key` is a UTF-8 string buffer.
+typedef struct FfiMapEntry {
+    ByteBuffer key;
+    ByteBuffer value;
+} FfiMapEntry;
+
+// Status codes, see `FfiStatus`.
+typedef enum FfiStatus {
+    FFI_STATUS_OK = 0,
+    FFI_STATUS_PANIC = 1,
+    FFI_STATUS_INVALID_ARGUMENT = 2,
+    FFI_STATUS_INVALID_ENCODING = 3,
+    FFI_STATUS_BUFFER_TOO_SMALL = 4,
+    FFI_STATUS_ALLOCATION_FAILED = 5,
+    FFI_STATUS_OUT_OF_BOUNDS = 6,
+    FFI_STATUS_REGISTRY = 7,
+    FFI_STATUS_CODEC = 8,
+    FFI_STATUS_IO = 9,
+    FFI_STATUS_STALE_HANDLE = 10,
+    FFI_STATUS_LOCK_LIMIT = 11,
+    FFI_STATUS_CORRUPTED = 12,
+} FfiStatus;
+
+// Host allocator functions (`export` feature), see `FfiAllocatorVTable` and `HostAllocator`.
+// Registered once, memory allocated before is still freed by the system allocator.
+typedef struct FfiAllocatorVTable {
+    uint8_t* (*alloc)(void* user_data, size_t size, size_t align);
+    void (*dealloc)(void* user_data, uint8_t* ptr, size_t size, size_t align);
+    uint8_t* (*realloc)(void* user_data, uint8_t* ptr, size_t old_size, size_t align, size_t new_size);
+    void* user_data;
+} FfiAllocatorVTable;
+
+FfiStatus set_allocator(const FfiAllocatorVTable* vtable);
+
+// Memory budget (`export` feature), allocations beyond the limit fail. A limit of 0 removes it.
+void set_memory_limit(size_t limit);
+size_t get_current_usage(void);
+size_t get_peak_usage(void);
+
+// Buffer counters (`export` and `stats` features), see `BufferStats`.
+typedef struct BufferStats {
+    size_t live_buffers;
+    size_t live_bytes;
+    size_t total_allocations;
+    size_t total_frees;
+    size_t peak_bytes;
+} BufferStats;
+
+FfiStatus get_buffer_stats(BufferStats* out);
+
+// Integrity checks of read-only buffers handed to the host (`export` feature), see `checked`.
+// A corruption is logged with the pointer and lengths and stored as `FFI_STATUS_CORRUPTED` last error,
+// it panics instead only if enabled.
+void enable_checked_buffers(bool enabled);
+void set_panic_on_corruption(bool panic);
+uint64_t get_corruption_count(void);
+
+// Allocation and release (`export` feature).
+// Every buffer returned by the library is released exactly once with the matching function.
+ByteBuffer ffi_byte_buffer_alloc(size_t len);
+FfiStatus ffi_byte_buffer_try_alloc(size_t len, ByteBuffer* out);
+FfiStatus ffi_byte_buffer_grow(ByteBuffer* buffer, size_t new_len);
+FfiStatus ffi_byte_buffer_from_bytes(const uint8_t* ptr, size_t len, ByteBuffer* out);
+void ffi_byte_buffer_free(ByteBuffer buffer);
+void ffi_string_free(ByteBuffer buffer);
+void ffi_cow_free(FfiCow buffer);
+FfiStatus ffi_secure_alloc_locked(size_t len, bool best_effort, ByteBuffer* out);
+void free_secure(ByteBuffer buffer);
+bool buffer_ct_eq(const uint8_t* a_ptr, size_t a_len, const uint8_t* b_ptr, size_t b_len);
+void ffi_buffer_array_free(FfiBufferArray array);
+void ffi_strings_free(ByteBuffer* ptr, size_t len);
+void ffi_byte_vecs_free(ByteBuffer* ptr, size_t len);
+void ffi_map_free(FfiMapEntry* ptr, size_t len);
+bool ffi_shared_clone(const uint8_t* ptr);
+bool ffi_shared_release(const uint8_t* ptr);
+ByteBuffer ffi_empty_buffer(void);
+OwnedVecBuffer new_vec_buffer(size_t cap);
+void free_vec_buffer(OwnedVecBuffer buffer);
+
+// Versioned envelope (`export` feature): "FBBE" magic, u16 format version, u16 flags,
+// u32 schema version and u64 payload length (little endian), followed by the payload.
+FfiStatus ffi_envelope_wrap(const uint8_t* ptr, size_t len, uint32_t schema_version, ByteBuffer* out);
+FfiStatus ffi_envelope_unwrap(const uint8_t* ptr, size_t len, FfiSliceRef* out_payload, uint32_t* out_version);
+
+// Last error of the calling thread (`export` feature), read the code before taking the message.
+int32_t ffi_last_error_code(void);
+ByteBuffer ffi_last_error_message(void);
+
+// Opaque handles of registered buffers (`export` feature), see `BufferHandle`.
+// 0 is never a valid handle, a freed handle is rejected with `FFI_STATUS_STALE_HANDLE`.
+typedef uint64_t BufferHandle;
+
+FfiStatus buffer_handle_alloc(size_t len, BufferHandle* out);
+FfiStatus buffer_handle_from_bytes(const uint8_t* ptr, size_t len, BufferHandle* out);
+FfiStatus buffer_handle_len(BufferHandle handle, size_t* out);
+FfiStatus buffer_handle_read(BufferHandle handle, size_t offset, uint8_t* dst_ptr, size_t len);
+FfiStatus buffer_handle_write(BufferHandle handle, size_t offset, const uint8_t* src_ptr, size_t len);
+FfiStatus buffer_handle_take(BufferHandle handle, ByteBuffer* out);
+FfiStatus buffer_handle_free(BufferHandle handle);
+
+// Arenas of transient buffers (`export` feature), see `Arena`.
+// The buffers are views owned by the arena, valid until it is reset or released.
+typedef struct Arena Arena;
+
+FfiStatus ffi_arena_new(size_t block_size, Arena** out_handle);
+FfiStatus ffi_arena_alloc(Arena* arena, size_t len, FfiSliceMut* out);
+FfiStatus ffi_arena_alloc_bytes(Arena* arena, const uint8_t* ptr, size_t len, FfiSliceRef* out);
+void ffi_arena_reset(Arena* arena);
+void ffi_arena_free(Arena* arena);
+
+// Shared-memory mailbox (`export` and `shm` features), see `ShmMailbox`.
+// One slot for a message, send waits while it is full and recv while it is empty.
+// A timeout of UINT64_MAX waits indefinitely, a timed out wait fails with FFI_STATUS_IO.
+typedef struct ShmMailbox ShmMailbox;
+
+FfiStatus ffi_shm_mailbox_create(const char* name, size_t capacity, ShmMailbox** out_handle);
+FfiStatus ffi_shm_mailbox_open(const char* name, ShmMailbox** out_handle);
+FfiStatus ffi_shm_mailbox_send(const ShmMailbox* mailbox, const uint8_t* ptr, size_t len, uint64_t timeout_ms);
+FfiStatus ffi_shm_mailbox_recv(const ShmMailbox* mailbox, uint64_t timeout_ms, ByteBuffer* out);
+void ffi_shm_mailbox_free(ShmMailbox* mailbox);
+
+// Pools of fixed-size buffers (`export` feature), see `BufferPool`.
+// A checked out buffer is returned with `ffi_pool_return`, never with another release function.
+typedef struct FfiPoolStats {
+    size_t buffer_size;
+    size_t capacity;
+    size_t idle;
+    size_t outstanding;
+    size_t hits;
+    size_t misses;
+} FfiPoolStats;
+
+FfiStatus ffi_pool_configure(size_t buffer_size, size_t capacity);
+FfiStatus ffi_pool_checkout(size_t len, ByteBuffer* out);
+FfiStatus ffi_pool_return(ByteBuffer buffer);
+FfiStatus ffi_pool_stats(FfiPoolStats* out);
+size_t ffi_pool_trim(void);
+
+#ifdef __cplusplus
+}
+#endif
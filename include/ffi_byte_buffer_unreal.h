@@ -0,0 +1,64 @@
+// Unreal Engine helpers of the `unreal` feature of ffi-byte-buffer.
+#pragma once
+
+#include "CoreMinimal.h"
+#include "HAL/UnrealMemory.h"
+
+extern "C" {
+
+typedef void* (*FfiUnrealMalloc)(SIZE_T Count, uint32 Alignment);
+typedef void (*FfiUnrealFree)(void* Original);
+
+// An empty buffer has a null `Data` and a `Num` of 0.
+struct FfiUnrealBuffer {
+    uint8* Data;
+    int32 Num;
+    // Deallocation function the buffer was allocated for, null for the rust allocator.
+    FfiUnrealFree Free;
+};
+
+// Status codes, see `FfiStatus` (0 is ok).
+int32 ffi_unreal_set_allocator(FfiUnrealMalloc Malloc, FfiUnrealFree Free);
+int32 ffi_unreal_buffer_from_bytes(const uint8* Data, int32 Num, FfiUnrealBuffer* Out);
+int32 ffi_unreal_buffer_from_tchar(const TCHAR* Chars, int32 Len, FfiUnrealBuffer* Out);
+void ffi_unreal_buffer_free(FfiUnrealBuffer Buffer);
+
+}
+
+namespace FfiByteBuffer {
+
+inline void* Malloc(SIZE_T Count, uint32 Alignment) { return FMemory::Malloc(Count, Alignment); }
+inline void Free(void* Original) { FMemory::Free(Original); }
+
+// Routes all buffer allocations through `FMemory`, call once at module startup.
+inline void UseFMemory() { ffi_unreal_set_allocator(&Malloc, &Free); }
+
+// Copies the buffer into a new `TArray<uint8>` and releases the buffer.
+inline TArray<uint8> ToTArray(FfiUnrealBuffer Buffer) {
+    TArray<uint8> Result(Buffer.Data, Buffer.Num);
+    ffi_unreal_buffer_free(Buffer);
+    return Result;
+}
+
+// Converts the UTF-8 buffer into a new `FString` and releases the buffer.
+inline FString ToFString(FfiUnrealBuffer Buffer) {
+    FString Result = FString(FUTF8ToTCHAR((const ANSICHAR*)Buffer.Data, Buffer.Num));
+    ffi_unreal_buffer_free(Buffer);
+    return Result;
+}
+
+// Copies the bytes of the given array into a new buffer.
+inline FfiUnrealBuffer FromTArray(const TArray<uint8>& Array) {
+    FfiUnrealBuffer Buffer = {};
+    ffi_unreal_buffer_from_bytes(Array.GetData(), Array.Num(), &Buffer);
+    return Buffer;
+}
+
+// Converts the given string into a new UTF-8 buffer.
+inline FfiUnrealBuffer FromFString(const FString& String) {
+    FfiUnrealBuffer Buffer = {};
+    ffi_unreal_buffer_from_tchar(*String, String.Len(), &Buffer);
+    return Buffer;
+}
+
+}
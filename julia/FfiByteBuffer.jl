@@ -0,0 +1,49 @@
+# Julia bindings of the `julia` feature of ffi-byte-buffer.
+#
+# The shared library is looked up by the name in the environment variable
+# `FFI_BYTE_BUFFER_LIB` (default: `libffi_byte_buffer`).
+module FfiByteBuffer
+
+export ByteBuffer, OwnedBuffer, bytes
+
+const LIB = get(ENV, "FFI_BYTE_BUFFER_LIB", "libffi_byte_buffer")
+
+# Layout of the rust `ByteBuffer` - an empty buffer has a null `ptr` and a `len` of 0.
+struct ByteBuffer
+    ptr::Ptr{UInt8}
+    len::Csize_t
+end
+
+# Owned buffer, released by its finalizer.
+mutable struct OwnedBuffer
+    raw::ByteBuffer
+
+    function OwnedBuffer(raw::ByteBuffer)
+        buffer = new(raw)
+        finalizer(buffer) do b
+            ccall((:ffi_julia_buffer_free, LIB), Cvoid, (ByteBuffer,), b.raw)
+        end
+    end
+end
+
+# Allocates a new zeroed buffer with the given length.
+OwnedBuffer(len::Integer) =
+    OwnedBuffer(ccall((:ffi_julia_buffer_new, LIB), ByteBuffer, (Csize_t,), len))
+
+# Allocates a new buffer with a copy of the given bytes.
+OwnedBuffer(src::Vector{UInt8}) = OwnedBuffer(
+    GC.@preserve src ccall(
+        (:ffi_julia_buffer_from_bytes, LIB), ByteBuffer, (Ptr{UInt8}, Csize_t), pointer(src), length(src),
+    ),
+)
+
+# Returns a `Vector{UInt8}` view of the buffer (no copy) - the buffer must be kept
+# alive (e.g. with `GC.@preserve`) while the view is used.
+function bytes(buffer::OwnedBuffer)
+    buffer.raw.len == 0 && return UInt8[]
+    unsafe_wrap(Vector{UInt8}, buffer.raw.ptr, buffer.raw.len; own = false)
+end
+
+Base.length(buffer::OwnedBuffer) = Int(buffer.raw.len)
+
+end
@@ -0,0 +1,58 @@
+//! Byte buffers aligned beyond 1 byte (e.g. 16 or 64 bytes for SIMD processing on the host),
+//! which are reclaimed with the same alignment, so they are deallocated with the correct layout.
+
+use std::{alloc::Layout, mem::ManuallyDrop};
+
+use crate::{FfiBuffer, error::FfiBufferError};
+
+const LABEL: &str = "new_aligned_byte_buffer_raw";
+
+/// Allocates a new zeroed byte buffer with the given `length` and alignment `align`
+/// and returns the pointer to the buffer.
+///
+/// The buffer must be converted back with [`from_aligned_byte_slice_raw`] and the same
+/// `length` and `align` at some point.
+///
+/// Note: Null is returned if `length` is 0, `align` is not a power of two,
+/// the size overflows or the allocation failed.
+pub fn new_aligned_byte_buffer_raw(length: usize, align: usize) -> *mut u8 {
+    if length == 0 {
+        return std::ptr::null_mut();
+    }
+
+    match FfiBuffer::builder(length)
+        .alignment(align)
+        .label(LABEL)
+        .build()
+    {
+        Some(buffer) => ManuallyDrop::new(buffer).as_mut_ptr(),
+        None => std::ptr::null_mut(),
+    }
+}
+
+/// Converts the given aligned byte buffer back into a rust managed buffer,
+/// which is deallocated with the alignment `align` when dropped.
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::InvalidArgument`] if `align` is not a power of two or the size
+/// overflows (the buffer can't have been created by this library then), the buffer is
+/// left untouched.
+///
+/// # Safety
+///
+/// The buffer must have been created with [`new_aligned_byte_buffer_raw`] with the same
+/// `length` and `align`, and must not be used afterwards.
+pub unsafe fn from_aligned_byte_slice_raw(
+    ptr: *mut u8,
+    length: usize,
+    align: usize,
+) -> Result<FfiBuffer, FfiBufferError> {
+    if ptr.is_null() || length == 0 {
+        return Ok(FfiBuffer::default());
+    }
+
+    let layout = Layout::from_size_align(length, align)
+        .map_err(|_| FfiBufferError::InvalidArgument("invalid layout of an aligned buffer"))?;
+    Ok(unsafe { FfiBuffer::from_raw_parts(ptr, layout, LABEL) })
+}
@@ -0,0 +1,183 @@
+//! Host provided allocator, for hosts (e.g. game engines) which require all memory to come
+//! from their own allocator.
+//!
+//! The allocator is plugged in as rust global allocator, so every buffer created or freed
+//! by this crate (and any other allocation of the library) goes through the host functions:
+//!
+//! ```ignore
+//! #[global_allocator]
+//! static ALLOCATOR: ffi_byte_buffer::allocator::HostAllocator =
+//!     ffi_byte_buffer::allocator::HostAllocator;
+//! ```
+//!
+//! Until the host registers its functions with [`set_allocator`], the system allocator is used.
+//! Memory allocated before (e.g. by the rust runtime or the registries of this crate) is still
+//! deallocated by the system allocator, see [`HostAllocator`].
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    ffi::c_void,
+    sync::OnceLock,
+};
+
+/// FFI compatible table of the host allocator functions, each is passed the `user_data`.
+///
+/// `alloc` returns null if the allocation failed, `align` is always a power of two.
+/// Without `realloc` a reallocation allocates, copies and deallocates.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct FfiAllocatorVTable {
+    pub alloc: unsafe extern "C" fn(user_data: *mut c_void, size: usize, align: usize) -> *mut u8,
+    pub dealloc:
+        unsafe extern "C" fn(user_data: *mut c_void, ptr: *mut u8, size: usize, align: usize),
+    pub realloc: Option<
+        unsafe extern "C" fn(
+            user_data: *mut c_void,
+            ptr: *mut u8,
+            old_size: usize,
+            align: usize,
+            new_size: usize,
+        ) -> *mut u8,
+    >,
+    pub user_data: *mut c_void,
+}
+
+struct VTable(FfiAllocatorVTable);
+
+// The host guarantees that `user_data` can be used from any thread (see `set_allocator`).
+unsafe impl Send for VTable {}
+unsafe impl Sync for VTable {}
+
+static VTABLE: OnceLock<VTable> = OnceLock::new();
+
+/// Registers the host allocator functions, used by [`HostAllocator`] for all allocations
+/// from now on. The allocator can be registered once, later calls return false.
+///
+/// # Safety
+///
+/// The functions must behave like [`GlobalAlloc`] (e.g. return memory of the requested
+/// size and alignment) and must be callable from any thread with the given `user_data`
+/// for the rest of the process.
+pub unsafe fn set_allocator(vtable: FfiAllocatorVTable) -> bool {
+    VTABLE.set(VTable(vtable)).is_ok()
+}
+
+/// Returns true if the host allocator functions are registered.
+pub fn is_host_allocator_set() -> bool {
+    VTABLE.get().is_some()
+}
+
+// Origin of an allocation, recorded in the last byte of its header.
+const ORIGIN_SYSTEM: u8 = 0;
+const ORIGIN_HOST: u8 = 1;
+
+// Returns the size of the header before an allocation with the given alignment,
+// a multiple of the alignment so the allocation stays aligned.
+fn header_size(align: usize) -> usize {
+    align.max(size_of::<usize>())
+}
+
+// Returns the layout of an allocation including its header, `None` if the size overflows.
+fn outer_layout(layout: Layout, new_size: usize) -> Option<Layout> {
+    let size = new_size.checked_add(header_size(layout.align()))?;
+    Layout::from_size_align(size, layout.align()).ok()
+}
+
+/// Global allocator forwarding to the host allocator functions (see [`set_allocator`]),
+/// or to the system allocator if none are registered.
+///
+/// Each allocation records the allocator it came from in a header in front of it
+/// (the alignment, at least the size of a `usize`), so it is always deallocated
+/// by the same allocator, even if the host functions were registered in between.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HostAllocator;
+
+unsafe impl GlobalAlloc for HostAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let Some(outer) = outer_layout(layout, layout.size()) else {
+            return std::ptr::null_mut();
+        };
+
+        let (origin, base) = match VTABLE.get() {
+            Some(VTable(vtable)) => (ORIGIN_HOST, unsafe {
+                (vtable.alloc)(vtable.user_data, outer.size(), outer.align())
+            }),
+            None => (ORIGIN_SYSTEM, unsafe { System.alloc(outer) }),
+        };
+        if base.is_null() {
+            return base;
+        }
+
+        unsafe {
+            let ptr = base.add(header_size(layout.align()));
+            ptr.sub(1).write(origin);
+            ptr
+        }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        let header_size = header_size(layout.align());
+        let base = unsafe { ptr.sub(header_size) };
+        let outer = unsafe {
+            Layout::from_size_align_unchecked(layout.size() + header_size, layout.align())
+        };
+
+        match (unsafe { ptr.sub(1).read() }, VTABLE.get()) {
+            (ORIGIN_HOST, Some(VTable(vtable))) => unsafe {
+                (vtable.dealloc)(vtable.user_data, base, outer.size(), outer.align())
+            },
+            _ => unsafe { System.dealloc(base, outer) },
+        }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let header_size = header_size(layout.align());
+        let Some(new_outer) = outer_layout(layout, new_size) else {
+            return std::ptr::null_mut();
+        };
+        let base = unsafe { ptr.sub(header_size) };
+        let outer = unsafe {
+            Layout::from_size_align_unchecked(layout.size() + header_size, layout.align())
+        };
+
+        // The header (with the origin) is moved along with the bytes.
+        let new_base = match (unsafe { ptr.sub(1).read() }, VTABLE.get()) {
+            (ORIGIN_SYSTEM, None) => unsafe { System.realloc(base, outer, new_outer.size()) },
+            (
+                ORIGIN_HOST,
+                Some(VTable(
+                    vtable @ FfiAllocatorVTable {
+                        realloc: Some(realloc),
+                        ..
+                    },
+                )),
+            ) => unsafe {
+                realloc(
+                    vtable.user_data,
+                    base,
+                    outer.size(),
+                    outer.align(),
+                    new_outer.size(),
+                )
+            },
+            _ => {
+                // Allocates (from the current allocator), copies and deallocates.
+                let new_layout =
+                    unsafe { Layout::from_size_align_unchecked(new_size, layout.align()) };
+                let new_ptr = unsafe { self.alloc(new_layout) };
+                if !new_ptr.is_null() {
+                    unsafe {
+                        new_ptr.copy_from_nonoverlapping(ptr, layout.size().min(new_size));
+                        self.dealloc(ptr, layout);
+                    }
+                }
+                return new_ptr;
+            }
+        };
+        if new_base.is_null() {
+            return new_base;
+        }
+
+        unsafe { new_base.add(header_size) }
+    }
+}
@@ -0,0 +1,167 @@
+//! Byte buffers allocated with a rust [`Allocator`] (e.g. jemalloc, mimalloc or an arena)
+//! instead of the global allocator, via the `allocator-api2` polyfill of `allocator_api`.
+//!
+//! The functions are the `..._in` variants of the crate root functions, a raw buffer must be
+//! converted back or freed with the allocator it was allocated with.
+
+use std::{alloc::Layout, ptr::NonNull};
+
+use allocator_api2::{alloc::Allocator, boxed::Box};
+
+use crate::{
+    audit::{self, FfiAuditEvent},
+    error::FfiBufferError,
+    oom, poison, stats,
+};
+
+/// Allocates a new zeroed boxed byte slice with the given `length` with the allocator.
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::CapacityOverflow`] if `length` exceeds `isize::MAX`,
+/// [`FfiBufferError::Alloc`] if the allocation failed.
+pub fn new_boxed_byte_slice_in<A: Allocator>(
+    length: usize,
+    alloc: A,
+) -> Result<Box<[u8], A>, FfiBufferError> {
+    if length == 0 {
+        let slice_raw = std::ptr::slice_from_raw_parts_mut(NonNull::<u8>::dangling().as_ptr(), 0);
+        return Ok(unsafe { Box::from_raw_in(slice_raw, alloc) });
+    }
+
+    let ptr = allocate(length, &alloc)?;
+    let slice_raw = std::ptr::slice_from_raw_parts_mut(ptr.as_ptr(), length);
+
+    Ok(unsafe { Box::from_raw_in(slice_raw, alloc) })
+}
+
+/// Allocates a new zeroed byte buffer with the given `length` with the allocator
+/// and returns the pointer to the buffer, see [`crate::new_boxed_byte_slice_buffer_raw`].
+///
+/// Note: Null is returned if `length` is 0, the size overflows or the allocation failed.
+///
+/// # Safety
+///
+/// Later at some point, after the buffer is filled, the buffer must be converted back with
+/// [`from_boxed_byte_slice_raw_in`] (or freed with [`free_boxed_byte_slice_raw_in`])
+/// with the same allocator.
+#[cfg_attr(any(feature = "debug-track", feature = "tracing"), track_caller)]
+pub fn new_boxed_byte_slice_buffer_raw_in<A: Allocator>(length: usize, alloc: &A) -> *mut u8 {
+    if length == 0 {
+        return std::ptr::null_mut();
+    }
+
+    let Ok(ptr) = allocate(length, alloc) else {
+        return std::ptr::null_mut();
+    };
+
+    stats::buffer_created(length);
+    audit::record(
+        FfiAuditEvent::Allocate,
+        ptr.as_ptr(),
+        length,
+        "new_boxed_byte_slice_buffer_raw_in",
+    );
+    #[cfg(feature = "debug-track")]
+    crate::track::issue(ptr.as_ptr(), length, std::panic::Location::caller());
+
+    ptr.as_ptr()
+}
+
+/// Converts the given boxed byte slice into its raw parts `(ptr, len)`, returned with its
+/// allocator, see [`crate::into_boxed_byte_slice_raw`].
+///
+/// The bytes must be converted back with [`from_boxed_byte_slice_raw_in`] (or freed with
+/// [`free_boxed_byte_slice_raw_in`]) with the returned allocator at some point.
+#[cfg_attr(any(feature = "debug-track", feature = "tracing"), track_caller)]
+pub fn into_boxed_byte_slice_raw_in<A: Allocator>(src: Box<[u8], A>) -> (*const u8, usize, A) {
+    let (slice_raw, alloc) = Box::into_raw_with_allocator(src);
+    let len = slice_raw.len();
+    if len == 0 {
+        return (std::ptr::null(), 0, alloc);
+    }
+
+    let ptr = slice_raw.cast::<u8>().cast_const();
+    stats::buffer_created(len);
+    audit::record(
+        FfiAuditEvent::Export,
+        ptr,
+        len,
+        "into_boxed_byte_slice_raw_in",
+    );
+    #[cfg(feature = "debug-track")]
+    crate::track::issue(ptr, len, std::panic::Location::caller());
+
+    (ptr, len, alloc)
+}
+
+/// Converts the given byte buffer back to a rust managed boxed byte slice of the allocator,
+/// see [`crate::from_boxed_byte_slice_raw`].
+///
+/// # Safety
+///
+/// The buffer must have been created with [`new_boxed_byte_slice_buffer_raw_in`]
+/// or [`into_boxed_byte_slice_raw_in`] with the given allocator (or one it is equivalent to)
+/// and the `length`, and must not be used afterwards.
+#[cfg_attr(any(feature = "debug-track", feature = "tracing"), track_caller)]
+pub unsafe fn from_boxed_byte_slice_raw_in<A: Allocator>(
+    slice_ptr: *mut u8,
+    length: usize,
+    alloc: A,
+) -> Box<[u8], A> {
+    if length == 0 || slice_ptr.is_null() {
+        return new_boxed_byte_slice_in(0, alloc).expect("empty slice doesn't allocate");
+    }
+
+    #[cfg(feature = "debug-track")]
+    crate::track::reclaim(slice_ptr, length);
+    stats::buffer_reclaimed(length);
+    audit::record(
+        FfiAuditEvent::Import,
+        slice_ptr,
+        length,
+        "from_boxed_byte_slice_raw_in",
+    );
+
+    let slice_raw = std::ptr::slice_from_raw_parts_mut(slice_ptr, length);
+    unsafe { Box::from_raw_in(slice_raw, alloc) }
+}
+
+/// Converts the given byte buffer back (see [`from_boxed_byte_slice_raw_in`]) and drops it,
+/// the bytes are poisoned before if enabled (see [`poison`]).
+///
+/// # Safety
+///
+/// See [`from_boxed_byte_slice_raw_in`].
+#[cfg_attr(any(feature = "debug-track", feature = "tracing"), track_caller)]
+pub unsafe fn free_boxed_byte_slice_raw_in<A: Allocator>(
+    slice_ptr: *mut u8,
+    length: usize,
+    alloc: &A,
+) {
+    let mut bytes = unsafe { from_boxed_byte_slice_raw_in(slice_ptr, length, alloc) };
+    if poison::is_enabled() {
+        // Volatile, so the writes are not optimized away right before the deallocation.
+        for byte in bytes.iter_mut() {
+            unsafe { std::ptr::write_volatile(byte, poison::POISON_BYTE) };
+        }
+    }
+}
+
+// Allocates `length` (non-zero) zeroed bytes with the allocator, consulting the out of memory
+// handler each time the allocation fails. Fails right away beyond the memory limit.
+fn allocate<A: Allocator>(length: usize, alloc: &A) -> Result<NonNull<u8>, FfiBufferError> {
+    let layout = Layout::array::<u8>(length).map_err(|_| FfiBufferError::CapacityOverflow)?;
+    if !stats::within_limit(length) {
+        stats::allocation_failed();
+        return Err(FfiBufferError::Alloc { len: length });
+    }
+
+    let ptr = oom::retry(layout, || {
+        alloc
+            .allocate_zeroed(layout)
+            .map_or(std::ptr::null_mut(), |ptr| ptr.cast::<u8>().as_ptr())
+    });
+
+    NonNull::new(ptr).ok_or(FfiBufferError::Alloc { len: length })
+}
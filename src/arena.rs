@@ -0,0 +1,144 @@
+//! Arena of transient buffers, e.g. the strings of one host callback, which are carved
+//! from larger blocks and invalidated together when the arena is reset.
+//!
+//! The buffers are handed out as views ([`FfiSliceMut`] and [`FfiSliceRef`]), the arena
+//! owns the bytes. C hosts use the arena through an opaque handle (`export` feature).
+
+use crate::{
+    FfiSliceMut, FfiSliceRef,
+    audit::{self, FfiAuditEvent},
+    error::FfiBufferError,
+    new_zeroed_boxed_byte_slice, stats,
+};
+
+/// Arena of transient buffers carved from blocks of a fixed minimum size.
+///
+/// Resetting the arena keeps its blocks for the next use, they are deallocated
+/// when the arena is dropped.
+#[derive(Debug)]
+pub struct Arena {
+    block_size: usize,
+    blocks: Vec<Box<[u8]>>,
+    // Index of the block carved from and the number of its carved bytes.
+    current: usize,
+    used: usize,
+    allocated: usize,
+}
+
+impl Arena {
+    /// Creates an empty arena, which allocates blocks of at least `block_size` bytes.
+    /// Nothing is allocated until the first buffer is.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FfiBufferError::InvalidArgument`] if `block_size` is 0.
+    pub fn new(block_size: usize) -> Result<Self, FfiBufferError> {
+        if block_size == 0 {
+            return Err(FfiBufferError::InvalidArgument("block size of 0"));
+        }
+
+        Ok(Self {
+            block_size,
+            blocks: Vec::new(),
+            current: 0,
+            used: 0,
+            allocated: 0,
+        })
+    }
+
+    /// Returns the number of bytes allocated since the arena was created or reset.
+    pub fn allocated(&self) -> usize {
+        self.allocated
+    }
+
+    /// Returns the sum of the sizes of all blocks.
+    pub fn capacity(&self) -> usize {
+        self.blocks.iter().map(|block| block.len()).sum()
+    }
+
+    /// Allocates a zeroed buffer of `len` bytes, valid until the arena is reset or dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FfiBufferError::Alloc`] if the allocation of a new block failed.
+    pub fn alloc(&mut self, len: usize) -> Result<FfiSliceMut, FfiBufferError> {
+        let bytes = self.carve(len)?;
+        bytes.fill(0);
+
+        Ok(FfiSliceMut::from_mut_slice(bytes))
+    }
+
+    /// Allocates a copy of the given bytes, valid until the arena is reset or dropped.
+    ///
+    /// # Errors
+    ///
+    /// See [`Arena::alloc`].
+    pub fn alloc_bytes(&mut self, src: &[u8]) -> Result<FfiSliceRef, FfiBufferError> {
+        let bytes = self.carve(src.len())?;
+        bytes.copy_from_slice(src);
+
+        Ok(FfiSliceRef::from_slice(bytes))
+    }
+
+    /// Allocates a copy of the given string as UTF-8 bytes, see [`Arena::alloc_bytes`].
+    ///
+    /// # Errors
+    ///
+    /// See [`Arena::alloc`].
+    pub fn alloc_str(&mut self, src: &str) -> Result<FfiSliceRef, FfiBufferError> {
+        self.alloc_bytes(src.as_bytes())
+    }
+
+    /// Invalidates all buffers allocated so far, their bytes are reused by later allocations.
+    pub fn reset(&mut self) {
+        self.current = 0;
+        self.used = 0;
+        self.allocated = 0;
+    }
+
+    // Returns the next `len` bytes of the current block, moving on to the next block
+    // (allocating one if needed) if the current one is too short.
+    fn carve(&mut self, len: usize) -> Result<&mut [u8], FfiBufferError> {
+        if len == 0 {
+            return Ok(&mut []);
+        }
+
+        while self
+            .blocks
+            .get(self.current)
+            .is_some_and(|block| block.len() - self.used < len)
+        {
+            self.current += 1;
+            self.used = 0;
+        }
+
+        if self.current == self.blocks.len() {
+            let block = new_zeroed_boxed_byte_slice(len.max(self.block_size))?;
+            stats::buffer_created(block.len());
+            audit::record(
+                FfiAuditEvent::Allocate,
+                block.as_ptr(),
+                block.len(),
+                "Arena",
+            );
+
+            self.blocks.push(block);
+            self.used = 0;
+        }
+
+        let start = self.used;
+        self.used += len;
+        self.allocated += len;
+
+        Ok(&mut self.blocks[self.current][start..start + len])
+    }
+}
+
+impl Drop for Arena {
+    fn drop(&mut self) {
+        for block in &self.blocks {
+            stats::buffer_reclaimed(block.len());
+            audit::record(FfiAuditEvent::Free, block.as_ptr(), block.len(), "Arena");
+        }
+    }
+}
@@ -0,0 +1,439 @@
+//! FFI compatible array of byte buffers.
+
+use crate::{
+    ByteBuffer,
+    error::FfiBufferError,
+    logging::{self, FfiLogLevel},
+    new_zeroed_boxed_byte_slice, poison,
+};
+
+/// FFI compatible array of [`ByteBuffer`] items.
+///
+/// The items either own their bytes, or - if `backing` is not empty - are
+/// views into the shared `backing` allocation (no item owns its bytes then).
+///
+/// An empty array is always represented by a null `ptr` and a `len` of 0.
+///
+/// Note: The array is not dropped - lifetime is not rust managed,
+/// it must be released with [`free_buffer_array_raw`] at some point.
+#[repr(C)]
+#[derive(Debug)]
+pub struct FfiBufferArray {
+    pub ptr: *mut ByteBuffer,
+    pub len: usize,
+    pub backing: ByteBuffer,
+}
+
+impl FfiBufferArray {
+    /// The canonical empty array, a null `ptr`, a `len` of 0 and an empty `backing`.
+    pub const EMPTY: Self = Self {
+        ptr: std::ptr::null_mut(),
+        len: 0,
+        backing: ByteBuffer::EMPTY,
+    };
+
+    /// Converts the given boxed byte slices into an array, where every item
+    /// owns its bytes.
+    pub fn from_boxed_slices(src: Vec<Box<[u8]>>) -> Self {
+        if src.is_empty() {
+            return Self::EMPTY;
+        }
+
+        let items: Box<[ByteBuffer]> = src.into_iter().map(ByteBuffer::from_boxed_slice).collect();
+
+        let len = items.len();
+        let ptr = Box::into_raw(items).cast::<ByteBuffer>();
+
+        Self {
+            ptr,
+            len,
+            backing: ByteBuffer::EMPTY,
+        }
+    }
+
+    /// Returns the items of the array.
+    ///
+    /// # Safety
+    ///
+    /// The array must be valid (not released) while the returned reference is used.
+    pub unsafe fn items(&self) -> &[ByteBuffer] {
+        if self.len == 0 {
+            return &[];
+        }
+
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
+
+    /// Converts the array back into boxed byte slices, releasing the array.
+    ///
+    /// Items owning their bytes are reclaimed without a copy, views into the `backing`
+    /// are copied (the `backing` is released afterwards).
+    ///
+    /// # Safety
+    ///
+    /// The array must have been created by this crate and must not be used afterwards.
+    pub unsafe fn into_boxed_slices(self) -> Vec<Box<[u8]>> {
+        if self.len == 0 {
+            return Vec::new();
+        }
+
+        if self.backing.len != 0 {
+            let copies = unsafe { self.items() }
+                .iter()
+                .map(|item| unsafe { item.as_slice() }.into())
+                .collect();
+            unsafe { free_buffer_array_raw(self) };
+            return copies;
+        }
+
+        let items_raw = std::ptr::slice_from_raw_parts_mut(self.ptr, self.len);
+        let items = unsafe { Box::from_raw(items_raw) };
+
+        items
+            .into_iter()
+            .map(|item| unsafe { item.into_boxed_slice() })
+            .collect()
+    }
+}
+
+impl Default for FfiBufferArray {
+    fn default() -> Self {
+        Self::EMPTY
+    }
+}
+
+impl From<Vec<Box<[u8]>>> for FfiBufferArray {
+    fn from(src: Vec<Box<[u8]>>) -> Self {
+        Self::from_boxed_slices(src)
+    }
+}
+
+impl From<Vec<Vec<u8>>> for FfiBufferArray {
+    fn from(src: Vec<Vec<u8>>) -> Self {
+        Self::from_boxed_slices(src.into_iter().map(Vec::into_boxed_slice).collect())
+    }
+}
+
+impl From<Vec<String>> for FfiBufferArray {
+    fn from(src: Vec<String>) -> Self {
+        Self::from_boxed_slices(
+            src.into_iter()
+                .map(|s| s.into_bytes().into_boxed_slice())
+                .collect(),
+        )
+    }
+}
+
+/// Converts the given strings (e.g. device names) into an array of UTF-8 string buffers
+/// and returns its raw parts `(ptr, len)`, see [`FfiBufferArray`].
+///
+/// The strings must be converted back with [`strings_from_raw`] or released with
+/// [`free_strings_raw`] at some point.
+pub fn strings_into_raw(src: Vec<String>) -> (*mut ByteBuffer, usize) {
+    let array = FfiBufferArray::from(src);
+    (array.ptr, array.len)
+}
+
+/// Converts the given array of string buffers back into strings, the array and all of its
+/// items are reclaimed in any case.
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::Utf8`] if an item is not valid UTF-8, e.g. if it was modified
+/// by the host.
+///
+/// # Safety
+///
+/// The array must have been created with [`strings_into_raw`] and must not be used afterwards.
+pub unsafe fn strings_from_raw(
+    ptr: *mut ByteBuffer,
+    len: usize,
+) -> Result<Vec<String>, FfiBufferError> {
+    if ptr.is_null() || len == 0 {
+        return Ok(Vec::new());
+    }
+
+    // All items are reclaimed before the conversion, so none leaks on an error.
+    let bytes = unsafe { byte_vecs_from_raw(ptr, len) };
+
+    bytes
+        .into_iter()
+        .map(|bytes| String::from_utf8(bytes).map_err(|e| e.utf8_error().into()))
+        .collect()
+}
+
+/// Releases the given array of string buffers, including all of its items.
+///
+/// # Safety
+///
+/// The array must have been created with [`strings_into_raw`] and must not be used afterwards.
+pub unsafe fn free_strings_raw(ptr: *mut ByteBuffer, len: usize) {
+    unsafe { free_byte_vecs_raw(ptr, len) };
+}
+
+/// Converts the given byte vectors (e.g. advertisement packets of a scan) into an array of
+/// byte buffers and returns its raw parts `(ptr, len)`, see [`FfiBufferArray`].
+///
+/// The byte vectors must be converted back with [`byte_vecs_from_raw`] or released with
+/// [`free_byte_vecs_raw`] at some point.
+pub fn byte_vecs_into_raw(src: Vec<Vec<u8>>) -> (*mut ByteBuffer, usize) {
+    let array = FfiBufferArray::from(src);
+    (array.ptr, array.len)
+}
+
+/// Converts the given array of byte buffers back into byte vectors,
+/// the array and all of its items are reclaimed.
+///
+/// # Safety
+///
+/// The array must have been created with [`byte_vecs_into_raw`] (or [`strings_into_raw`])
+/// and must not be used afterwards.
+pub unsafe fn byte_vecs_from_raw(ptr: *mut ByteBuffer, len: usize) -> Vec<Vec<u8>> {
+    if ptr.is_null() || len == 0 {
+        return Vec::new();
+    }
+
+    let array = FfiBufferArray {
+        ptr,
+        len,
+        backing: ByteBuffer::EMPTY,
+    };
+    unsafe { array.into_boxed_slices() }
+        .into_iter()
+        .map(Vec::from)
+        .collect()
+}
+
+/// Releases the given array of byte buffers, including all of its items.
+///
+/// # Safety
+///
+/// The array must have been created with [`byte_vecs_into_raw`] (or [`strings_into_raw`])
+/// and must not be used afterwards.
+pub unsafe fn free_byte_vecs_raw(ptr: *mut ByteBuffer, len: usize) {
+    if ptr.is_null() {
+        return;
+    }
+
+    let array = FfiBufferArray {
+        ptr,
+        len,
+        backing: ByteBuffer::EMPTY,
+    };
+    unsafe { free_buffer_array_raw(array) };
+}
+
+/// Splits the given boxed byte slice at every occurrence of `separator` into
+/// a buffer array, without copying any bytes.
+///
+/// The returned items are views into the given slice, which becomes the
+/// `backing` of the array. The split behaves like `slice::split`: a leading,
+/// trailing or repeated separator produces empty items.
+///
+/// If `src` is empty an empty array is returned, if `separator` is empty
+/// the array has exactly one item covering the whole `src`.
+///
+/// Note: The array must be released with [`free_buffer_array_raw`] at some point,
+/// the items must not be converted to boxed byte slices on their own.
+pub fn split_boxed_byte_slice_raw(src: Box<[u8]>, separator: &[u8]) -> FfiBufferArray {
+    if src.is_empty() {
+        return FfiBufferArray::EMPTY;
+    }
+
+    let ranges = split_ranges(&src, separator);
+    views_into_buffer_array(src, &ranges)
+}
+
+/// Size in bytes of the record count field of a joined buffer header.
+const JOINED_COUNT_SIZE: usize = 8;
+
+/// Size in bytes of one `(offset, len)` record entry of a joined buffer header.
+const JOINED_ENTRY_SIZE: usize = 16;
+
+/// Concatenates the items of the given buffer array into one self-describing
+/// buffer and releases the array.
+///
+/// The returned buffer starts with a header and is followed by the item bytes:
+/// - `u64` - record count
+/// - `count` times `(u64, u64)` - offset and length of each record, where the
+///   offset is relative to the end of the header
+///
+/// All header fields are little-endian, so the host can address the individual
+/// records while only holding one pointer. The header is present even if
+/// the array is empty.
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::CapacityOverflow`] if the joined length overflows,
+/// [`FfiBufferError::Alloc`] if the allocation failed. The array is released in any case.
+///
+/// # Safety
+///
+/// The array must have been created by this crate and must not be used afterwards.
+pub unsafe fn join_buffer_array_raw(array: FfiBufferArray) -> Result<ByteBuffer, FfiBufferError> {
+    let joined = join_items(unsafe { array.items() });
+    unsafe { free_buffer_array_raw(array) };
+
+    joined.map(ByteBuffer::from_boxed_slice)
+}
+
+// Concatenates the given items behind the header of a joined buffer.
+fn join_items(items: &[ByteBuffer]) -> Result<Box<[u8]>, FfiBufferError> {
+    let header_len = JOINED_ENTRY_SIZE
+        .checked_mul(items.len())
+        .and_then(|len| len.checked_add(JOINED_COUNT_SIZE))
+        .ok_or(FfiBufferError::CapacityOverflow)?;
+    let joined_len = items
+        .iter()
+        .try_fold(header_len, |len, item| len.checked_add(item.len))
+        .ok_or(FfiBufferError::CapacityOverflow)?;
+
+    let mut joined = new_zeroed_boxed_byte_slice(joined_len)?;
+    let (header, data) = joined.split_at_mut(header_len);
+    let (count, entries) = header.split_at_mut(JOINED_COUNT_SIZE);
+    count.copy_from_slice(&(items.len() as u64).to_le_bytes());
+
+    let mut offset = 0;
+    for (item, entry) in items
+        .iter()
+        .zip(entries.chunks_exact_mut(JOINED_ENTRY_SIZE))
+    {
+        entry[..8].copy_from_slice(&(offset as u64).to_le_bytes());
+        entry[8..].copy_from_slice(&(item.len as u64).to_le_bytes());
+        data[offset..offset + item.len].copy_from_slice(unsafe { item.as_slice() });
+        offset += item.len;
+    }
+
+    Ok(joined)
+}
+
+/// Splits the given joined buffer (see [`join_buffer_array_raw`]) back into
+/// a buffer array, without copying any bytes.
+///
+/// The returned items are views into the given slice, which becomes the
+/// `backing` of the array.
+///
+/// Returns `None` if the header is malformed or a record lies outside of the slice,
+/// the given slice is dropped then.
+///
+/// Note: The array must be released with [`free_buffer_array_raw`] at some point,
+/// the items must not be converted to boxed byte slices on their own.
+pub fn split_joined_boxed_byte_slice_raw(src: Box<[u8]>) -> Option<FfiBufferArray> {
+    let Some(ranges) = joined_ranges(&src) else {
+        logging::log(FfiLogLevel::Warn, "malformed joined buffer header");
+        return None;
+    };
+
+    if ranges.is_empty() {
+        return Some(FfiBufferArray::EMPTY);
+    }
+
+    Some(views_into_buffer_array(src, &ranges))
+}
+
+/// Releases the given buffer array, including all of its items.
+///
+/// # Safety
+///
+/// The array must have been created by this crate and must not be used afterwards.
+pub unsafe fn free_buffer_array_raw(array: FfiBufferArray) {
+    if array.len == 0 {
+        return;
+    }
+
+    let items_raw = std::ptr::slice_from_raw_parts_mut(array.ptr, array.len);
+    let items = unsafe { Box::from_raw(items_raw) };
+
+    if array.backing.len != 0 {
+        poison::drop_poisoned(unsafe { array.backing.into_boxed_slice() });
+        return;
+    }
+
+    for item in items {
+        poison::drop_poisoned(unsafe { item.into_boxed_slice() });
+    }
+}
+
+// Builds an array with `src` as backing and views for the given `(start, len)` ranges.
+fn views_into_buffer_array(src: Box<[u8]>, ranges: &[(usize, usize)]) -> FfiBufferArray {
+    let backing = ByteBuffer::from_boxed_slice(src);
+
+    let items: Box<[ByteBuffer]> = ranges
+        .iter()
+        .map(|&(start, len)| {
+            if len == 0 {
+                return ByteBuffer::EMPTY;
+            }
+
+            ByteBuffer {
+                ptr: unsafe { backing.ptr.add(start) },
+                len,
+            }
+        })
+        .collect();
+
+    let len = items.len();
+    let ptr = Box::into_raw(items).cast::<ByteBuffer>();
+
+    FfiBufferArray { ptr, len, backing }
+}
+
+// Returns the `(start, len)` ranges of the records of a joined buffer,
+// relative to the start of the joined buffer.
+fn joined_ranges(src: &[u8]) -> Option<Vec<(usize, usize)>> {
+    let read_u64 = |at: usize| -> Option<usize> {
+        let bytes = src.get(at..at + 8)?;
+        usize::try_from(u64::from_le_bytes(bytes.try_into().ok()?)).ok()
+    };
+
+    let count = read_u64(0)?;
+    let header_len = count
+        .checked_mul(JOINED_ENTRY_SIZE)?
+        .checked_add(JOINED_COUNT_SIZE)?;
+
+    if header_len > src.len() {
+        return None;
+    }
+
+    let data_len = src.len() - header_len;
+
+    (0..count)
+        .map(|i| {
+            let entry = JOINED_COUNT_SIZE + JOINED_ENTRY_SIZE * i;
+            let offset = read_u64(entry)?;
+            let len = read_u64(entry + 8)?;
+
+            if offset.checked_add(len)? > data_len {
+                return None;
+            }
+
+            Some((header_len + offset, len))
+        })
+        .collect()
+}
+
+// Returns the `(start, len)` ranges of the items between the separators.
+fn split_ranges(src: &[u8], separator: &[u8]) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+
+    if separator.is_empty() {
+        ranges.push((0, src.len()));
+        return ranges;
+    }
+
+    let mut start = 0;
+    let mut i = 0;
+
+    while i + separator.len() <= src.len() {
+        if src[i..].starts_with(separator) {
+            ranges.push((start, i - start));
+            i += separator.len();
+            start = i;
+        } else {
+            i += 1;
+        }
+    }
+
+    ranges.push((start, src.len() - start));
+    ranges
+}
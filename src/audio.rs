@@ -0,0 +1,71 @@
+//! Conversion between interleaved (`L R L R ...`) and planar (`L L ... R R ...`)
+//! multi-channel audio sample layouts.
+//!
+//! Samples are treated as opaque groups of `sample_width` bytes, so any sample
+//! format (e.g. 16 bit PCM, 32 bit float) is supported.
+
+use crate::ByteBuffer;
+
+/// Converts the interleaved samples of `src` into planar samples written to `dst`.
+///
+/// Returns false (and leaves `dst` untouched) if `channels` or `sample_width` is 0,
+/// `src` is not made of whole frames or `dst` does not have the length of `src`.
+pub fn deinterleave_into(src: &[u8], dst: &mut [u8], channels: usize, sample_width: usize) -> bool {
+    let Some(frames) = frame_count(src, dst, channels, sample_width) else {
+        return false;
+    };
+
+    for (frame, samples) in src.chunks_exact(channels * sample_width).enumerate() {
+        for (channel, sample) in samples.chunks_exact(sample_width).enumerate() {
+            let offset = (channel * frames + frame) * sample_width;
+            dst[offset..offset + sample_width].copy_from_slice(sample);
+        }
+    }
+
+    true
+}
+
+/// Converts the planar samples of `src` into interleaved samples written to `dst`.
+///
+/// Returns false (and leaves `dst` untouched) if `channels` or `sample_width` is 0,
+/// `src` is not made of whole frames or `dst` does not have the length of `src`.
+pub fn interleave_into(src: &[u8], dst: &mut [u8], channels: usize, sample_width: usize) -> bool {
+    let Some(frames) = frame_count(src, dst, channels, sample_width) else {
+        return false;
+    };
+
+    for (frame, samples) in dst.chunks_exact_mut(channels * sample_width).enumerate() {
+        for (channel, sample) in samples.chunks_exact_mut(sample_width).enumerate() {
+            let offset = (channel * frames + frame) * sample_width;
+            sample.copy_from_slice(&src[offset..offset + sample_width]);
+        }
+    }
+
+    true
+}
+
+/// Returns the interleaved samples of `src` as new byte buffer with planar samples,
+/// `None` if the arguments are invalid (see [`deinterleave_into`]).
+pub fn deinterleave(src: &[u8], channels: usize, sample_width: usize) -> Option<ByteBuffer> {
+    let mut dst = vec![0; src.len()].into_boxed_slice();
+    deinterleave_into(src, &mut dst, channels, sample_width)
+        .then(|| ByteBuffer::from_boxed_slice(dst))
+}
+
+/// Returns the planar samples of `src` as new byte buffer with interleaved samples,
+/// `None` if the arguments are invalid (see [`interleave_into`]).
+pub fn interleave(src: &[u8], channels: usize, sample_width: usize) -> Option<ByteBuffer> {
+    let mut dst = vec![0; src.len()].into_boxed_slice();
+    interleave_into(src, &mut dst, channels, sample_width)
+        .then(|| ByteBuffer::from_boxed_slice(dst))
+}
+
+// Returns the number of frames (samples per channel), `None` if the arguments are invalid.
+fn frame_count(src: &[u8], dst: &[u8], channels: usize, sample_width: usize) -> Option<usize> {
+    let frame_width = channels.checked_mul(sample_width).filter(|&w| w != 0)?;
+    if !src.len().is_multiple_of(frame_width) || src.len() != dst.len() {
+        return None;
+    }
+
+    Some(src.len() / frame_width)
+}
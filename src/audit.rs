@@ -0,0 +1,155 @@
+//! Bounded audit trail of the buffers crossing the FFI boundary, for post-mortem analysis
+//! of host crashes. Disabled by default, see [`enable`].
+
+use std::{
+    collections::VecDeque,
+    sync::{
+        Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{ByteBuffer, checked, watchdog};
+
+/// FFI compatible event kind of an audit entry.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FfiAuditEvent {
+    /// A buffer was allocated for the host to be filled.
+    Allocate = 1,
+    /// A buffer was handed out to the host.
+    Export = 2,
+    /// A buffer was converted back from the host.
+    Import = 3,
+    /// A buffer was freed without being converted back.
+    Free = 4,
+}
+
+/// Entry of the audit trail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuditEntry {
+    /// Nanoseconds since the unix epoch.
+    pub timestamp: u64,
+    pub event: FfiAuditEvent,
+    pub ptr: usize,
+    pub len: usize,
+    /// Name of the function which recorded the entry.
+    pub label: &'static str,
+}
+
+struct Log {
+    entries: VecDeque<AuditEntry>,
+    capacity: usize,
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static LOG: Mutex<Log> = Mutex::new(Log {
+    entries: VecDeque::new(),
+    capacity: 0,
+});
+
+/// Enables the audit trail, which keeps the last `capacity` entries - 0 disables it again.
+///
+/// Already recorded entries beyond the new capacity are discarded (oldest first).
+pub fn enable(capacity: usize) {
+    let mut log = LOG.lock().unwrap_or_else(|e| e.into_inner());
+    log.capacity = capacity;
+    while log.entries.len() > capacity {
+        log.entries.pop_front();
+    }
+
+    ENABLED.store(capacity > 0, Ordering::Relaxed);
+}
+
+/// Returns the recorded entries, oldest first.
+pub fn entries() -> Vec<AuditEntry> {
+    LOG.lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .entries
+        .iter()
+        .copied()
+        .collect()
+}
+
+/// Returns the recorded entries serialized as byte buffer, to be passed to the host.
+///
+/// All integers are little endian: a `u64` entry count, followed by each entry as
+/// `u64` timestamp, `i32` event, `u64` ptr, `u64` len, `u32` label length and the UTF-8 label.
+pub fn serialize() -> ByteBuffer {
+    let entries = entries();
+
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&(entries.len() as u64).to_le_bytes());
+    for entry in entries {
+        bytes.extend_from_slice(&entry.timestamp.to_le_bytes());
+        bytes.extend_from_slice(&(entry.event as i32).to_le_bytes());
+        bytes.extend_from_slice(&(entry.ptr as u64).to_le_bytes());
+        bytes.extend_from_slice(&(entry.len as u64).to_le_bytes());
+        bytes.extend_from_slice(&(entry.label.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(entry.label.as_bytes());
+    }
+
+    ByteBuffer::from_boxed_slice(bytes.into_boxed_slice())
+}
+
+// Records a lifecycle event of a buffer, which is also observed by the watchdog and the
+// integrity checks (and emitted as `tracing` event with the `tracing` feature).
+#[cfg_attr(feature = "tracing", track_caller)]
+pub(crate) fn record(event: FfiAuditEvent, ptr: *const u8, len: usize, label: &'static str) {
+    watchdog::observe(event, ptr, len, label);
+    checked::observe(event, ptr);
+    #[cfg(feature = "tracing")]
+    trace(event, ptr, len, label, std::panic::Location::caller());
+
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+
+    let mut log = LOG.lock().unwrap_or_else(|e| e.into_inner());
+    if log.capacity == 0 {
+        return;
+    }
+    if log.entries.len() == log.capacity {
+        log.entries.pop_front();
+    }
+
+    log.entries.push_back(AuditEntry {
+        timestamp,
+        event,
+        ptr: ptr as usize,
+        len,
+        label,
+    });
+}
+
+// Emits the lifecycle event as `tracing` event, `location` is the caller of the public function
+// if it is `track_caller`.
+#[cfg(feature = "tracing")]
+fn trace(
+    event: FfiAuditEvent,
+    ptr: *const u8,
+    len: usize,
+    label: &'static str,
+    location: &std::panic::Location<'_>,
+) {
+    let message = match event {
+        FfiAuditEvent::Allocate => "allocate",
+        FfiAuditEvent::Export => "hand-off",
+        FfiAuditEvent::Import => "reclaim",
+        FfiAuditEvent::Free => "free",
+    };
+
+    tracing::trace!(
+        target: "ffi_byte_buffer",
+        ptr = ?ptr,
+        len,
+        label,
+        location = %location,
+        "{message}",
+    );
+}
@@ -0,0 +1,117 @@
+//! Stride aware copies of rectangles (blits) between 2D byte layouts, e.g. image frames.
+//!
+//! A rectangle is `height` rows of `width` bytes, the rows of a layout start
+//! `stride` bytes apart. All arguments are validated, so no copy reads or writes out of bounds.
+
+/// Copies the rectangle of `width` x `height` bytes from the start of `src` to the start of `dst`.
+///
+/// Returns false (and copies nothing) if a row is wider than its stride
+/// or a layout is too short for the rectangle.
+pub fn copy_rect(
+    src: &[u8],
+    src_stride: usize,
+    dst: &mut [u8],
+    dst_stride: usize,
+    width: usize,
+    height: usize,
+) -> bool {
+    match (
+        rect_len(src_stride, width, height),
+        rect_len(dst_stride, width, height),
+    ) {
+        (Some(src_len), Some(dst_len)) if src_len <= src.len() && dst_len <= dst.len() => {}
+        _ => return false,
+    }
+
+    for row in 0..height {
+        let src_row = &src[row * src_stride..][..width];
+        dst[row * dst_stride..][..width].copy_from_slice(src_row);
+    }
+
+    true
+}
+
+/// Copies the rectangle from the given foreign layout into `dst`, see [`copy_rect`].
+///
+/// # Safety
+///
+/// The foreign layout must be valid (not deallocated) for reads of
+/// `src_stride * (height - 1) + width` bytes while this function is in process.
+/// A null pointer is only valid with an empty rectangle.
+pub unsafe fn copy_rect_from_foreign(
+    src_ptr: *const u8,
+    src_stride: usize,
+    dst: &mut [u8],
+    dst_stride: usize,
+    width: usize,
+    height: usize,
+) -> bool {
+    let Some(src) = (unsafe { foreign_layout(src_ptr, src_stride, width, height) }) else {
+        return false;
+    };
+
+    copy_rect(src, src_stride, dst, dst_stride, width, height)
+}
+
+/// Copies the rectangle from `src` into the given foreign layout, see [`copy_rect`].
+///
+/// # Safety
+///
+/// The foreign layout must be valid (not deallocated) for writes of
+/// `dst_stride * (height - 1) + width` bytes while this function is in process.
+/// A null pointer is only valid with an empty rectangle.
+pub unsafe fn copy_rect_into_foreign(
+    src: &[u8],
+    src_stride: usize,
+    dst_ptr: *mut u8,
+    dst_stride: usize,
+    width: usize,
+    height: usize,
+) -> bool {
+    let Some(len) = rect_len(dst_stride, width, height) else {
+        return false;
+    };
+    if len == 0 {
+        return true;
+    }
+    if dst_ptr.is_null() {
+        return false;
+    }
+
+    let dst = unsafe { std::slice::from_raw_parts_mut(dst_ptr, len) };
+    copy_rect(src, src_stride, dst, dst_stride, width, height)
+}
+
+// Returns the foreign layout of the rectangle as slice, `None` if invalid.
+unsafe fn foreign_layout<'a>(
+    ptr: *const u8,
+    stride: usize,
+    width: usize,
+    height: usize,
+) -> Option<&'a [u8]> {
+    let len = rect_len(stride, width, height)?;
+    if len == 0 {
+        return Some(&[]);
+    }
+    if ptr.is_null() {
+        return None;
+    }
+
+    Some(unsafe { std::slice::from_raw_parts(ptr, len) })
+}
+
+// Returns the number of bytes spanned by the rectangle in a layout with the given stride,
+// `None` if a row is wider than the stride or the length overflows.
+fn rect_len(stride: usize, width: usize, height: usize) -> Option<usize> {
+    if width == 0 || height == 0 {
+        return Some(0);
+    }
+    if height > 1 && width > stride {
+        return None;
+    }
+
+    stride
+        .checked_mul(height - 1)?
+        .checked_add(width)
+        .filter(|&len| len <= isize::MAX as usize)
+}
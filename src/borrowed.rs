@@ -0,0 +1,329 @@
+//! Borrowed access to C-Bytes, received and owned from C (e.g. Bluetooth mac address
+//! or uuid bytes).
+//!
+//! Note: The given C-Bytes are not deallocated or dropped in any form, that must be
+//! done by the owning C side.
+
+use crate::error::FfiBufferError;
+
+/// Returns a rust byte slice representation of the given C-Bytes, received and owned from C.
+///
+/// # Arguments
+/// - `c_bytes_ptr` - pointer to the C-Bytes
+/// - `c_bytes_len` - length of the C-Bytes
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::InvalidArgument`] if the pointer is null with a length,
+/// [`FfiBufferError::CapacityOverflow`] if the length exceeds `isize::MAX`.
+///
+/// # Safety
+///
+/// The given C-Bytes must be valid (not deallocated from the owning C side)
+/// while the returned reference is used.
+pub unsafe fn c_bytes_as_slice_ref<'a>(
+    c_bytes_ptr: *const u8,
+    c_bytes_len: usize,
+) -> Result<&'a [u8], FfiBufferError> {
+    if c_bytes_len == 0 {
+        return Ok(&[]);
+    }
+    if c_bytes_ptr.is_null() {
+        return Err(FfiBufferError::InvalidArgument(
+            "null pointer with a length",
+        ));
+    }
+    if c_bytes_len > isize::MAX as usize {
+        return Err(FfiBufferError::CapacityOverflow);
+    }
+
+    Ok(unsafe { std::slice::from_raw_parts(c_bytes_ptr, c_bytes_len) })
+}
+
+/// Returns a mutable rust byte slice representation of the given C-Bytes, received and
+/// owned from C, e.g. an output buffer for rust to fill.
+///
+/// # Arguments
+/// - `c_bytes_ptr` - pointer to the C-Bytes
+/// - `c_bytes_len` - length of the C-Bytes
+///
+/// # Errors
+///
+/// See [`c_bytes_as_slice_ref`].
+///
+/// # Safety
+///
+/// The given C-Bytes must be valid (not deallocated from the owning C side)
+/// while the returned reference is used. The bytes must not be accessed other than
+/// through the returned reference meanwhile, neither from C nor through another reference
+/// (ranges of the same C-Bytes which don't overlap may be borrowed at the same time).
+pub unsafe fn c_bytes_as_slice_mut<'a>(
+    c_bytes_ptr: *mut u8,
+    c_bytes_len: usize,
+) -> Result<&'a mut [u8], FfiBufferError> {
+    if c_bytes_len == 0 {
+        return Ok(&mut []);
+    }
+    if c_bytes_ptr.is_null() {
+        return Err(FfiBufferError::InvalidArgument(
+            "null pointer with a length",
+        ));
+    }
+    if c_bytes_len > isize::MAX as usize {
+        return Err(FfiBufferError::CapacityOverflow);
+    }
+
+    Ok(unsafe { std::slice::from_raw_parts_mut(c_bytes_ptr, c_bytes_len) })
+}
+
+/// Returns a rust string slice representation of the given C-Bytes, received and owned from C.
+///
+/// # Arguments
+/// - `c_bytes_ptr` - pointer to the C-Bytes
+/// - `c_bytes_len` - length of the C-Bytes
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::Utf8`] if the bytes are not valid UTF-8,
+/// see [`c_bytes_as_slice_ref`] for the other errors.
+///
+/// # Safety
+///
+/// The given C-Bytes must be valid (not deallocated from the owning C side)
+/// while the returned reference is used.
+pub unsafe fn c_bytes_as_str_ref<'a>(
+    c_bytes_ptr: *const u8,
+    c_bytes_len: usize,
+) -> Result<&'a str, FfiBufferError> {
+    let bytes = unsafe { c_bytes_as_slice_ref(c_bytes_ptr, c_bytes_len) }?;
+    Ok(std::str::from_utf8(bytes)?)
+}
+
+/// Returns a new rust string from the given C-Bytes, received and owned from C.
+///
+/// # Arguments
+/// - `c_bytes_ptr` - pointer to the C-Bytes
+/// - `c_bytes_len` - length of the C-Bytes
+///
+/// # Errors
+///
+/// See [`c_bytes_as_str_ref`].
+///
+/// # Safety
+///
+/// The given C-Bytes must be valid (not deallocated from the owning C side)
+/// while this function is in process of creating the rust string.
+pub unsafe fn c_bytes_to_string(
+    c_bytes_ptr: *const u8,
+    c_bytes_len: usize,
+) -> Result<String, FfiBufferError> {
+    unsafe { c_bytes_as_str_ref(c_bytes_ptr, c_bytes_len) }.map(str::to_string)
+}
+
+/// Returns a new `[u8; N]` byte array from the first `N` of the given C-Bytes,
+/// received and owned from C. Further bytes beyond `N` are ignored if present.
+///
+/// # Arguments
+/// - `c_bytes_ptr` - pointer to the C-Bytes
+/// - `c_bytes_len` - length of the C-Bytes
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::OutOfBounds`] if the C-Bytes have a length less than `N`,
+/// see [`c_bytes_as_slice_ref`] for the other errors.
+///
+/// # Safety
+///
+/// The given C-Bytes must be valid (not deallocated from the owning C side)
+/// while this function is in process of creating the rust array.
+pub unsafe fn try_c_bytes_to_array<const N: usize>(
+    c_bytes_ptr: *const u8,
+    c_bytes_len: usize,
+) -> Result<[u8; N], FfiBufferError> {
+    let bytes = unsafe { c_bytes_as_slice_ref(c_bytes_ptr, c_bytes_len) }?;
+
+    match bytes.first_chunk::<N>() {
+        Some(array) => Ok(*array),
+        None => Err(FfiBufferError::OutOfBounds {
+            index: N.saturating_sub(1),
+            len: bytes.len(),
+        }),
+    }
+}
+
+/// Returns a new `[u8; N]` byte array from the first `N` of the given C-Bytes,
+/// received and owned from C, e.g. for 4, 8, 20 or 32 byte identifiers.
+/// Further bytes beyond `N` are ignored if present.
+///
+/// # Arguments
+/// - `c_bytes_ptr` - pointer to the C-Bytes
+/// - `c_bytes_len` - length of the C-Bytes
+///
+/// # Panics
+///
+/// This function will panic if the C-Bytes are invalid or have a length less than `N`,
+/// see [`try_c_bytes_to_array`] for the checked variant.
+///
+/// # Safety
+///
+/// The given C-Bytes must be valid (not deallocated from the owning C side)
+/// while this function is in process of creating the rust array.
+pub unsafe fn c_bytes_to_array<const N: usize>(
+    c_bytes_ptr: *const u8,
+    c_bytes_len: usize,
+) -> [u8; N] {
+    unsafe { try_c_bytes_to_array(c_bytes_ptr, c_bytes_len) }
+        .unwrap_or_else(|e| panic!("invalid C-Bytes for a {N} byte array: {e}"))
+}
+
+/// Returns a new `[u8; 6]` byte array from the given C-Bytes, received and owned from C.
+///
+/// Use cases are where mac address bytes (length of 6) are received from C, like:
+/// - `BTAddress` (Android - mac address type)
+/// - `BTSerial`
+///
+/// # Errors
+///
+/// See [`try_c_bytes_to_array`].
+///
+/// # Safety
+///
+/// See [`try_c_bytes_to_array`].
+pub unsafe fn c_bytes_to_6_bytes_cap_array(
+    c_bytes_ptr: *const u8,
+    c_bytes_len: usize,
+) -> Result<[u8; 6], FfiBufferError> {
+    unsafe { try_c_bytes_to_array(c_bytes_ptr, c_bytes_len) }
+}
+
+/// Returns a new `[u8; 16]` byte array from the given C-Bytes, received and owned from C.
+///
+/// Use cases are where uuid bytes (length of 16) are received from C, like:
+/// - `BTAddress` (IOS - uuid type)
+/// - `BTUuid`
+///
+/// # Errors
+///
+/// See [`try_c_bytes_to_array`].
+///
+/// # Safety
+///
+/// See [`try_c_bytes_to_array`].
+pub unsafe fn c_bytes_to_16_bytes_cap_array(
+    c_bytes_ptr: *const u8,
+    c_bytes_len: usize,
+) -> Result<[u8; 16], FfiBufferError> {
+    unsafe { try_c_bytes_to_array(c_bytes_ptr, c_bytes_len) }
+}
+
+// The tests only use rust owned memory, so they run under Miri (`cargo +nightly miri test
+// borrowed`), which checks the raw pointer accesses and the aliasing of the references.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn null_pointer_without_length_is_empty() {
+        let bytes = unsafe { c_bytes_as_slice_ref(std::ptr::null(), 0) }.unwrap();
+        assert!(bytes.is_empty());
+        let bytes = unsafe { c_bytes_as_slice_mut(std::ptr::null_mut(), 0) }.unwrap();
+        assert!(bytes.is_empty());
+        let string = unsafe { c_bytes_to_string(std::ptr::null(), 0) }.unwrap();
+        assert!(string.is_empty());
+    }
+
+    #[test]
+    fn null_pointer_with_length_fails() {
+        let result = unsafe { c_bytes_as_slice_ref(std::ptr::null(), 6) };
+        assert!(matches!(result, Err(FfiBufferError::InvalidArgument(_))));
+        let result = unsafe { c_bytes_as_slice_mut(std::ptr::null_mut(), 6) };
+        assert!(matches!(result, Err(FfiBufferError::InvalidArgument(_))));
+        let result = unsafe { c_bytes_to_6_bytes_cap_array(std::ptr::null(), 6) };
+        assert!(matches!(result, Err(FfiBufferError::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn length_beyond_isize_max_fails() {
+        // Not dereferenced, the length is rejected first.
+        let ptr = std::ptr::NonNull::<u8>::dangling().as_ptr();
+        let result = unsafe { c_bytes_as_slice_ref(ptr, isize::MAX as usize + 1) };
+        assert!(matches!(result, Err(FfiBufferError::CapacityOverflow)));
+        let result = unsafe { c_bytes_as_slice_mut(ptr, usize::MAX) };
+        assert!(matches!(result, Err(FfiBufferError::CapacityOverflow)));
+    }
+
+    #[test]
+    fn invalid_utf8_fails() {
+        let bytes = [0x66, 0xff, 0x6f];
+        let result = unsafe { c_bytes_as_str_ref(bytes.as_ptr(), bytes.len()) };
+        assert!(matches!(result, Err(FfiBufferError::Utf8(_))));
+    }
+
+    #[test]
+    fn array_length_mismatch() {
+        let mac = [0x00, 0x1a, 0x7d, 0xda, 0x71, 0x13];
+
+        let array = unsafe { c_bytes_to_6_bytes_cap_array(mac.as_ptr(), mac.len()) }.unwrap();
+        assert_eq!(array, mac);
+        // Further bytes are ignored.
+        let array = unsafe { c_bytes_to_array::<4>(mac.as_ptr(), mac.len()) };
+        assert_eq!(array, [0x00, 0x1a, 0x7d, 0xda]);
+
+        let result = unsafe { try_c_bytes_to_array::<6>(mac.as_ptr(), 5) };
+        assert!(matches!(
+            result,
+            Err(FfiBufferError::OutOfBounds { index: 5, len: 5 })
+        ));
+        let result = unsafe { c_bytes_to_16_bytes_cap_array(mac.as_ptr(), mac.len()) };
+        assert!(matches!(
+            result,
+            Err(FfiBufferError::OutOfBounds { index: 15, len: 6 })
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid C-Bytes for a 8 byte array")]
+    fn array_length_mismatch_panics() {
+        let id = [0u8; 4];
+        let _ = unsafe { c_bytes_to_array::<8>(id.as_ptr(), id.len()) };
+    }
+
+    #[test]
+    fn mut_slice_writes_through() {
+        let mut bytes = [0u8; 4];
+        let ptr = bytes.as_mut_ptr();
+
+        let slice = unsafe { c_bytes_as_slice_mut(ptr, 4) }.unwrap();
+        slice.copy_from_slice(&[1, 2, 3, 4]);
+        // The owner accesses the bytes again only after the last use of the slice.
+        assert_eq!(unsafe { ptr.add(3).read() }, 4);
+        assert_eq!(bytes, [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn mut_slices_of_disjoint_ranges() {
+        let mut bytes = [0u8; 6];
+        let ptr = bytes.as_mut_ptr();
+
+        let head = unsafe { c_bytes_as_slice_mut(ptr, 2) }.unwrap();
+        let tail = unsafe { c_bytes_as_slice_mut(ptr.add(2), 4) }.unwrap();
+        head.fill(0xaa);
+        tail.fill(0xbb);
+        head[1] = 0xcc;
+
+        assert_eq!(bytes, [0xaa, 0xcc, 0xbb, 0xbb, 0xbb, 0xbb]);
+    }
+
+    #[test]
+    fn shared_slices_after_mut_slice() {
+        let mut bytes = *b"uuid";
+        let ptr = bytes.as_mut_ptr();
+
+        unsafe { c_bytes_as_slice_mut(ptr, 4) }.unwrap()[0] = b'U';
+        // Any number of shared slices once the mutable slice is gone.
+        let a = unsafe { c_bytes_as_slice_ref(ptr, 4) }.unwrap();
+        let b = unsafe { c_bytes_as_str_ref(ptr, 4) }.unwrap();
+        assert_eq!(a, b"Uuid");
+        assert_eq!(b, "Uuid");
+    }
+}
@@ -0,0 +1,276 @@
+//! FFI compatible representation of a boxed byte slice.
+
+use std::{borrow::Borrow, mem::ManuallyDrop, ops::Deref, str::Utf8Error};
+
+use crate::{
+    audit::{self, FfiAuditEvent},
+    checked, stats,
+};
+
+/// FFI compatible representation of a boxed byte slice `Box<[u8]>`.
+///
+/// The layout is shared with the C side (see `include/ffi_byte_buffer.h`), so the buffer
+/// can be passed around as one value instead of separate pointer and length arguments.
+///
+/// An empty buffer is always represented by a null `ptr` and a `len` of 0.
+///
+/// Note: The buffer does not drop its bytes - lifetime is not rust managed,
+/// it must be converted back with [`ByteBuffer::into_boxed_slice`] at some point.
+#[repr(C)]
+#[derive(Debug)]
+pub struct ByteBuffer {
+    pub ptr: *mut u8,
+    pub len: usize,
+}
+
+impl ByteBuffer {
+    /// The canonical empty buffer, a null `ptr` and a `len` of 0.
+    pub const EMPTY: Self = Self {
+        ptr: std::ptr::null_mut(),
+        len: 0,
+    };
+
+    /// Converts the given boxed byte slice into a byte buffer, handed to the host read-only
+    /// (its checksum is recorded if enabled, see [`crate::checked`]).
+    ///
+    /// The bytes will not be dropped until the buffer is converted back with
+    /// [`ByteBuffer::into_boxed_slice`].
+    #[cfg_attr(any(feature = "debug-track", feature = "tracing"), track_caller)]
+    pub fn from_boxed_slice(src: Box<[u8]>) -> Self {
+        let buffer = Self::from_boxed_slice_writable(src);
+        checked::tag(buffer.ptr, buffer.len);
+
+        buffer
+    }
+
+    // Converts the given boxed byte slice into a byte buffer, e.g. for the host to be filled,
+    // which is not checked.
+    #[cfg_attr(any(feature = "debug-track", feature = "tracing"), track_caller)]
+    pub(crate) fn from_boxed_slice_writable(src: Box<[u8]>) -> Self {
+        if src.is_empty() {
+            return Self::EMPTY;
+        }
+
+        let mut src = ManuallyDrop::new(src);
+        stats::buffer_created(src.len());
+        audit::record(
+            FfiAuditEvent::Export,
+            src.as_ptr(),
+            src.len(),
+            "ByteBuffer::from_boxed_slice",
+        );
+        #[cfg(feature = "debug-track")]
+        crate::track::issue(src.as_ptr(), src.len(), std::panic::Location::caller());
+
+        Self {
+            ptr: src.as_mut_ptr(),
+            len: src.len(),
+        }
+    }
+
+    /// Creates a byte buffer from the given raw parts, e.g. received from the host.
+    ///
+    /// Note: A null `ptr` or a `len` of 0 results in the canonical empty buffer.
+    pub fn from_raw(ptr: *mut u8, len: usize) -> Self {
+        if ptr.is_null() || len == 0 {
+            return Self::EMPTY;
+        }
+
+        Self { ptr, len }
+    }
+
+    /// Returns the raw parts `(ptr, len)` of the byte buffer, e.g. to be passed as
+    /// separate arguments.
+    pub fn into_raw(self) -> (*mut u8, usize) {
+        (self.ptr, self.len)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the bytes of the buffer.
+    ///
+    /// # Safety
+    ///
+    /// The buffer must be valid (not deallocated) while the returned reference is used.
+    pub unsafe fn as_slice(&self) -> &[u8] {
+        if self.len == 0 {
+            return &[];
+        }
+
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
+
+    /// Returns a view of the bytes of the buffer, which can be passed to APIs taking `&[u8]`
+    /// (e.g. hashers, writers or parsers), see [`ByteBufferView`].
+    ///
+    /// # Safety
+    ///
+    /// The buffer must be valid (not deallocated) and not modified while the view is used.
+    pub unsafe fn view(&self) -> ByteBufferView<'_> {
+        ByteBufferView {
+            bytes: unsafe { self.as_slice() },
+        }
+    }
+
+    /// Converts the byte buffer back to a rust managed boxed byte slice.
+    ///
+    /// The checksum is verified if one was recorded, a corruption is stored as last error
+    /// (see [`crate::checked`]).
+    ///
+    /// # Safety
+    ///
+    /// The buffer must have been created with [`ByteBuffer::from_boxed_slice`]
+    /// (or has the same layout `Box<[u8]>`) and must not be used afterwards.
+    #[cfg_attr(any(feature = "debug-track", feature = "tracing"), track_caller)]
+    pub unsafe fn into_boxed_slice(self) -> Box<[u8]> {
+        if self.len == 0 {
+            return Box::default();
+        }
+
+        checked::verify_reported(self.ptr, self.len, "ByteBuffer::into_boxed_slice");
+        #[cfg(feature = "debug-track")]
+        crate::track::reclaim(self.ptr, self.len);
+        stats::buffer_reclaimed(self.len);
+        audit::record(
+            FfiAuditEvent::Import,
+            self.ptr,
+            self.len,
+            "ByteBuffer::into_boxed_slice",
+        );
+
+        let slice_raw = std::ptr::slice_from_raw_parts_mut(self.ptr, self.len);
+        unsafe { Box::from_raw(slice_raw) }
+    }
+}
+
+impl Default for ByteBuffer {
+    fn default() -> Self {
+        Self::EMPTY
+    }
+}
+
+impl From<Box<[u8]>> for ByteBuffer {
+    fn from(src: Box<[u8]>) -> Self {
+        Self::from_boxed_slice(src)
+    }
+}
+
+impl From<Vec<u8>> for ByteBuffer {
+    fn from(src: Vec<u8>) -> Self {
+        Self::from_boxed_slice(src.into_boxed_slice())
+    }
+}
+
+impl From<String> for ByteBuffer {
+    fn from(src: String) -> Self {
+        Self::from_boxed_slice(src.into_bytes().into_boxed_slice())
+    }
+}
+
+/// Copies the given bytes.
+impl From<&[u8]> for ByteBuffer {
+    fn from(src: &[u8]) -> Self {
+        Self::from_boxed_slice(Box::from(src))
+    }
+}
+
+/// Copies the given string.
+impl From<&str> for ByteBuffer {
+    fn from(src: &str) -> Self {
+        Self::from_boxed_slice(Box::from(src.as_bytes()))
+    }
+}
+
+impl FromIterator<u8> for ByteBuffer {
+    fn from_iter<I: IntoIterator<Item = u8>>(iter: I) -> Self {
+        Self::from_boxed_slice(iter.into_iter().collect())
+    }
+}
+
+/// Borrowed view of the bytes of a [`ByteBuffer`], created with the unsafe
+/// [`ByteBuffer::view`] since the buffer itself is a raw representation.
+///
+/// The view dereferences to `[u8]` and implements `AsRef<[u8]>` and `Borrow<[u8]>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ByteBufferView<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> ByteBufferView<'a> {
+    /// Returns the viewed bytes.
+    pub fn as_slice(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Returns the viewed bytes as string, e.g. of a buffer created from a `String`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the [`Utf8Error`] if the bytes are not valid UTF-8.
+    pub fn as_str(&self) -> Result<&'a str, Utf8Error> {
+        std::str::from_utf8(self.bytes)
+    }
+}
+
+impl Deref for ByteBufferView<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.bytes
+    }
+}
+
+impl AsRef<[u8]> for ByteBufferView<'_> {
+    fn as_ref(&self) -> &[u8] {
+        self.bytes
+    }
+}
+
+impl Borrow<[u8]> for ByteBufferView<'_> {
+    fn borrow(&self) -> &[u8] {
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn view_borrows_the_bytes() {
+        let buffer = ByteBuffer::from(vec![1, 2, 3]);
+        let view = unsafe { buffer.view() };
+
+        assert_eq!(&*view, [1, 2, 3]);
+        assert_eq!(view.iter().sum::<u8>(), 6);
+        let mut copy = Vec::new();
+        std::io::Write::write_all(&mut copy, view.as_ref()).unwrap();
+        assert_eq!(copy, [1, 2, 3]);
+        // Looked up by slice, consistent with `Borrow`.
+        let set = HashSet::from([view]);
+        assert!(set.contains(&[1u8, 2, 3][..]));
+
+        drop(unsafe { buffer.into_boxed_slice() });
+    }
+
+    #[test]
+    fn view_as_str() {
+        let buffer = ByteBuffer::from(String::from("uuid"));
+        assert_eq!(unsafe { buffer.view() }.as_str().unwrap(), "uuid");
+        drop(unsafe { buffer.into_boxed_slice() });
+
+        let buffer = ByteBuffer::from(vec![0xff]);
+        assert!(unsafe { buffer.view() }.as_str().is_err());
+        drop(unsafe { buffer.into_boxed_slice() });
+
+        assert!(unsafe { ByteBuffer::EMPTY.view() }.is_empty());
+    }
+}
@@ -0,0 +1,174 @@
+//! Integrity checks of read-only buffers handed to the host, to catch a host which
+//! tramples buffer memory deterministically. Disabled by default, see [`enable`].
+//!
+//! While enabled, [`crate::ByteBuffer::from_boxed_slice`], [`crate::into_boxed_byte_slice_raw`]
+//! and [`crate::into_boxed_slice_raw`] record the xxHash64 (see [`crate::hash64`]) of each
+//! buffer in a side table, and [`crate::ByteBuffer::into_boxed_slice`],
+//! [`crate::from_boxed_byte_slice_raw`] and [`crate::from_boxed_slice_raw`] verify it.
+//! A buffer reallocated by [`crate::grow_boxed_byte_slice_buffer_raw`] is checked with its
+//! new contents.
+//!
+//! A corruption (changed bytes or length) is logged as error with the pointer and lengths
+//! (see [`crate::logging`]) and reported as [`FfiBufferError::Corrupted`] (stored as last
+//! error by the conversions which can't fail, see [`crate::error`]).
+//! It panics instead only if [`set_panic_on_corruption`] is enabled.
+//!
+//! Note: Buffers allocated for the host to be filled (e.g. [`crate::new_byte_buffer`])
+//! are not checked.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        LazyLock, Mutex,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+};
+
+use crate::{
+    audit::FfiAuditEvent,
+    error::FfiBufferError,
+    hash64,
+    logging::{self, FfiLogLevel},
+};
+
+struct Tag {
+    len: usize,
+    hash: u64,
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static PANIC: AtomicBool = AtomicBool::new(false);
+static CORRUPTIONS: AtomicU64 = AtomicU64::new(0);
+static TAGS: LazyLock<Mutex<HashMap<usize, Tag>>> = LazyLock::new(Default::default);
+
+/// Enables or disables the integrity checks, disabling forgets the recorded checksums.
+///
+/// Only buffers handed out while enabled are checked.
+pub fn enable(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+    if !enabled {
+        TAGS.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Enables or disables panicking on a detected corruption (after it was logged),
+/// e.g. to stop at the first corruption in a debugger. Disabled by default.
+///
+/// Note: The panic may unwind from the conversions of any buffer (see [`crate::unwind`]
+/// for the exported functions).
+pub fn set_panic_on_corruption(panic: bool) {
+    PANIC.store(panic, Ordering::Relaxed);
+}
+
+/// Returns the number of corruptions detected so far.
+pub fn corruption_count() -> u64 {
+    CORRUPTIONS.load(Ordering::Relaxed)
+}
+
+// Records the checksum of the given buffer handed to the host, if enabled.
+pub(crate) fn tag(ptr: *const u8, len: usize) {
+    if !is_enabled() || len == 0 {
+        return;
+    }
+
+    let hash = hash64(unsafe { std::slice::from_raw_parts(ptr, len) }, 0);
+    TAGS.lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(ptr as usize, Tag { len, hash });
+}
+
+// Moves the checksum of the given buffer reallocated from `old_ptr` to `new_ptr`, if it
+// has one, so its new contents are checked.
+pub(crate) fn retag(old_ptr: *const u8, new_ptr: *const u8, new_len: usize) {
+    if !is_enabled() {
+        return;
+    }
+
+    let tagged = TAGS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&(old_ptr as usize))
+        .is_some();
+    if tagged {
+        tag(new_ptr, new_len);
+    }
+}
+
+// Verifies the given buffer received back from the host against its recorded checksum,
+// buffers without a checksum are not checked.
+//
+// # Errors
+//
+// Returns `FfiBufferError::Corrupted` on a corruption.
+//
+// # Panics
+//
+// Panics on a corruption if `set_panic_on_corruption` is enabled.
+pub(crate) fn verify(
+    ptr: *const u8,
+    len: usize,
+    label: &'static str,
+) -> Result<(), FfiBufferError> {
+    if !is_enabled() || ptr.is_null() {
+        return Ok(());
+    }
+    let Some(tag) = TAGS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&(ptr as usize))
+    else {
+        return Ok(());
+    };
+
+    let msg = if len != tag.len {
+        format!(
+            "buffer {ptr:p} corrupted ({label}): length {len} received, {} handed out",
+            tag.len
+        )
+    } else {
+        let hash = hash64(unsafe { std::slice::from_raw_parts(ptr, len) }, 0);
+        if hash == tag.hash {
+            return Ok(());
+        }
+        format!(
+            "buffer {ptr:p} of length {len} corrupted ({label}): checksum {hash:#018x}, {:#018x} handed out",
+            tag.hash
+        )
+    };
+
+    CORRUPTIONS.fetch_add(1, Ordering::Relaxed);
+    logging::log(FfiLogLevel::Error, &msg);
+    if PANIC.load(Ordering::Relaxed) {
+        panic!("{msg}");
+    }
+
+    Err(FfiBufferError::Corrupted {
+        ptr: ptr as usize,
+        len,
+        label,
+    })
+}
+
+// Verifies the given buffer (see `verify`) for a conversion which can't fail,
+// a corruption is stored as last error of the current thread.
+pub(crate) fn verify_reported(ptr: *const u8, len: usize, label: &'static str) {
+    if let Err(error) = verify(ptr, len, label) {
+        error.report();
+    }
+}
+
+// Observes a lifecycle event of a buffer, a checksum is forgotten once its buffer
+// is reclaimed (or its address is allocated again) by any other way.
+pub(crate) fn observe(event: FfiAuditEvent, ptr: *const u8) {
+    if !is_enabled() || event == FfiAuditEvent::Export {
+        return;
+    }
+
+    TAGS.lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&(ptr as usize));
+}
@@ -0,0 +1,189 @@
+//! Compression of large payloads right at the FFI boundary, with a single allocation
+//! for the output buffer.
+//!
+//! The codecs are enabled by the `lz4` and `zstd` features. The LZ4 format is a block
+//! prefixed by its decompressed length as little endian `u32` (as `compress_prepend_size`
+//! of `lz4_flex`), the Zstandard format is a regular frame.
+//!
+//! The decompressed length declared by untrusted input is checked against a caller given
+//! limit before anything is allocated, see [`decompress_from_raw`].
+
+#[cfg(feature = "zstd")]
+use std::io::Read;
+
+use crate::{
+    borrowed::c_bytes_as_slice_ref, error::FfiBufferError, into_boxed_byte_slice_raw,
+    new_zeroed_boxed_byte_slice, truncate_boxed_byte_slice,
+};
+
+// Upper bound of the Zstandard compression ratio, a 128 KiB block in 4 bytes.
+#[cfg(feature = "zstd")]
+const ZSTD_MAX_RATIO: u64 = 128 * 1024 / 4;
+
+/// Compression codec of [`compress_into_raw`] and [`decompress_from_raw`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Codec {
+    /// LZ4 block with its decompressed length, fast with a moderate ratio.
+    #[cfg(feature = "lz4")]
+    Lz4,
+    /// Zstandard frame, a better ratio at a tunable speed.
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+/// Compresses the given bytes into a new buffer and returns its raw parts `(ptr, len)`,
+/// to be passed to the host.
+///
+/// `level` is the Zstandard compression level (0 is the default level 3), it is ignored by LZ4.
+///
+/// Note: The buffer is allocated with the worst case compressed length and then reallocated
+/// to the actual length, which may copy the compressed bytes once (depending on the allocator).
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::Alloc`] if the allocation failed, [`FfiBufferError::Codec`] if
+/// the compression failed (e.g. LZ4 input longer than `u32::MAX` bytes).
+///
+/// # Safety
+///
+/// Later at some point, the buffer must be converted back with
+/// [`crate::from_boxed_byte_slice_raw`] (or freed with [`crate::free_boxed_byte_slice_raw`]).
+#[cfg_attr(any(feature = "debug-track", feature = "tracing"), track_caller)]
+#[cfg_attr(not(feature = "zstd"), allow(unused_variables))]
+pub fn compress_into_raw(
+    src: &[u8],
+    codec: Codec,
+    level: i32,
+) -> Result<(*const u8, usize), FfiBufferError> {
+    let compressed = match codec {
+        #[cfg(feature = "lz4")]
+        Codec::Lz4 => {
+            let len = u32::try_from(src.len())
+                .map_err(|_| FfiBufferError::Codec("LZ4 input too long".to_string()))?;
+
+            let mut bytes = new_zeroed_boxed_byte_slice(
+                4 + lz4_flex::block::get_maximum_output_size(src.len()),
+            )?;
+            bytes[..4].copy_from_slice(&len.to_le_bytes());
+            let written = lz4_flex::block::compress_into(src, &mut bytes[4..])
+                .map_err(|error| FfiBufferError::Codec(error.to_string()))?;
+
+            truncate_boxed_byte_slice(bytes, 4 + written)
+        }
+        #[cfg(feature = "zstd")]
+        Codec::Zstd => {
+            let mut bytes =
+                new_zeroed_boxed_byte_slice(zstd::zstd_safe::compress_bound(src.len()))?;
+            let written = zstd::bulk::compress_to_buffer(src, &mut bytes[..], level)
+                .map_err(|error| FfiBufferError::Codec(error.to_string()))?;
+
+            truncate_boxed_byte_slice(bytes, written)
+        }
+    };
+
+    Ok(into_boxed_byte_slice_raw(compressed))
+}
+
+/// Decompresses the given byte range, e.g. received from the host, into a new boxed byte slice
+/// of exactly the decompressed length. The bytes are only read, the caller keeps owning them.
+///
+/// The decompressed length must not exceed `max_output` bytes, so untrusted input can't
+/// make this function allocate arbitrary amounts of memory.
+///
+/// Note: A Zstandard frame without its content size (e.g. of a streaming compressor)
+/// is decompressed into a growing buffer, up to `max_output` bytes.
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::Codec`] if the bytes are not valid in the codec's format (or
+/// don't match their decompressed length), the decompressed length exceeds `max_output`
+/// or is implausible for the compressed length, [`FfiBufferError::Alloc`] if the allocation
+/// failed, see [`c_bytes_as_slice_ref`] for the other errors.
+///
+/// # Safety
+///
+/// The byte range must be valid (not deallocated) while this function is in process.
+pub unsafe fn decompress_from_raw(
+    ptr: *const u8,
+    len: usize,
+    codec: Codec,
+    max_output: usize,
+) -> Result<Box<[u8]>, FfiBufferError> {
+    let src = unsafe { c_bytes_as_slice_ref(ptr, len) }?;
+    let too_long = |len: u64| {
+        FfiBufferError::Codec(format!(
+            "decompressed length {len} exceeds the limit of {max_output} bytes"
+        ))
+    };
+
+    let (bytes, written) = match codec {
+        #[cfg(feature = "lz4")]
+        Codec::Lz4 => {
+            let (len, block) = src
+                .split_first_chunk::<4>()
+                .ok_or_else(|| FfiBufferError::Codec("truncated LZ4 length".to_string()))?;
+
+            let len = u32::from_le_bytes(*len);
+            if len as usize > max_output {
+                return Err(too_long(u64::from(len)));
+            }
+
+            let mut bytes = new_zeroed_boxed_byte_slice(len as usize)?;
+            let written = lz4_flex::block::decompress_into(block, &mut bytes)
+                .map_err(|error| FfiBufferError::Codec(error.to_string()))?;
+            (bytes, written)
+        }
+        #[cfg(feature = "zstd")]
+        Codec::Zstd => {
+            let content_size = zstd::zstd_safe::get_frame_content_size(src)
+                .map_err(|_| FfiBufferError::Codec("invalid Zstandard frame".to_string()))?;
+            let Some(content_size) = content_size else {
+                return decode_zstd_stream(src, max_output);
+            };
+
+            if content_size > max_output as u64 {
+                return Err(too_long(content_size));
+            }
+            // Each block (at most 128 KiB) takes at least 4 bytes (a run length encoded block).
+            if content_size > (src.len() as u64).saturating_mul(ZSTD_MAX_RATIO) {
+                return Err(FfiBufferError::Codec(format!(
+                    "implausible Zstandard content size {content_size} of {} bytes",
+                    src.len()
+                )));
+            }
+
+            let mut bytes = new_zeroed_boxed_byte_slice(content_size as usize)?;
+            let written = zstd::bulk::decompress_to_buffer(src, &mut bytes[..])
+                .map_err(|error| FfiBufferError::Codec(error.to_string()))?;
+            (bytes, written)
+        }
+    };
+    if written != bytes.len() {
+        return Err(FfiBufferError::Codec(format!(
+            "decompressed {written} bytes, {} expected",
+            bytes.len()
+        )));
+    }
+
+    Ok(bytes)
+}
+
+// Decompresses a Zstandard frame without content size, up to `max_output` bytes.
+#[cfg(feature = "zstd")]
+fn decode_zstd_stream(src: &[u8], max_output: usize) -> Result<Box<[u8]>, FfiBufferError> {
+    let codec_error = |error: std::io::Error| FfiBufferError::Codec(error.to_string());
+
+    let decoder = zstd::stream::read::Decoder::with_buffer(src).map_err(codec_error)?;
+    let mut bytes = Vec::new();
+    decoder
+        .take(max_output as u64 + 1)
+        .read_to_end(&mut bytes)
+        .map_err(codec_error)?;
+    if bytes.len() > max_output {
+        return Err(FfiBufferError::Codec(format!(
+            "decompressed length exceeds the limit of {max_output} bytes"
+        )));
+    }
+
+    Ok(bytes.into_boxed_slice())
+}
@@ -0,0 +1,175 @@
+//! FFI compatible copy-on-write buffer, either a zero-copy borrow of rust owned data
+//! or an owned allocation, released uniformly with one routine.
+
+use std::{borrow::Cow, sync::Arc};
+
+use crate::{ByteBuffer, shared};
+
+/// FFI compatible buffer, which borrows static or cached (see [`FfiCow::cached`]) rust data
+/// if `owned` is false and owns a boxed byte slice `Box<[u8]>` if `owned` is true.
+///
+/// An empty buffer is always represented by a null `ptr`, a `len` of 0 and `owned` false.
+///
+/// Note: The buffer does not drop its bytes - it must be released with [`FfiCow::free`]
+/// (or converted back with [`FfiCow::into_cow`]) at some point, which only deallocates
+/// owned bytes. The host must not modify the bytes.
+#[repr(C)]
+#[derive(Debug)]
+pub struct FfiCow {
+    pub ptr: *const u8,
+    pub len: usize,
+    pub owned: bool,
+}
+
+impl FfiCow {
+    /// The canonical empty buffer, a null `ptr`, a `len` of 0 and `owned` false.
+    pub const EMPTY: Self = Self {
+        ptr: std::ptr::null(),
+        len: 0,
+        owned: false,
+    };
+
+    /// Creates a borrowing buffer of the given static bytes, nothing is copied.
+    pub fn borrowed(src: &'static [u8]) -> Self {
+        if src.is_empty() {
+            return Self::EMPTY;
+        }
+
+        Self {
+            ptr: src.as_ptr(),
+            len: src.len(),
+            owned: false,
+        }
+    }
+
+    /// Creates a borrowing buffer of the given cached bytes, nothing is copied.
+    ///
+    /// The buffer holds a reference of the bytes (see [`crate::shared_into_raw`]), so they
+    /// stay alive until the buffer is released, even if the cache drops them.
+    pub fn cached(src: Arc<[u8]>) -> Self {
+        let (ptr, len) = shared::shared_into_raw(src);
+        Self {
+            ptr,
+            len,
+            owned: false,
+        }
+    }
+
+    /// Creates an owning buffer of the given boxed byte slice.
+    #[cfg_attr(any(feature = "debug-track", feature = "tracing"), track_caller)]
+    pub fn owned(src: Box<[u8]>) -> Self {
+        let (ptr, len) = ByteBuffer::from_boxed_slice(src).into_raw();
+        Self {
+            ptr,
+            len,
+            owned: len != 0,
+        }
+    }
+
+    pub fn is_owned(&self) -> bool {
+        self.owned
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the bytes of the buffer.
+    ///
+    /// # Safety
+    ///
+    /// The buffer must be valid (not released) while the returned reference is used.
+    pub unsafe fn as_slice(&self) -> &[u8] {
+        if self.len == 0 {
+            return &[];
+        }
+
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
+
+    /// Converts the buffer back to a rust managed copy-on-write slice.
+    ///
+    /// Note: The bytes of a cached buffer are copied (and its reference is released),
+    /// use [`FfiCow::free`] to release it without a copy.
+    ///
+    /// # Safety
+    ///
+    /// The buffer must have been created with [`FfiCow::borrowed`], [`FfiCow::cached`]
+    /// or [`FfiCow::owned`] and must not be used afterwards.
+    #[cfg_attr(any(feature = "debug-track", feature = "tracing"), track_caller)]
+    pub unsafe fn into_cow(self) -> Cow<'static, [u8]> {
+        if self.len == 0 {
+            return Cow::Borrowed(&[]);
+        }
+        if !self.owned {
+            if let Some(payload) = shared::shared_get_raw(self.ptr) {
+                shared::shared_release_raw(self.ptr);
+                return Cow::Owned(payload.to_vec());
+            }
+            return Cow::Borrowed(unsafe { std::slice::from_raw_parts(self.ptr, self.len) });
+        }
+
+        let buffer = ByteBuffer::from_raw(self.ptr.cast_mut(), self.len);
+        Cow::Owned(unsafe { buffer.into_boxed_slice() }.into_vec())
+    }
+
+    /// Releases the buffer, only owned bytes are deallocated (and the reference of cached bytes
+    /// is released).
+    ///
+    /// # Safety
+    ///
+    /// See [`FfiCow::into_cow`].
+    #[cfg_attr(any(feature = "debug-track", feature = "tracing"), track_caller)]
+    pub unsafe fn free(self) {
+        if !self.owned {
+            // Static bytes are not registered, nothing is released then.
+            shared::shared_release_raw(self.ptr);
+            return;
+        }
+
+        drop(unsafe { self.into_cow() });
+    }
+}
+
+impl Default for FfiCow {
+    fn default() -> Self {
+        Self::EMPTY
+    }
+}
+
+impl From<Cow<'static, [u8]>> for FfiCow {
+    fn from(src: Cow<'static, [u8]>) -> Self {
+        match src {
+            Cow::Borrowed(bytes) => Self::borrowed(bytes),
+            Cow::Owned(bytes) => Self::owned(bytes.into_boxed_slice()),
+        }
+    }
+}
+
+impl From<&'static [u8]> for FfiCow {
+    fn from(src: &'static [u8]) -> Self {
+        Self::borrowed(src)
+    }
+}
+
+impl From<Arc<[u8]>> for FfiCow {
+    fn from(src: Arc<[u8]>) -> Self {
+        Self::cached(src)
+    }
+}
+
+impl From<Box<[u8]>> for FfiCow {
+    fn from(src: Box<[u8]>) -> Self {
+        Self::owned(src)
+    }
+}
+
+impl From<Vec<u8>> for FfiCow {
+    fn from(src: Vec<u8>) -> Self {
+        Self::owned(src.into_boxed_slice())
+    }
+}
@@ -0,0 +1,63 @@
+//! NUL terminated strings `char*` for C APIs, which don't take a pointer and a length.
+
+use std::ffi::{CString, IntoStringError, NulError, c_char};
+
+use crate::{
+    audit::{self, FfiAuditEvent},
+    stats,
+};
+
+/// Converts the given string into a NUL terminated string (see [`CString::into_raw`])
+/// and returns the pointer to it.
+///
+/// The string must be converted back with [`string_from_cstring_raw`] at some point.
+///
+/// # Errors
+///
+/// Returns the [`NulError`] (which holds the bytes of the string) if the string contains
+/// an interior NUL byte.
+pub fn string_into_cstring_raw(src: String) -> Result<*mut c_char, NulError> {
+    let cstring = CString::new(src)?;
+    let len = cstring.as_bytes_with_nul().len();
+
+    let ptr = cstring.into_raw();
+    stats::buffer_created(len);
+    audit::record(
+        FfiAuditEvent::Export,
+        ptr.cast(),
+        len,
+        "string_into_cstring_raw",
+    );
+
+    Ok(ptr)
+}
+
+/// Converts the given NUL terminated string back to a rust string (see [`CString::from_raw`]),
+/// a null pointer results in an empty string.
+///
+/// # Errors
+///
+/// Returns the [`IntoStringError`] (which holds the reclaimed string) if the string is not
+/// valid UTF-8, e.g. if it was modified by the host.
+///
+/// # Safety
+///
+/// The pointer must be null or have been created with [`string_into_cstring_raw`]
+/// and must not be used afterwards. The host may modify the bytes, but not the length.
+pub unsafe fn string_from_cstring_raw(ptr: *mut c_char) -> Result<String, IntoStringError> {
+    if ptr.is_null() {
+        return Ok(String::default());
+    }
+
+    let cstring = unsafe { CString::from_raw(ptr) };
+    let len = cstring.as_bytes_with_nul().len();
+    stats::buffer_reclaimed(len);
+    audit::record(
+        FfiAuditEvent::Import,
+        ptr.cast(),
+        len,
+        "string_from_cstring_raw",
+    );
+
+    cstring.into_string()
+}
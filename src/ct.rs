@@ -0,0 +1,36 @@
+//! Constant-time comparison of byte buffers, e.g. of tokens or MACs, which doesn't leak
+//! the position of the first difference through its timing.
+
+use crate::borrowed::c_bytes_as_slice_ref;
+
+/// Returns true if the given bytes are equal, in time depending only on their lengths.
+///
+/// Note: The lengths are not secret - different lengths return false right away.
+pub fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    let diff = a.iter().zip(b).fold(0u8, |diff, (a, b)| diff | (a ^ b));
+    // Hides the value, so the comparison is not turned into an early return.
+    std::hint::black_box(diff) == 0
+}
+
+/// Returns true if the given byte ranges are equal, see [`ct_eq`].
+///
+/// Note: False is returned if a pointer is null with a length other than 0.
+///
+/// # Safety
+///
+/// Both byte ranges must be valid (not deallocated) while this function is in process.
+pub unsafe fn ct_eq_raw(a_ptr: *const u8, a_len: usize, b_ptr: *const u8, b_len: usize) -> bool {
+    match unsafe {
+        (
+            c_bytes_as_slice_ref(a_ptr, a_len),
+            c_bytes_as_slice_ref(b_ptr, b_len),
+        )
+    } {
+        (Ok(a), Ok(b)) => ct_eq(a, b),
+        _ => false,
+    }
+}
@@ -0,0 +1,290 @@
+//! Endian-aware cursors for parsing binary payloads (e.g. of protocols) in place,
+//! without copying them into a `Vec` first, and for building them.
+
+use crate::{
+    ByteBuffer, borrowed::c_bytes_as_slice_ref, error::FfiBufferError, into_boxed_byte_slice_raw,
+};
+
+// Defines a `read_...` method for each given number type and byte order.
+macro_rules! read_numbers {
+    ($($name:ident: $ty:ty = $from:ident;)*) => {
+        $(
+            #[doc = concat!("Reads a `", stringify!($ty), "` (see [`", stringify!($ty), "::", stringify!($from), "`]).")]
+            ///
+            /// # Errors
+            ///
+            /// See [`ByteReader::bytes`].
+            pub fn $name(&mut self) -> Result<$ty, FfiBufferError> {
+                self.array().map(<$ty>::$from)
+            }
+        )*
+    };
+}
+
+/// Zero-copy cursor over bytes, e.g. received and owned from C.
+///
+/// Each read advances the cursor, a failed read (underflow) leaves the cursor unchanged.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ByteReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    /// Returns a cursor over the given C-Bytes, received and owned from C.
+    ///
+    /// # Errors
+    ///
+    /// See [`c_bytes_as_slice_ref`].
+    ///
+    /// # Safety
+    ///
+    /// The given C-Bytes must be valid (not deallocated from the owning C side)
+    /// while the cursor or bytes read with it are used.
+    pub unsafe fn from_raw(ptr: *const u8, len: usize) -> Result<Self, FfiBufferError> {
+        let bytes = unsafe { c_bytes_as_slice_ref(ptr, len) }?;
+        Ok(Self::new(bytes))
+    }
+
+    /// Returns the number of bytes read so far.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the number of bytes which are not read yet.
+    pub fn remaining(&self) -> usize {
+        self.bytes.len() - self.position
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    /// Returns the bytes which are not read yet, without advancing the cursor.
+    pub fn remaining_bytes(&self) -> &'a [u8] {
+        &self.bytes[self.position..]
+    }
+
+    /// Reads the next `n` bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FfiBufferError::OutOfBounds`] if less than `n` bytes remain.
+    pub fn bytes(&mut self, n: usize) -> Result<&'a [u8], FfiBufferError> {
+        if n > self.remaining() {
+            return Err(FfiBufferError::OutOfBounds {
+                index: self.position.saturating_add(n - 1),
+                len: self.bytes.len(),
+            });
+        }
+
+        let bytes = &self.bytes[self.position..self.position + n];
+        self.position += n;
+
+        Ok(bytes)
+    }
+
+    /// Skips the next `n` bytes.
+    ///
+    /// # Errors
+    ///
+    /// See [`ByteReader::bytes`].
+    pub fn skip(&mut self, n: usize) -> Result<(), FfiBufferError> {
+        self.bytes(n).map(drop)
+    }
+
+    /// Reads the next `N` bytes as array.
+    ///
+    /// # Errors
+    ///
+    /// See [`ByteReader::bytes`].
+    pub fn array<const N: usize>(&mut self) -> Result<[u8; N], FfiBufferError> {
+        let bytes = self.bytes(N)?;
+        Ok(bytes.try_into().expect("read exactly N bytes"))
+    }
+
+    read_numbers! {
+        read_u8: u8 = from_le_bytes;
+        read_i8: i8 = from_le_bytes;
+        read_u16_le: u16 = from_le_bytes;
+        read_u16_be: u16 = from_be_bytes;
+        read_i16_le: i16 = from_le_bytes;
+        read_i16_be: i16 = from_be_bytes;
+        read_u32_le: u32 = from_le_bytes;
+        read_u32_be: u32 = from_be_bytes;
+        read_i32_le: i32 = from_le_bytes;
+        read_i32_be: i32 = from_be_bytes;
+        read_u64_le: u64 = from_le_bytes;
+        read_u64_be: u64 = from_be_bytes;
+        read_i64_le: i64 = from_le_bytes;
+        read_i64_be: i64 = from_be_bytes;
+        read_f32_le: f32 = from_le_bytes;
+        read_f32_be: f32 = from_be_bytes;
+        read_f64_le: f64 = from_le_bytes;
+        read_f64_be: f64 = from_be_bytes;
+    }
+}
+
+// Defines a `write_...` method for each given number type and byte order.
+macro_rules! write_numbers {
+    ($($name:ident: $ty:ty = $to:ident;)*) => {
+        $(
+            #[doc = concat!("Appends a `", stringify!($ty), "` (see [`", stringify!($ty), "::", stringify!($to), "`]).")]
+            pub fn $name(&mut self, value: $ty) -> &mut Self {
+                self.write_bytes(&value.$to())
+            }
+        )*
+    };
+}
+
+/// Growable builder of a binary payload, which is handed to the host as raw buffer
+/// (see [`ByteWriter::into_raw`] and [`ByteWriter::into_byte_buffer`]).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ByteWriter {
+    bytes: Vec<u8>,
+}
+
+impl ByteWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a builder with room for `capacity` bytes.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            bytes: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Returns the bytes written so far.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Appends the given bytes.
+    pub fn write_bytes(&mut self, bytes: &[u8]) -> &mut Self {
+        self.bytes.extend_from_slice(bytes);
+        self
+    }
+
+    /// Appends the given string, preceded by its length as little endian `u32`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FfiBufferError::CapacityOverflow`] if the string is longer than `u32::MAX` bytes.
+    pub fn write_str_len_prefixed(&mut self, src: &str) -> Result<&mut Self, FfiBufferError> {
+        let len = u32::try_from(src.len()).map_err(|_| FfiBufferError::CapacityOverflow)?;
+        Ok(self.write_u32_le(len).write_bytes(src.as_bytes()))
+    }
+
+    write_numbers! {
+        write_u8: u8 = to_le_bytes;
+        write_i8: i8 = to_le_bytes;
+        write_u16_le: u16 = to_le_bytes;
+        write_u16_be: u16 = to_be_bytes;
+        write_i16_le: i16 = to_le_bytes;
+        write_i16_be: i16 = to_be_bytes;
+        write_u32_le: u32 = to_le_bytes;
+        write_u32_be: u32 = to_be_bytes;
+        write_i32_le: i32 = to_le_bytes;
+        write_i32_be: i32 = to_be_bytes;
+        write_u64_le: u64 = to_le_bytes;
+        write_u64_be: u64 = to_be_bytes;
+        write_i64_le: i64 = to_le_bytes;
+        write_i64_be: i64 = to_be_bytes;
+        write_f32_le: f32 = to_le_bytes;
+        write_f32_be: f32 = to_be_bytes;
+        write_f64_le: f64 = to_le_bytes;
+        write_f64_be: f64 = to_be_bytes;
+    }
+
+    pub fn into_boxed_slice(self) -> Box<[u8]> {
+        self.bytes.into_boxed_slice()
+    }
+
+    /// Converts the written bytes into a boxed byte slice and returns its raw parts
+    /// `(ptr, len)`, see [`into_boxed_byte_slice_raw`].
+    pub fn into_raw(self) -> (*const u8, usize) {
+        into_boxed_byte_slice_raw(self.into_boxed_slice())
+    }
+
+    /// Converts the written bytes into a byte buffer, to be passed to the host.
+    pub fn into_byte_buffer(self) -> ByteBuffer {
+        ByteBuffer::from_boxed_slice(self.into_boxed_slice())
+    }
+}
+
+impl Extend<u8> for ByteWriter {
+    fn extend<I: IntoIterator<Item = u8>>(&mut self, iter: I) {
+        self.bytes.extend(iter);
+    }
+}
+
+impl<'a> Extend<&'a u8> for ByteWriter {
+    fn extend<I: IntoIterator<Item = &'a u8>>(&mut self, iter: I) {
+        self.bytes.extend(iter);
+    }
+}
+
+impl FromIterator<u8> for ByteWriter {
+    fn from_iter<I: IntoIterator<Item = u8>>(iter: I) -> Self {
+        Self {
+            bytes: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl From<ByteWriter> for ByteBuffer {
+    fn from(src: ByteWriter) -> Self {
+        src.into_byte_buffer()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FfiBuffer;
+
+    fn payload() -> impl Iterator<Item = u8> {
+        (0..=u8::MAX).cycle().take(1000)
+    }
+
+    #[test]
+    fn writer_collects_like_ffi_buffer() {
+        let collected: FfiBuffer = payload().collect();
+
+        let from_iter: ByteWriter = payload().collect();
+        assert_eq!(from_iter.as_slice(), collected.as_slice());
+
+        let mut extended = ByteWriter::with_capacity(16);
+        extended.extend(payload().take(300));
+        extended.extend(payload().skip(300).collect::<Vec<_>>().iter());
+        assert_eq!(extended, from_iter);
+
+        // The written bytes collect into the same buffer.
+        let buffer: FfiBuffer = extended.as_slice().iter().copied().collect();
+        assert_eq!(buffer, collected);
+    }
+
+    #[test]
+    fn empty_writer_collects_like_ffi_buffer() {
+        let collected: FfiBuffer = std::iter::empty().collect();
+        let mut writer: ByteWriter = std::iter::empty().collect();
+        writer.extend(std::iter::empty::<u8>());
+
+        assert!(writer.is_empty() && collected.is_empty());
+        assert_eq!(writer.as_slice(), collected.as_slice());
+    }
+}
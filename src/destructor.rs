@@ -0,0 +1,97 @@
+//! Buffers released through a `void (*)(void*)` destructor, the convention of
+//! C APIs taking ownership of a buffer (e.g. `sqlite3_bind_blob`).
+
+use std::{
+    alloc::{Layout, dealloc},
+    ffi::c_void,
+    mem::{align_of, size_of},
+};
+
+use crate::{
+    audit::{self, FfiAuditEvent},
+    error::FfiBufferError,
+    oom, stats,
+};
+
+/// Destructor releasing a buffer by its pointer only, the C signature is `void (*)(void*)`.
+pub type FfiDestructor = unsafe extern "C" fn(ptr: *mut c_void);
+
+// The length is stored in front of the bytes, as the destructor only receives the pointer.
+const HEADER_SIZE: usize = size_of::<usize>();
+
+/// Copies the given bytes into a new buffer, which is released by calling the
+/// returned destructor with the returned pointer.
+///
+/// Returns the pointer to the bytes, their length and the destructor.
+/// If `src` is empty the pointer is null (the destructor ignores it).
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::CapacityOverflow`] if the length with header overflows,
+/// [`FfiBufferError::Alloc`] if the allocation failed.
+///
+/// Note: Some C APIs (like `sqlite3_bind_blob`) treat a null pointer as SQL `NULL`
+/// instead of an empty blob.
+///
+/// # Safety
+///
+/// Later at some point the destructor must be called exactly once with the returned
+/// pointer, the pointer must not be converted with one of the `from_...` functions.
+pub fn to_byte_slice_raw_with_destructor(
+    src: &[u8],
+) -> Result<(*mut u8, usize, FfiDestructor), FfiBufferError> {
+    if src.is_empty() {
+        return Ok((std::ptr::null_mut(), 0, destroy_buffer));
+    }
+
+    let len = src.len();
+    let layout = block_layout(len).ok_or(FfiBufferError::CapacityOverflow)?;
+    let block = oom::allocate(layout, false);
+    if block.is_null() {
+        return Err(FfiBufferError::Alloc { len: layout.size() });
+    }
+
+    unsafe {
+        block.cast::<usize>().write(len);
+        let ptr = block.add(HEADER_SIZE);
+        std::ptr::copy_nonoverlapping(src.as_ptr(), ptr, len);
+
+        stats::buffer_created(len);
+        audit::record(
+            FfiAuditEvent::Export,
+            ptr,
+            len,
+            "to_byte_slice_raw_with_destructor",
+        );
+
+        Ok((ptr, len, destroy_buffer))
+    }
+}
+
+/// Releases a buffer created by [`to_byte_slice_raw_with_destructor`],
+/// a null pointer is ignored.
+///
+/// # Safety
+///
+/// The given pointer must be null or returned by
+/// [`to_byte_slice_raw_with_destructor`] and not used afterwards.
+pub unsafe extern "C" fn destroy_buffer(ptr: *mut c_void) {
+    if ptr.is_null() {
+        return;
+    }
+
+    unsafe {
+        let block = ptr.cast::<u8>().sub(HEADER_SIZE);
+        let len = block.cast::<usize>().read();
+
+        stats::buffer_reclaimed(len);
+        audit::record(FfiAuditEvent::Free, ptr.cast(), len, "destroy_buffer");
+        // The layout was valid on allocation.
+        dealloc(block, block_layout(len).unwrap_unchecked());
+    }
+}
+
+fn block_layout(len: usize) -> Option<Layout> {
+    let size = HEADER_SIZE.checked_add(len)?;
+    Layout::from_size_align(size, align_of::<usize>()).ok()
+}
@@ -0,0 +1,32 @@
+//! Binary diff/patch of byte buffers (bsdiff based), so only the delta
+//! between two near-identical payloads needs to cross the FFI boundary.
+
+use crate::{ByteBuffer, error::FfiBufferError};
+
+/// Returns a patch describing the difference between `old` and `new`.
+///
+/// The patch can be applied to `old` with [`apply_patch`] to get `new` back.
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::Codec`] if the patch can't be created.
+pub fn diff(old: &[u8], new: &[u8]) -> Result<ByteBuffer, FfiBufferError> {
+    let mut patch = Vec::new();
+    bsdiff::diff(old, new, &mut patch).map_err(|e| FfiBufferError::Codec(e.to_string()))?;
+
+    Ok(ByteBuffer::from_boxed_slice(patch.into_boxed_slice()))
+}
+
+/// Applies the given `patch` (created with [`diff`]) to `old` and returns
+/// the new bytes.
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::Codec`] if the patch is malformed or does not match `old`.
+pub fn apply_patch(old: &[u8], patch: &[u8]) -> Result<ByteBuffer, FfiBufferError> {
+    let mut new = Vec::new();
+    bsdiff::patch(old, &mut &patch[..], &mut new)
+        .map_err(|e| FfiBufferError::Codec(e.to_string()))?;
+
+    Ok(ByteBuffer::from_boxed_slice(new.into_boxed_slice()))
+}
@@ -0,0 +1,344 @@
+//! Physically contiguous buffers allocated from a Linux DMA heap (`/dev/dma_heap`),
+//! e.g. the CMA heap, for native drivers which require DMA capable memory.
+
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    collections::HashMap,
+    fs::File,
+    hash::{Hash, Hasher},
+    io,
+    ops::{Deref, DerefMut, Index, IndexMut, Range},
+    os::fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, OwnedFd},
+    slice::{Chunks, Iter, SliceIndex, Windows},
+    sync::{LazyLock, Mutex},
+};
+
+use crate::{ByteBuffer, error::FfiBufferError, stats};
+
+/// `struct dma_heap_allocation_data` of `linux/dma-heap.h`.
+#[repr(C)]
+struct DmaHeapAllocationData {
+    len: u64,
+    fd: u32,
+    fd_flags: u32,
+    heap_flags: u64,
+}
+
+/// `DMA_HEAP_IOCTL_ALLOC`: `_IOWR('H', 0x0, struct dma_heap_allocation_data)`.
+const DMA_HEAP_IOCTL_ALLOC: libc::c_ulong = 0xC018_4800;
+
+/// Exported buffers, by pointer, until they are converted back.
+static EXPORTED: LazyLock<Mutex<HashMap<usize, DmaBuffer>>> = LazyLock::new(Default::default);
+
+/// Buffer allocated from a DMA heap, backed by a dma-buf file descriptor and mapped into memory.
+///
+/// The mapping and the dma-buf are released when the buffer is dropped.
+#[derive(Debug)]
+pub struct DmaBuffer {
+    ptr: *mut u8,
+    len: usize,
+    fd: OwnedFd,
+}
+
+// The mapping is owned by the buffer, the pointer is not shared.
+unsafe impl Send for DmaBuffer {}
+unsafe impl Sync for DmaBuffer {}
+
+impl DmaBuffer {
+    /// Allocates a zeroed buffer with the given `len` from the given DMA heap.
+    ///
+    /// # Arguments
+    ///
+    /// * `heap` - The name of the heap in `/dev/dma_heap`, e.g. `linux,cma`
+    /// * `len` - The length of the buffer, must not be 0
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FfiBufferError::InvalidArgument`] if `len` is 0, [`FfiBufferError::Io`] if the
+    /// heap cannot be opened, the allocation or the mapping failed.
+    pub fn allocate(heap: &str, len: usize) -> Result<Self, FfiBufferError> {
+        if len == 0 {
+            return Err(FfiBufferError::InvalidArgument("buffer length of 0"));
+        }
+
+        let heap = File::open(format!("/dev/dma_heap/{heap}"))?;
+        let mut data = DmaHeapAllocationData {
+            len: len as u64,
+            fd: 0,
+            fd_flags: (libc::O_RDWR | libc::O_CLOEXEC) as u32,
+            heap_flags: 0,
+        };
+
+        if unsafe { libc::ioctl(heap.as_raw_fd(), DMA_HEAP_IOCTL_ALLOC, &mut data) } < 0 {
+            stats::allocation_failed();
+            return Err(io::Error::last_os_error().into());
+        }
+
+        let fd = unsafe { OwnedFd::from_raw_fd(data.fd as i32) };
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                fd.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error().into());
+        }
+
+        stats::buffer_created(len);
+        Ok(Self {
+            ptr: ptr.cast(),
+            len,
+            fd,
+        })
+    }
+
+    /// Returns the dma-buf file descriptor, to be passed to the driver.
+    pub fn fd(&self) -> BorrowedFd<'_> {
+        self.fd.as_fd()
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr, self.len) }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the byte at the given index, `None` if out of bounds.
+    pub fn get(&self, index: usize) -> Option<&u8> {
+        self.as_slice().get(index)
+    }
+
+    /// Returns the bytes of the given range, `None` if out of bounds.
+    pub fn get_range(&self, range: Range<usize>) -> Option<&[u8]> {
+        self.as_slice().get(range)
+    }
+
+    pub fn iter(&self) -> Iter<'_, u8> {
+        self.as_slice().iter()
+    }
+
+    /// Returns an iterator over `chunk_size` bytes at a time, see [`slice::chunks`].
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `chunk_size` is 0.
+    pub fn chunks(&self, chunk_size: usize) -> Chunks<'_, u8> {
+        self.as_slice().chunks(chunk_size)
+    }
+
+    /// Returns an iterator over all overlapping windows of `size` bytes, see [`slice::windows`].
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `size` is 0.
+    pub fn windows(&self, size: usize) -> Windows<'_, u8> {
+        self.as_slice().windows(size)
+    }
+
+    /// Converts the buffer into a byte buffer pointing to the mapping, the buffer stays
+    /// alive until it is converted back with [`DmaBuffer::from_byte_buffer`].
+    ///
+    /// The CPU caches are flushed for the device, see [`flush_for_device`].
+    pub fn into_byte_buffer(self) -> ByteBuffer {
+        unsafe { flush_for_device(self.ptr, self.len) };
+
+        let buffer = ByteBuffer {
+            ptr: self.ptr,
+            len: self.len,
+        };
+        EXPORTED
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(self.ptr as usize, self);
+
+        buffer
+    }
+
+    /// Converts the given byte buffer back into the DMA buffer.
+    ///
+    /// Returns the byte buffer unchanged if it was not created by [`DmaBuffer::into_byte_buffer`],
+    /// so it can be reclaimed as a regular boxed byte slice.
+    ///
+    /// The CPU caches are invalidated, see [`invalidate_for_cpu`].
+    pub fn from_byte_buffer(buffer: ByteBuffer) -> Result<Self, ByteBuffer> {
+        let dma_buffer = EXPORTED
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&(buffer.ptr as usize))
+            .ok_or(buffer)?;
+        unsafe { invalidate_for_cpu(dma_buffer.ptr, dma_buffer.len) };
+
+        Ok(dma_buffer)
+    }
+}
+
+impl Drop for DmaBuffer {
+    fn drop(&mut self) {
+        unsafe { libc::munmap(self.ptr.cast(), self.len) };
+        stats::buffer_reclaimed(self.len);
+    }
+}
+
+/// Cleans the data cache lines of the given memory, so a device reads the bytes written by the CPU.
+///
+/// Only implemented on `aarch64`, a no-op on other platforms (cache coherent DMA is assumed).
+///
+/// # Safety
+///
+/// The given memory must be mapped.
+pub unsafe fn flush_for_device(ptr: *const u8, len: usize) {
+    #[cfg(target_arch = "aarch64")]
+    unsafe {
+        for_each_cache_line(
+            ptr,
+            len,
+            |line| std::arch::asm!("dc cvac, {}", in(reg) line, options(nostack, preserves_flags)),
+        );
+    }
+
+    #[cfg(not(target_arch = "aarch64"))]
+    let _ = (ptr, len);
+}
+
+/// Cleans and invalidates the data cache lines of the given memory,
+/// so the CPU reads the bytes written by a device instead of stale cached data.
+///
+/// Only implemented on `aarch64`, a no-op on other platforms (cache coherent DMA is assumed).
+///
+/// # Safety
+///
+/// The given memory must be mapped.
+pub unsafe fn invalidate_for_cpu(ptr: *const u8, len: usize) {
+    #[cfg(target_arch = "aarch64")]
+    unsafe {
+        for_each_cache_line(
+            ptr,
+            len,
+            |line| std::arch::asm!("dc civac, {}", in(reg) line, options(nostack, preserves_flags)),
+        );
+    }
+
+    #[cfg(not(target_arch = "aarch64"))]
+    let _ = (ptr, len);
+}
+
+/// Calls `op` with the address of each data cache line of the given memory,
+/// followed by a data synchronization barrier.
+#[cfg(target_arch = "aarch64")]
+unsafe fn for_each_cache_line(ptr: *const u8, len: usize, op: impl Fn(usize)) {
+    if len == 0 {
+        return;
+    }
+
+    // `CTR_EL0.DminLine` is the log2 of the smallest data cache line size in 4 byte words.
+    let ctr: u64;
+    unsafe { std::arch::asm!("mrs {}, ctr_el0", out(reg) ctr, options(nomem, nostack)) };
+    let line_size = 4usize << ((ctr >> 16) & 0xf);
+
+    let start = ptr as usize & !(line_size - 1);
+    let end = ptr as usize + len;
+    for line in (start..end).step_by(line_size) {
+        op(line);
+    }
+
+    unsafe { std::arch::asm!("dsb sy", options(nostack, preserves_flags)) };
+}
+
+impl Deref for DmaBuffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl DerefMut for DmaBuffer {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        self.as_mut_slice()
+    }
+}
+
+impl AsMut<[u8]> for DmaBuffer {
+    fn as_mut(&mut self) -> &mut [u8] {
+        self.as_mut_slice()
+    }
+}
+
+impl AsRef<[u8]> for DmaBuffer {
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl Borrow<[u8]> for DmaBuffer {
+    fn borrow(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+/// Panics if the index is out of bounds, see [`DmaBuffer::get`] and [`DmaBuffer::get_range`].
+impl<I: SliceIndex<[u8]>> Index<I> for DmaBuffer {
+    type Output = I::Output;
+
+    fn index(&self, index: I) -> &I::Output {
+        &self.as_slice()[index]
+    }
+}
+
+impl<I: SliceIndex<[u8]>> IndexMut<I> for DmaBuffer {
+    fn index_mut(&mut self, index: I) -> &mut I::Output {
+        &mut self.as_mut_slice()[index]
+    }
+}
+
+impl<'a> IntoIterator for &'a DmaBuffer {
+    type Item = &'a u8;
+    type IntoIter = Iter<'a, u8>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Compares the contents, consistent with `[u8]` (see [`Borrow`]).
+impl PartialEq for DmaBuffer {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl Eq for DmaBuffer {}
+
+impl PartialOrd for DmaBuffer {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for DmaBuffer {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_slice().cmp(other.as_slice())
+    }
+}
+
+impl Hash for DmaBuffer {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state);
+    }
+}
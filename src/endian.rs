@@ -0,0 +1,61 @@
+//! In-place byte order conversion of buffers made of fixed size elements,
+//! e.g. big-endian `u16`/`u32`/`f32` sensor payloads.
+//!
+//! Elements are treated as opaque groups of `element_size` bytes, so the element type
+//! only matters for its size (`f32` is swapped like `u32`).
+
+/// Reverses the byte order of each element of `element_size` bytes of the given bytes.
+///
+/// Returns false (and leaves the bytes untouched) if `element_size` is not 2, 4, 8 or 16
+/// or the bytes are not made of whole elements.
+pub fn swap_bytes_in_place(bytes: &mut [u8], element_size: usize) -> bool {
+    if !is_whole_elements(bytes, element_size) {
+        return false;
+    }
+
+    for element in bytes.chunks_exact_mut(element_size) {
+        element.reverse();
+    }
+
+    true
+}
+
+/// Converts the big-endian elements of the given bytes to the native byte order,
+/// a no-op (after validation) on big-endian targets, see [`swap_bytes_in_place`].
+pub fn from_be_in_place(bytes: &mut [u8], element_size: usize) -> bool {
+    if cfg!(target_endian = "big") {
+        return is_whole_elements(bytes, element_size);
+    }
+
+    swap_bytes_in_place(bytes, element_size)
+}
+
+/// Converts the little-endian elements of the given bytes to the native byte order,
+/// a no-op (after validation) on little-endian targets, see [`swap_bytes_in_place`].
+pub fn from_le_in_place(bytes: &mut [u8], element_size: usize) -> bool {
+    if cfg!(target_endian = "little") {
+        return is_whole_elements(bytes, element_size);
+    }
+
+    swap_bytes_in_place(bytes, element_size)
+}
+
+/// Reverses the byte order of each `u16` of the given bytes, see [`swap_bytes_in_place`].
+pub fn swap_u16_in_place(bytes: &mut [u8]) -> bool {
+    swap_bytes_in_place(bytes, size_of::<u16>())
+}
+
+/// Reverses the byte order of each `u32` of the given bytes, see [`swap_bytes_in_place`].
+pub fn swap_u32_in_place(bytes: &mut [u8]) -> bool {
+    swap_bytes_in_place(bytes, size_of::<u32>())
+}
+
+/// Reverses the byte order of each `f32` of the given bytes, see [`swap_bytes_in_place`].
+pub fn swap_f32_in_place(bytes: &mut [u8]) -> bool {
+    swap_bytes_in_place(bytes, size_of::<f32>())
+}
+
+// Returns true if `element_size` is supported and the bytes are made of whole elements.
+fn is_whole_elements(bytes: &[u8], element_size: usize) -> bool {
+    matches!(element_size, 2 | 4 | 8 | 16) && bytes.len().is_multiple_of(element_size)
+}
@@ -0,0 +1,86 @@
+//! Versioned envelope of a payload, for forward compatibility between independently
+//! shipped host and rust libraries.
+//!
+//! The envelope is a header followed by the payload, all integers are little endian:
+//! the [`MAGIC`] bytes, the `u16` envelope format [`VERSION`], `u16` flags (0, reserved),
+//! the `u32` schema version of the payload and the `u64` payload length.
+
+use crate::{ByteBuffer, borrowed::c_bytes_as_slice_ref, error::FfiBufferError};
+
+/// Magic bytes an envelope starts with.
+pub const MAGIC: [u8; 4] = *b"FBBE";
+/// Envelope format version written by [`wrap`], the only version [`unwrap`] accepts.
+pub const VERSION: u16 = 1;
+/// Length of the envelope header in bytes.
+pub const HEADER_LEN: usize = 20;
+
+/// Returns the given payload wrapped in an envelope with the given `schema_version`.
+pub fn wrap(payload: &[u8], schema_version: u32) -> Box<[u8]> {
+    let mut bytes = Vec::with_capacity(HEADER_LEN + payload.len());
+    bytes.extend_from_slice(&MAGIC);
+    bytes.extend_from_slice(&VERSION.to_le_bytes());
+    bytes.extend_from_slice(&0u16.to_le_bytes());
+    bytes.extend_from_slice(&schema_version.to_le_bytes());
+    bytes.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+    bytes.extend_from_slice(payload);
+
+    bytes.into_boxed_slice()
+}
+
+/// Returns the given payload wrapped in an envelope as byte buffer, to be passed to the host,
+/// see [`wrap`].
+pub fn wrap_into_byte_buffer(payload: &[u8], schema_version: u32) -> ByteBuffer {
+    ByteBuffer::from_boxed_slice(wrap(payload, schema_version))
+}
+
+/// Validates the given envelope and returns its payload and schema version.
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::InvalidMagic`] if the bytes don't start with [`MAGIC`],
+/// [`FfiBufferError::UnsupportedVersion`] if the envelope format is not [`VERSION`],
+/// [`FfiBufferError::Codec`] if the header is truncated or the payload length doesn't match.
+pub fn unwrap_slice(bytes: &[u8]) -> Result<(&[u8], u32), FfiBufferError> {
+    let Some((header, payload)) = bytes.split_first_chunk::<HEADER_LEN>() else {
+        if let Some(magic) = bytes.first_chunk::<4>().filter(|magic| **magic != MAGIC) {
+            return Err(FfiBufferError::InvalidMagic(*magic));
+        }
+        return Err(FfiBufferError::Codec(
+            "truncated envelope header".to_string(),
+        ));
+    };
+
+    let magic = [header[0], header[1], header[2], header[3]];
+    if magic != MAGIC {
+        return Err(FfiBufferError::InvalidMagic(magic));
+    }
+    let version = u16::from_le_bytes([header[4], header[5]]);
+    if version != VERSION {
+        return Err(FfiBufferError::UnsupportedVersion(version));
+    }
+
+    let schema_version = u32::from_le_bytes([header[8], header[9], header[10], header[11]]);
+    let len = u64::from_le_bytes(header[12..].try_into().expect("8 bytes"));
+    if len != payload.len() as u64 {
+        return Err(FfiBufferError::Codec(format!(
+            "envelope payload length {len}, {} bytes received",
+            payload.len()
+        )));
+    }
+
+    Ok((payload, schema_version))
+}
+
+/// Validates the given envelope, e.g. received from the host, and returns its payload
+/// and schema version, see [`unwrap_slice`].
+///
+/// # Errors
+///
+/// See [`unwrap_slice`] and [`c_bytes_as_slice_ref`].
+///
+/// # Safety
+///
+/// The bytes must be valid (not deallocated) while the returned payload is used.
+pub unsafe fn unwrap<'a>(ptr: *const u8, len: usize) -> Result<(&'a [u8], u32), FfiBufferError> {
+    unwrap_slice(unsafe { c_bytes_as_slice_ref(ptr, len) }?)
+}
@@ -0,0 +1,160 @@
+//! Error reporting to non-rust callers.
+
+use std::{cell::RefCell, io, str::Utf8Error};
+
+/// FFI compatible status code of a call.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FfiStatus {
+    Ok = 0,
+    /// A panic occurred - the panic message is available as last error.
+    Panic = 1,
+    /// An invalid argument (e.g. a null pointer) was given.
+    InvalidArgument = 2,
+    /// The given bytes are not valid in the expected text encoding.
+    InvalidEncoding = 3,
+    /// The given destination buffer is too small - the required size is reported.
+    BufferTooSmall = 4,
+    /// An allocation failed or its size overflowed.
+    AllocationFailed = 5,
+    /// An index or range is out of the bounds of a buffer.
+    OutOfBounds = 6,
+    /// A registry (e.g. of handles) rejected the operation.
+    Registry = 7,
+    /// Encoding or decoding a payload failed.
+    Codec = 8,
+    /// An I/O operation of the OS failed.
+    Io = 9,
+    /// The given handle was valid once, but its buffer was freed since.
+    StaleHandle = 10,
+    /// Locking pages in memory exceeds the limit of locked memory (e.g. `RLIMIT_MEMLOCK`).
+    LockLimit = 11,
+    /// A buffer received back from the host was corrupted (see [`crate::checked`]).
+    Corrupted = 12,
+}
+
+/// Failure of a fallible operation of this crate.
+#[derive(Debug, thiserror::Error)]
+pub enum FfiBufferError {
+    #[error("allocation of {len} bytes failed")]
+    Alloc { len: usize },
+    #[error("capacity overflow")]
+    CapacityOverflow,
+    #[error(transparent)]
+    Utf8(#[from] Utf8Error),
+    #[error("index {index} out of bounds of length {len}")]
+    OutOfBounds { index: usize, len: usize },
+    #[error("invalid argument: {0}")]
+    InvalidArgument(&'static str),
+    #[error("registry error: {0}")]
+    Registry(&'static str),
+    #[error("codec error: {0}")]
+    Codec(String),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("stale handle {0:#x}")]
+    StaleHandle(u64),
+    #[error("locking {len} bytes in memory exceeds the limit of locked memory")]
+    LockLimit { len: usize },
+    #[error("invalid magic {0:02x?}")]
+    InvalidMagic([u8; 4]),
+    #[error("unsupported version {0}")]
+    UnsupportedVersion(u16),
+    #[error("buffer {ptr:#x} of length {len} corrupted ({label})")]
+    Corrupted {
+        ptr: usize,
+        len: usize,
+        label: &'static str,
+    },
+}
+
+impl FfiBufferError {
+    /// Returns the FFI status code of the error.
+    pub fn code(&self) -> FfiStatus {
+        match self {
+            Self::Alloc { .. } | Self::CapacityOverflow => FfiStatus::AllocationFailed,
+            Self::Utf8(_) => FfiStatus::InvalidEncoding,
+            Self::OutOfBounds { .. } => FfiStatus::OutOfBounds,
+            Self::InvalidArgument(_) => FfiStatus::InvalidArgument,
+            Self::Registry(_) => FfiStatus::Registry,
+            Self::Codec(_) | Self::InvalidMagic(_) | Self::UnsupportedVersion(_) => {
+                FfiStatus::Codec
+            }
+            Self::Io(_) => FfiStatus::Io,
+            Self::StaleHandle(_) => FfiStatus::StaleHandle,
+            Self::LockLimit { .. } => FfiStatus::LockLimit,
+            Self::Corrupted { .. } => FfiStatus::Corrupted,
+        }
+    }
+
+    /// Stores the error as last error of the current thread and returns its status code,
+    /// e.g. to be returned by an exported function.
+    pub fn report(self) -> FfiStatus {
+        let status = self.code();
+        set_last_error(self.into());
+        status
+    }
+}
+
+/// Error with a status code and a message, stored as last error of a thread.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    status: FfiStatus,
+    message: String,
+}
+
+impl Error {
+    pub fn new(status: FfiStatus, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+
+    pub fn status(&self) -> FfiStatus {
+        self.status
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Stores the error as last error of the current thread and returns its status code,
+    /// e.g. to be returned by an exported function.
+    pub fn report(self) -> FfiStatus {
+        let status = self.status;
+        set_last_error(self);
+        status
+    }
+}
+
+impl From<FfiBufferError> for Error {
+    fn from(error: FfiBufferError) -> Self {
+        Self::new(error.code(), error.to_string())
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<Error>> = const { RefCell::new(None) };
+}
+
+/// Sets the last error of the current thread, replacing a previous one.
+pub fn set_last_error(error: Error) {
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(error));
+}
+
+/// Clears the last error of the current thread, exported functions do on entry.
+pub fn clear_last_error() {
+    LAST_ERROR.with(|last| *last.borrow_mut() = None);
+}
+
+/// Takes the last error of the current thread, if there is one.
+pub fn take_last_error() -> Option<Error> {
+    LAST_ERROR.with(|last| last.borrow_mut().take())
+}
+
+/// Returns the status code of the last error of the current thread without taking it,
+/// if there is one.
+pub fn last_error_status() -> Option<FfiStatus> {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map(Error::status))
+}
@@ -0,0 +1,1944 @@
+//! `extern "C"` functions with stable symbol names, to be called directly by
+//! the FFI client or hosts.
+
+use std::{
+    borrow::Cow,
+    ffi::{CStr, c_char, c_void},
+    time::Duration,
+};
+
+#[cfg(feature = "shm")]
+use crate::shm::ShmMailbox;
+use crate::{
+    ByteBuffer, FfiBuffer, FfiBufferArray, FfiCow, FfiMapEntry, FfiSliceMut, FfiSliceRef,
+    OwnedVecBuffer, SecureByteBuffer,
+    allocator::{self, FfiAllocatorVTable},
+    arena::Arena,
+    audio, audit, blit, checked, endian, envelope,
+    error::{self, Error, FfiBufferError, FfiStatus},
+    handles::{self, BufferHandle},
+    hash64,
+    intern::{self, FfiInternStats, InternHandle},
+    lifecycle::{self, FfiInitConfig},
+    logging::{self, FfiLogCallback},
+    new_zeroed_boxed_byte_slice,
+    oom::{self, FfiOomHandler},
+    poison,
+    pool::{self, FfiPoolStats},
+    replace, slice_mut, slice_ref,
+    stats::{self, FfiMemoryReport},
+    unwind::{self, FfiUnwindPolicy, ffi_fn},
+    upload::{self, FfiUploadBuffer},
+    watchdog::{self, FfiDumpCallback},
+};
+
+ffi_fn! {
+    /// Initializes the library, see [`lifecycle::init`].
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if the pool buffer size of `config` is 0.
+    ///
+    /// # Safety
+    ///
+    /// The given `config` must be null (the default configuration is used then)
+    /// or valid while this function is in process.
+    pub unsafe fn ffi_byte_buffer_init(config: *const FfiInitConfig) -> FfiStatus {
+        let config = unsafe { config.as_ref() }.copied().unwrap_or_default();
+        match lifecycle::init(&config) {
+            Ok(()) => FfiStatus::Ok,
+            Err(error) => error.report(),
+        }
+    }
+}
+
+ffi_fn! {
+    /// Shuts the library down, see [`lifecycle::shutdown`].
+    pub fn ffi_byte_buffer_shutdown() {
+        lifecycle::shutdown();
+    }
+}
+
+ffi_fn! {
+    /// Registers the given `callback` to receive all diagnostics,
+    /// null restores the default (stderr), see [`logging::set_log_callback`].
+    ///
+    /// # Safety
+    ///
+    /// The given `ctx` must be valid, to be passed to `callback` from any thread,
+    /// until another callback is registered.
+    pub unsafe fn set_log_callback(ctx: *mut c_void, callback: Option<FfiLogCallback>) {
+        unsafe { logging::set_log_callback(ctx, callback) };
+    }
+}
+
+ffi_fn! {
+    /// Returns the pointer width of the library in bits, e.g. 32 on wasm32 and 64 on wasm64,
+    /// to select between [`ByteBuffer32`](crate::width::ByteBuffer32) and
+    /// [`ByteBuffer64`](crate::width::ByteBuffer64).
+    pub fn ffi_pointer_width() -> u32 {
+        usize::BITS
+    }
+}
+
+ffi_fn! {
+    /// Sets the global unwind policy, see [`unwind::set_unwind_policy`].
+    pub fn set_unwind_policy(policy: FfiUnwindPolicy) {
+        unwind::set_unwind_policy(policy);
+    }
+}
+
+ffi_fn! {
+    /// Registers the given `handler` to be called after an allocation failed,
+    /// null restores the default (fail), see [`oom::set_oom_handler`].
+    ///
+    /// # Safety
+    ///
+    /// The given `ctx` must be valid, to be passed to `handler` from any thread,
+    /// until another handler is registered.
+    pub unsafe fn set_oom_handler(ctx: *mut c_void, handler: Option<FfiOomHandler>) {
+        unsafe { oom::set_oom_handler(ctx, handler) };
+    }
+}
+
+ffi_fn! {
+    /// Registers the host allocator functions, see [`allocator::set_allocator`].
+    /// Only effective if the library uses [`allocator::HostAllocator`] as global allocator.
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if `vtable` is null or an allocator is registered already.
+    ///
+    /// # Safety
+    ///
+    /// The given `vtable` must be null or valid while this function is in process,
+    /// see [`allocator::set_allocator`] for the functions.
+    pub unsafe fn set_allocator(vtable: *const FfiAllocatorVTable) -> FfiStatus {
+        let Some(&vtable) = (unsafe { vtable.as_ref() }) else {
+            return FfiBufferError::InvalidArgument("null `vtable`").report();
+        };
+        if !unsafe { allocator::set_allocator(vtable) } {
+            return FfiBufferError::InvalidArgument("an allocator is registered already").report();
+        }
+
+        FfiStatus::Ok
+    }
+}
+
+ffi_fn! {
+    /// Writes the current buffer counters (see [`stats::BufferStats::snapshot`]) to `out`.
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if `out` is null.
+    ///
+    /// # Safety
+    ///
+    /// The given `out` must be null or valid for writes.
+    #[cfg(feature = "stats")]
+    pub unsafe fn get_buffer_stats(out: *mut stats::BufferStats) -> FfiStatus {
+        if out.is_null() {
+            return FfiBufferError::InvalidArgument("null `out`").report();
+        }
+
+        unsafe { out.write(stats::BufferStats::snapshot()) };
+
+        FfiStatus::Ok
+    }
+}
+
+ffi_fn! {
+    /// Sets the limit of the live bytes, 0 removes the limit, see [`stats::set_memory_limit`].
+    pub fn set_memory_limit(limit: usize) {
+        stats::set_memory_limit((limit != 0).then_some(limit));
+    }
+}
+
+ffi_fn! {
+    /// Returns the sum of the lengths of all live buffers, see [`stats::current_usage`].
+    pub fn get_current_usage() -> usize {
+        stats::current_usage()
+    }
+}
+
+ffi_fn! {
+    /// Returns the highest number of live bytes so far, see [`stats::peak_usage`].
+    pub fn get_peak_usage() -> usize {
+        stats::peak_usage()
+    }
+}
+
+ffi_fn! {
+    /// Writes the current memory usage report (see [`stats::memory_report`]) to `out`.
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if `out` is null.
+    ///
+    /// # Safety
+    ///
+    /// The given `out` must be null or valid for writes.
+    pub unsafe fn get_memory_report(out: *mut FfiMemoryReport) -> FfiStatus {
+        if out.is_null() {
+            return FfiBufferError::InvalidArgument("null `out`").report();
+        }
+
+        unsafe { out.write(stats::memory_report()) };
+
+        FfiStatus::Ok
+    }
+}
+
+ffi_fn! {
+    @keep_last_error
+    /// Returns the status code of the last error of the calling thread without taking it
+    /// (see [`error::last_error_status`]), 0 ([`FfiStatus::Ok`]) if there is none.
+    ///
+    /// Exported functions clear the last error on entry and store an error with a message
+    /// whenever they fail (e.g. return a status other than [`FfiStatus::Ok`]), so it belongs
+    /// to the last call of an exported function.
+    pub fn ffi_last_error_code() -> i32 {
+        error::last_error_status().unwrap_or(FfiStatus::Ok) as i32
+    }
+}
+
+ffi_fn! {
+    @keep_last_error
+    /// Takes the last error of the calling thread (see [`error::take_last_error`]) and returns
+    /// its message as UTF-8 string buffer, an empty buffer if there is none.
+    ///
+    /// Note: The error is cleared afterwards, [`ffi_last_error_code`] must be called before.
+    /// The buffer must be released with [`ffi_string_free`].
+    pub fn ffi_last_error_message() -> ByteBuffer {
+        match error::take_last_error() {
+            Some(error) => crate::string_into_byte_buffer(error.message().to_string()),
+            None => ByteBuffer::EMPTY,
+        }
+    }
+}
+
+ffi_fn! {
+    /// Returns the canonical empty byte buffer, a null `ptr` and a `len` of 0 (see [`ByteBuffer::EMPTY`]).
+    pub fn ffi_empty_buffer() -> ByteBuffer {
+        ByteBuffer::EMPTY
+    }
+}
+
+ffi_fn! {
+    /// Allocates a new zeroed byte buffer with the given `len`, see [`crate::new_byte_buffer`].
+    ///
+    /// Returns an empty buffer if `len` is 0 or the allocation failed.
+    /// The buffer must be released with [`ffi_byte_buffer_free`].
+    pub fn ffi_byte_buffer_alloc(len: usize) -> ByteBuffer {
+        crate::new_byte_buffer(len)
+    }
+}
+
+ffi_fn! {
+    /// Writes a new zeroed byte buffer with the given `len` to `out`,
+    /// see [`crate::try_new_boxed_byte_slice_buffer_raw`].
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if `len` is 0 or `out` is null,
+    /// [`FfiStatus::AllocationFailed`] if the size overflows or the allocation failed.
+    /// The buffer must be released with [`ffi_byte_buffer_free`].
+    ///
+    /// # Safety
+    ///
+    /// The given `out` must be null or valid for writes.
+    pub unsafe fn ffi_byte_buffer_try_alloc(len: usize, out: *mut ByteBuffer) -> FfiStatus {
+        if out.is_null() {
+            return FfiBufferError::InvalidArgument("null `out`").report();
+        }
+
+        match crate::try_new_boxed_byte_slice_buffer_raw(len) {
+            Ok(ptr) => {
+                unsafe { out.write(ByteBuffer::from_raw(ptr.as_ptr(), len)) };
+                FfiStatus::Ok
+            }
+            Err(error) => error.report(),
+        }
+    }
+}
+
+ffi_fn! {
+    /// Reallocates the given `buffer` in place to `new_len`,
+    /// see [`crate::grow_boxed_byte_slice_buffer_raw`].
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if `buffer` is null,
+    /// [`FfiStatus::AllocationFailed`] if the reallocation failed (`buffer` is unchanged then).
+    /// A `new_len` of 0 frees the buffer and leaves it empty.
+    ///
+    /// # Safety
+    ///
+    /// The given `buffer` must be null or valid for reads and writes,
+    /// and hold a buffer returned by this library.
+    pub unsafe fn ffi_byte_buffer_grow(buffer: *mut ByteBuffer, new_len: usize) -> FfiStatus {
+        let Some(buffer) = (unsafe { buffer.as_mut() }) else {
+            return FfiBufferError::InvalidArgument("null `buffer`").report();
+        };
+
+        let ptr = unsafe { crate::grow_boxed_byte_slice_buffer_raw(buffer.ptr, buffer.len, new_len) };
+        if ptr.is_null() && new_len > 0 {
+            return FfiBufferError::Alloc { len: new_len }.report();
+        }
+
+        *buffer = ByteBuffer::from_raw(ptr, new_len);
+        FfiStatus::Ok
+    }
+}
+
+ffi_fn! {
+    /// Writes a new byte buffer with a copy of the given byte range to `out`.
+    ///
+    /// The buffer must be released with [`ffi_byte_buffer_free`].
+    ///
+    /// # Safety
+    ///
+    /// The byte range must be valid (not deallocated) while this function is in process,
+    /// a null pointer is only valid with a length of 0.
+    /// The given `out` must be null or valid for writes.
+    pub unsafe fn ffi_byte_buffer_from_bytes(ptr: *const u8, len: usize, out: *mut ByteBuffer) -> FfiStatus {
+        let Some(bytes) = (unsafe { slice_ref(ptr, len) }) else {
+            return FfiBufferError::InvalidArgument("null `ptr` with a non-zero length").report();
+        };
+        if out.is_null() {
+            return FfiBufferError::InvalidArgument("null `out`").report();
+        }
+
+        unsafe { out.write(ByteBuffer::from(bytes)) };
+
+        FfiStatus::Ok
+    }
+}
+
+ffi_fn! {
+    /// Writes a new byte buffer with the given byte range wrapped in an envelope with the given
+    /// `schema_version` to `out`, see [`envelope::wrap`].
+    ///
+    /// The buffer must be released with [`ffi_byte_buffer_free`].
+    ///
+    /// # Safety
+    ///
+    /// The byte range must be valid (not deallocated) while this function is in process,
+    /// a null pointer is only valid with a length of 0.
+    /// The given `out` must be null or valid for writes.
+    pub unsafe fn ffi_envelope_wrap(
+        ptr: *const u8,
+        len: usize,
+        schema_version: u32,
+        out: *mut ByteBuffer,
+    ) -> FfiStatus {
+        let Some(payload) = (unsafe { slice_ref(ptr, len) }) else {
+            return FfiBufferError::InvalidArgument("null `ptr` with a non-zero length").report();
+        };
+        if out.is_null() {
+            return FfiBufferError::InvalidArgument("null `out`").report();
+        }
+
+        unsafe { out.write(envelope::wrap_into_byte_buffer(payload, schema_version)) };
+
+        FfiStatus::Ok
+    }
+}
+
+ffi_fn! {
+    /// Validates the given envelope and writes its payload (pointing into the envelope)
+    /// to `out_payload` and its schema version to `out_version`, see [`envelope::unwrap`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if an out pointer is null,
+    /// [`FfiStatus::Codec`] if the magic, the envelope version or the lengths are invalid.
+    ///
+    /// # Safety
+    ///
+    /// The envelope must be valid (not deallocated) while the payload is used,
+    /// a null pointer is only valid with a length of 0.
+    /// The given out pointers must be null or valid for writes.
+    pub unsafe fn ffi_envelope_unwrap(
+        ptr: *const u8,
+        len: usize,
+        out_payload: *mut FfiSliceRef,
+        out_version: *mut u32,
+    ) -> FfiStatus {
+        if out_payload.is_null() || out_version.is_null() {
+            return FfiBufferError::InvalidArgument("null `out_payload` or `out_version`").report();
+        }
+
+        match unsafe { envelope::unwrap(ptr, len) } {
+            Ok((payload, schema_version)) => {
+                unsafe {
+                    out_payload.write(FfiSliceRef::from_slice(payload));
+                    out_version.write(schema_version);
+                }
+                FfiStatus::Ok
+            }
+            Err(error) => error.report(),
+        }
+    }
+}
+
+ffi_fn! {
+    /// Releases the given byte buffer, an empty buffer is ignored.
+    ///
+    /// # Safety
+    ///
+    /// The buffer must have been created by this library as boxed byte slice (not by
+    /// [`FfiBuffer::into_byte_buffer`], see [`free_ffi_buffer`]) and must not be used afterwards.
+    pub unsafe fn ffi_byte_buffer_free(buffer: ByteBuffer) {
+        poison::drop_poisoned(unsafe { buffer.into_boxed_slice() });
+    }
+}
+
+ffi_fn! {
+    /// Releases the given copy-on-write buffer, only owned bytes are deallocated,
+    /// see [`FfiCow::free`].
+    ///
+    /// # Safety
+    ///
+    /// The buffer must have been created by this library and must not be used afterwards.
+    pub unsafe fn ffi_cow_free(buffer: FfiCow) {
+        if !buffer.is_owned() {
+            unsafe { buffer.free() };
+            return;
+        }
+        if let Cow::Owned(bytes) = unsafe { buffer.into_cow() } {
+            poison::drop_poisoned(bytes.into_boxed_slice());
+        }
+    }
+}
+
+ffi_fn! {
+    /// Allocates a new zeroed page-locked secure buffer with the given `len` and writes it
+    /// to `out`, see [`SecretBytes::new_locked`](crate::SecretBytes::new_locked).
+    /// If `best_effort` is true, the buffer falls back to unlocked heap memory if the pages
+    /// can't be locked.
+    ///
+    /// The buffer must be released with [`free_secure`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if `out` is null,
+    /// [`FfiStatus::AllocationFailed`] if the allocation failed,
+    /// [`FfiStatus::LockLimit`] if locking exceeds the limit of locked memory
+    /// and `best_effort` is false.
+    ///
+    /// # Safety
+    ///
+    /// `out` must be valid for writes.
+    #[cfg(feature = "mlock")]
+    pub unsafe fn ffi_secure_alloc_locked(
+        len: usize,
+        best_effort: bool,
+        out: *mut SecureByteBuffer,
+    ) -> FfiStatus {
+        if out.is_null() {
+            return FfiBufferError::InvalidArgument("null `out`").report();
+        }
+
+        let mode = if best_effort {
+            crate::LockMode::BestEffort
+        } else {
+            crate::LockMode::Required
+        };
+        match SecureByteBuffer::new_locked(len, mode) {
+            Ok(buffer) => {
+                unsafe { out.write(buffer) };
+                FfiStatus::Ok
+            }
+            Err(error) => error.report(),
+        }
+    }
+}
+
+ffi_fn! {
+    /// Zeroizes and releases the given secure buffer, see [`SecureByteBuffer::free`].
+    /// An empty buffer is ignored.
+    ///
+    /// # Safety
+    ///
+    /// The buffer must have been created by this library as boxed byte slice
+    /// and must not be used afterwards.
+    pub unsafe fn free_secure(buffer: SecureByteBuffer) {
+        unsafe { buffer.free() };
+    }
+}
+
+ffi_fn! {
+    /// Releases the given UTF-8 string buffer (e.g. of [`crate::string_into_byte_buffer`]),
+    /// an empty buffer is ignored.
+    ///
+    /// # Safety
+    ///
+    /// The buffer must have been created by this library and must not be used afterwards.
+    pub unsafe fn ffi_string_free(buffer: ByteBuffer) {
+        poison::drop_poisoned(unsafe { buffer.into_boxed_slice() });
+    }
+}
+
+ffi_fn! {
+    /// Releases the given buffer array including all of its items, see [`crate::free_buffer_array_raw`].
+    ///
+    /// # Safety
+    ///
+    /// The array must have been created by this library and must not be used afterwards.
+    pub unsafe fn ffi_buffer_array_free(array: FfiBufferArray) {
+        unsafe { crate::free_buffer_array_raw(array) };
+    }
+}
+
+ffi_fn! {
+    /// Releases the given array of string buffers including all of its items,
+    /// see [`crate::free_strings_raw`].
+    ///
+    /// # Safety
+    ///
+    /// The array must have been created by this library (see [`crate::strings_into_raw`])
+    /// and must not be used afterwards.
+    pub unsafe fn ffi_strings_free(ptr: *mut ByteBuffer, len: usize) {
+        unsafe { crate::free_strings_raw(ptr, len) };
+    }
+}
+
+ffi_fn! {
+    /// Releases the given array of byte buffers including all of its items,
+    /// see [`crate::free_byte_vecs_raw`].
+    ///
+    /// # Safety
+    ///
+    /// The array must have been created by this library (see [`crate::byte_vecs_into_raw`])
+    /// and must not be used afterwards.
+    pub unsafe fn ffi_byte_vecs_free(ptr: *mut ByteBuffer, len: usize) {
+        unsafe { crate::free_byte_vecs_raw(ptr, len) };
+    }
+}
+
+ffi_fn! {
+    /// Releases the given array of map entries including all of its keys and values,
+    /// see [`crate::free_map_raw`].
+    ///
+    /// # Safety
+    ///
+    /// The array must have been created by this library (see [`crate::map_into_raw`])
+    /// and must not be used afterwards.
+    pub unsafe fn ffi_map_free(ptr: *mut FfiMapEntry, len: usize) {
+        unsafe { crate::free_map_raw(ptr, len) };
+    }
+}
+
+ffi_fn! {
+    /// Adds a reference of the given shared payload, see [`crate::shared_clone_raw`].
+    ///
+    /// Returns false if the pointer is not a shared payload exported by this library.
+    /// Each reference must be released with [`ffi_shared_release`].
+    pub fn ffi_shared_clone(ptr: *const u8) -> bool {
+        crate::shared_clone_raw(ptr)
+    }
+}
+
+ffi_fn! {
+    /// Releases a reference of the given shared payload, see [`crate::shared_release_raw`].
+    ///
+    /// Returns false if the pointer is not a shared payload exported by this library.
+    /// The payload must not be used afterwards, unless other references are held.
+    pub fn ffi_shared_release(ptr: *const u8) -> bool {
+        crate::shared_release_raw(ptr)
+    }
+}
+
+ffi_fn! {
+    /// Returns true if the given byte ranges have equal content.
+    ///
+    /// # Safety
+    ///
+    /// Both byte ranges must be valid (not deallocated) while this function is in process.
+    /// A null pointer is only valid with a length of 0, otherwise false is returned.
+    pub unsafe fn buffer_eq(
+        a_ptr: *const u8,
+        a_len: usize,
+        b_ptr: *const u8,
+        b_len: usize,
+    ) -> bool {
+        match unsafe { (slice_ref(a_ptr, a_len), slice_ref(b_ptr, b_len)) } {
+            (Some(a), Some(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+ffi_fn! {
+    /// Returns true if the given byte ranges have equal content, compared in constant time
+    /// (see [`crate::ct_eq`]), e.g. tokens or MACs.
+    ///
+    /// # Safety
+    ///
+    /// Both byte ranges must be valid (not deallocated) while this function is in process.
+    /// A null pointer is only valid with a length of 0, otherwise false is returned.
+    pub unsafe fn buffer_ct_eq(
+        a_ptr: *const u8,
+        a_len: usize,
+        b_ptr: *const u8,
+        b_len: usize,
+    ) -> bool {
+        unsafe { crate::ct_eq_raw(a_ptr, a_len, b_ptr, b_len) }
+    }
+}
+
+ffi_fn! {
+    /// Returns the xxHash64 of the given byte range with the given `seed`.
+    ///
+    /// # Safety
+    ///
+    /// The byte range must be valid (not deallocated) while this function is in process.
+    /// A null pointer is only valid with a length of 0, otherwise 0 is returned.
+    pub unsafe fn buffer_hash64(ptr: *const u8, len: usize, seed: u64) -> u64 {
+        match unsafe { slice_ref(ptr, len) } {
+            Some(bytes) => hash64(bytes, seed),
+            None => 0,
+        }
+    }
+}
+
+ffi_fn! {
+    /// Interns the given byte range and writes its handle to `out`, see [`intern::intern`].
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if `out` is null or the byte range is invalid,
+    /// [`FfiStatus::Registry`] if the interning pool is full.
+    ///
+    /// # Safety
+    ///
+    /// The byte range must be valid (not deallocated) while this function is in process,
+    /// a null pointer is only valid with a length of 0. The given `out` must be null or valid for writes.
+    pub unsafe fn intern_bytes(
+        ptr: *const u8,
+        len: usize,
+        out: *mut InternHandle,
+    ) -> FfiStatus {
+        let Some(bytes) = (unsafe { slice_ref(ptr, len) }) else {
+            return FfiBufferError::InvalidArgument("null `ptr` with a non-zero length").report();
+        };
+        if out.is_null() {
+            return FfiBufferError::InvalidArgument("null `out`").report();
+        }
+
+        match intern::intern(bytes) {
+            Ok(handle) => {
+                unsafe { out.write(handle) };
+                FfiStatus::Ok
+            }
+            Err(error) => error.report(),
+        }
+    }
+}
+
+ffi_fn! {
+    /// Writes a copy of the payload of the given `handle` as byte buffer to `out`,
+    /// see [`intern::resolve_to_byte_buffer`].
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if `out` is null or the handle is unknown.
+    ///
+    /// # Safety
+    ///
+    /// The given `out` must be null or valid for writes.
+    pub unsafe fn resolve_interned(handle: InternHandle, out: *mut ByteBuffer) -> FfiStatus {
+        if out.is_null() {
+            return FfiBufferError::InvalidArgument("null `out`").report();
+        }
+        let Some(buffer) = intern::resolve_to_byte_buffer(handle) else {
+            return FfiBufferError::InvalidArgument("unknown `handle`").report();
+        };
+
+        unsafe { out.write(buffer) };
+
+        FfiStatus::Ok
+    }
+}
+
+ffi_fn! {
+    /// Writes the current statistics of the interning pool (see [`intern::intern_stats`]) to `out`.
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if `out` is null.
+    ///
+    /// # Safety
+    ///
+    /// The given `out` must be null or valid for writes.
+    pub unsafe fn get_intern_stats(out: *mut FfiInternStats) -> FfiStatus {
+        if out.is_null() {
+            return FfiBufferError::InvalidArgument("null `out`").report();
+        }
+
+        unsafe { out.write(intern::intern_stats()) };
+
+        FfiStatus::Ok
+    }
+}
+
+ffi_fn! {
+    /// Registers a new zeroed buffer with the given `len` and writes its handle to `out`,
+    /// see [`handles::register`].
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if `out` is null,
+    /// [`FfiStatus::AllocationFailed`] if the allocation failed,
+    /// [`FfiStatus::Registry`] if the registry is full.
+    /// The buffer must be released with [`buffer_handle_free`] or [`buffer_handle_take`].
+    ///
+    /// # Safety
+    ///
+    /// The given `out` must be null or valid for writes.
+    pub unsafe fn buffer_handle_alloc(len: usize, out: *mut BufferHandle) -> FfiStatus {
+        if out.is_null() {
+            return FfiBufferError::InvalidArgument("null `out`").report();
+        }
+
+        match new_zeroed_boxed_byte_slice(len).and_then(handles::register) {
+            Ok(handle) => {
+                unsafe { out.write(handle) };
+                FfiStatus::Ok
+            }
+            Err(error) => error.report(),
+        }
+    }
+}
+
+ffi_fn! {
+    /// Registers a copy of the given byte range and writes its handle to `out`,
+    /// see [`handles::register`].
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if the byte range is invalid or `out` is null,
+    /// [`FfiStatus::Registry`] if the registry is full.
+    /// The buffer must be released with [`buffer_handle_free`] or [`buffer_handle_take`].
+    ///
+    /// # Safety
+    ///
+    /// The byte range must be valid (not deallocated) while this function is in process,
+    /// a null pointer is only valid with a length of 0.
+    /// The given `out` must be null or valid for writes.
+    pub unsafe fn buffer_handle_from_bytes(ptr: *const u8, len: usize, out: *mut BufferHandle) -> FfiStatus {
+        let Some(bytes) = (unsafe { slice_ref(ptr, len) }) else {
+            return FfiBufferError::InvalidArgument("null `ptr` with a non-zero length").report();
+        };
+        if out.is_null() {
+            return FfiBufferError::InvalidArgument("null `out`").report();
+        }
+
+        match handles::register(bytes.into()) {
+            Ok(handle) => {
+                unsafe { out.write(handle) };
+                FfiStatus::Ok
+            }
+            Err(error) => error.report(),
+        }
+    }
+}
+
+ffi_fn! {
+    /// Writes the length of the buffer of the given `handle` to `out`.
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if `out` is null,
+    /// [`FfiStatus::StaleHandle`] if the buffer of the handle was freed,
+    /// [`FfiStatus::Registry`] if the handle is unknown.
+    ///
+    /// # Safety
+    ///
+    /// The given `out` must be null or valid for writes.
+    pub unsafe fn buffer_handle_len(handle: BufferHandle, out: *mut usize) -> FfiStatus {
+        if out.is_null() {
+            return FfiBufferError::InvalidArgument("null `out`").report();
+        }
+        match handles::get(handle, <[u8]>::len) {
+            Ok(len) => {
+                unsafe { out.write(len) };
+                FfiStatus::Ok
+            }
+            Err(error) => error.report(),
+        }
+    }
+}
+
+ffi_fn! {
+    /// Copies `len` bytes at `offset` of the buffer of the given `handle` to the destination.
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if the destination is invalid,
+    /// [`FfiStatus::StaleHandle`] if the buffer of the handle was freed,
+    /// [`FfiStatus::Registry`] if the handle is unknown,
+    /// [`FfiStatus::OutOfBounds`] if the range is not within the buffer.
+    ///
+    /// # Safety
+    ///
+    /// The destination must be valid for `len` writes while this function is in process,
+    /// a null pointer is only valid with a length of 0.
+    pub unsafe fn buffer_handle_read(
+        handle: BufferHandle,
+        offset: usize,
+        dst_ptr: *mut u8,
+        len: usize,
+    ) -> FfiStatus {
+        let Some(dst) = (unsafe { slice_mut(dst_ptr, len) }) else {
+            return FfiBufferError::InvalidArgument("null `dst_ptr` with a non-zero length").report();
+        };
+
+        let copied = handles::get(handle, |bytes| {
+            if !range_in_bounds(offset, len, bytes.len()) {
+                return out_of_bounds(offset, len, bytes.len());
+            }
+            dst.copy_from_slice(&bytes[offset..offset + len]);
+            FfiStatus::Ok
+        });
+
+        copied.unwrap_or_else(FfiBufferError::report)
+    }
+}
+
+ffi_fn! {
+    /// Copies the source (`len` bytes) to `offset` of the buffer of the given `handle`.
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if the source is invalid,
+    /// [`FfiStatus::StaleHandle`] if the buffer of the handle was freed,
+    /// [`FfiStatus::Registry`] if the handle is unknown or its buffer is in use,
+    /// [`FfiStatus::OutOfBounds`] if the range is not within the buffer.
+    ///
+    /// # Safety
+    ///
+    /// The source must be valid (not deallocated) while this function is in process,
+    /// a null pointer is only valid with a length of 0.
+    pub unsafe fn buffer_handle_write(
+        handle: BufferHandle,
+        offset: usize,
+        src_ptr: *const u8,
+        len: usize,
+    ) -> FfiStatus {
+        let Some(src) = (unsafe { slice_ref(src_ptr, len) }) else {
+            return FfiBufferError::InvalidArgument("null `src_ptr` with a non-zero length").report();
+        };
+
+        let copied = handles::get_mut(handle, |bytes| {
+            if !range_in_bounds(offset, len, bytes.len()) {
+                return out_of_bounds(offset, len, bytes.len());
+            }
+            bytes[offset..offset + len].copy_from_slice(src);
+            FfiStatus::Ok
+        });
+
+        copied.unwrap_or_else(FfiBufferError::report)
+    }
+}
+
+ffi_fn! {
+    /// Takes the buffer of the given `handle` out of the registry and writes it to `out`,
+    /// see [`handles::take`]. The handle is stale afterwards.
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if `out` is null,
+    /// [`FfiStatus::StaleHandle`] if the buffer of the handle was freed,
+    /// [`FfiStatus::Registry`] if the handle is unknown or its buffer is in use
+    /// (e.g. by a concurrent read). The buffer must be released with [`ffi_byte_buffer_free`].
+    ///
+    /// # Safety
+    ///
+    /// The given `out` must be null or valid for writes.
+    pub unsafe fn buffer_handle_take(handle: BufferHandle, out: *mut ByteBuffer) -> FfiStatus {
+        if out.is_null() {
+            return FfiBufferError::InvalidArgument("null `out`").report();
+        }
+        match handles::take(handle) {
+            Ok(buffer) => {
+                unsafe { out.write(ByteBuffer::from_boxed_slice_writable(buffer)) };
+                FfiStatus::Ok
+            }
+            Err(error) => error.report(),
+        }
+    }
+}
+
+ffi_fn! {
+    /// Releases the buffer of the given `handle`, see [`handles::free`].
+    /// The handle is stale afterwards.
+    ///
+    /// Returns [`FfiStatus::StaleHandle`] if the buffer of the handle was freed,
+    /// [`FfiStatus::Registry`] if the handle is unknown or its buffer is in use.
+    pub fn buffer_handle_free(handle: BufferHandle) -> FfiStatus {
+        match handles::free(handle) {
+            Ok(()) => FfiStatus::Ok,
+            Err(error) => error.report(),
+        }
+    }
+}
+
+ffi_fn! {
+    /// Creates an empty arena with blocks of at least `block_size` bytes
+    /// (see [`Arena::new`]) and writes its handle to `out_handle`.
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if `out_handle` is null or `block_size` is 0.
+    /// The arena must be released with [`ffi_arena_free`].
+    ///
+    /// # Safety
+    ///
+    /// The given `out_handle` must be null or valid for writes.
+    pub unsafe fn ffi_arena_new(block_size: usize, out_handle: *mut *mut Arena) -> FfiStatus {
+        if out_handle.is_null() {
+            return FfiBufferError::InvalidArgument("null `out_handle`").report();
+        }
+
+        match Arena::new(block_size) {
+            Ok(arena) => {
+                unsafe { out_handle.write(Box::into_raw(Box::new(arena))) };
+                FfiStatus::Ok
+            }
+            Err(error) => error.report(),
+        }
+    }
+}
+
+ffi_fn! {
+    /// Allocates a zeroed buffer of `len` bytes from the arena and writes it to `out`,
+    /// see [`Arena::alloc`]. The buffer is valid until the arena is reset or released.
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if `arena` or `out` is null,
+    /// [`FfiStatus::AllocationFailed`] if the allocation failed.
+    ///
+    /// # Safety
+    ///
+    /// The given `arena` must be null or a handle of [`ffi_arena_new`], not used by another
+    /// thread while this function is in process. The given `out` must be null or valid for writes.
+    pub unsafe fn ffi_arena_alloc(arena: *mut Arena, len: usize, out: *mut FfiSliceMut) -> FfiStatus {
+        if out.is_null() {
+            return FfiBufferError::InvalidArgument("null `out`").report();
+        }
+        let Some(arena) = (unsafe { arena.as_mut() }) else {
+            return FfiBufferError::InvalidArgument("null `arena`").report();
+        };
+
+        match arena.alloc(len) {
+            Ok(slice) => {
+                unsafe { out.write(slice) };
+                FfiStatus::Ok
+            }
+            Err(error) => error.report(),
+        }
+    }
+}
+
+ffi_fn! {
+    /// Allocates a copy of the given bytes from the arena and writes it to `out`,
+    /// see [`Arena::alloc_bytes`]. The copy is valid until the arena is reset or released.
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if `arena` or `out` is null or the bytes are invalid,
+    /// [`FfiStatus::AllocationFailed`] if the allocation failed.
+    ///
+    /// # Safety
+    ///
+    /// See [`ffi_arena_alloc`]. The given bytes must be valid while this function is in process,
+    /// a null pointer is only valid with a length of 0.
+    pub unsafe fn ffi_arena_alloc_bytes(
+        arena: *mut Arena,
+        ptr: *const u8,
+        len: usize,
+        out: *mut FfiSliceRef,
+    ) -> FfiStatus {
+        if out.is_null() {
+            return FfiBufferError::InvalidArgument("null `out`").report();
+        }
+        let (Some(arena), Some(src)) = (unsafe { arena.as_mut() }, unsafe { slice_ref(ptr, len) }) else {
+            return FfiBufferError::InvalidArgument("null `arena` or `ptr` with a non-zero length").report();
+        };
+
+        match arena.alloc_bytes(src) {
+            Ok(slice) => {
+                unsafe { out.write(slice) };
+                FfiStatus::Ok
+            }
+            Err(error) => error.report(),
+        }
+    }
+}
+
+ffi_fn! {
+    /// Invalidates all buffers allocated from the arena, see [`Arena::reset`].
+    /// A null `arena` is ignored.
+    ///
+    /// # Safety
+    ///
+    /// See [`ffi_arena_alloc`]. The buffers of the arena must not be used afterwards.
+    pub unsafe fn ffi_arena_reset(arena: *mut Arena) {
+        if let Some(arena) = unsafe { arena.as_mut() } {
+            arena.reset();
+        }
+    }
+}
+
+ffi_fn! {
+    /// Releases the arena including all of its buffers, a null `arena` is ignored.
+    ///
+    /// # Safety
+    ///
+    /// The given `arena` must be null or a handle of [`ffi_arena_new`], the arena and its buffers
+    /// must not be used afterwards.
+    pub unsafe fn ffi_arena_free(arena: *mut Arena) {
+        if !arena.is_null() {
+            drop(unsafe { Box::from_raw(arena) });
+        }
+    }
+}
+
+ffi_fn! {
+    /// Creates a shared-memory mailbox with the given name and a slot of `capacity` bytes
+    /// (see [`ShmMailbox::create`]) and writes its handle to `out_handle`.
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if `name` or `out_handle` is null or the name
+    /// is invalid, [`FfiStatus::InvalidEncoding`] if the name is not UTF-8,
+    /// [`FfiStatus::Io`] if the mailbox can't be created (e.g. the name exists).
+    ///
+    /// The mailbox must be released with [`ffi_shm_mailbox_free`].
+    ///
+    /// # Safety
+    ///
+    /// The given `name` must be null or a valid NUL terminated string,
+    /// `out_handle` must be null or valid for writes.
+    #[cfg(feature = "shm")]
+    pub unsafe fn ffi_shm_mailbox_create(
+        name: *const c_char,
+        capacity: usize,
+        out_handle: *mut *mut ShmMailbox,
+    ) -> FfiStatus {
+        let name = match unsafe { mailbox_name(name) } {
+            Ok(name) => name,
+            Err(status) => return status,
+        };
+        if out_handle.is_null() {
+            return FfiBufferError::InvalidArgument("null `out_handle`").report();
+        }
+
+        match ShmMailbox::create(name, capacity) {
+            Ok(mailbox) => {
+                unsafe { out_handle.write(Box::into_raw(Box::new(mailbox))) };
+                FfiStatus::Ok
+            }
+            Err(error) => error.report(),
+        }
+    }
+}
+
+ffi_fn! {
+    /// Opens the shared-memory mailbox with the given name (see [`ShmMailbox::open`])
+    /// and writes its handle to `out_handle`.
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if `name` or `out_handle` is null or the name
+    /// is invalid, [`FfiStatus::InvalidEncoding`] if the name is not UTF-8,
+    /// [`FfiStatus::Codec`] if the region is not an initialized mailbox,
+    /// [`FfiStatus::Io`] if the mailbox can't be opened (e.g. it doesn't exist).
+    ///
+    /// The mailbox must be released with [`ffi_shm_mailbox_free`].
+    ///
+    /// # Safety
+    ///
+    /// See [`ffi_shm_mailbox_create`].
+    #[cfg(feature = "shm")]
+    pub unsafe fn ffi_shm_mailbox_open(
+        name: *const c_char,
+        out_handle: *mut *mut ShmMailbox,
+    ) -> FfiStatus {
+        let name = match unsafe { mailbox_name(name) } {
+            Ok(name) => name,
+            Err(status) => return status,
+        };
+        if out_handle.is_null() {
+            return FfiBufferError::InvalidArgument("null `out_handle`").report();
+        }
+
+        match ShmMailbox::open(name) {
+            Ok(mailbox) => {
+                unsafe { out_handle.write(Box::into_raw(Box::new(mailbox))) };
+                FfiStatus::Ok
+            }
+            Err(error) => error.report(),
+        }
+    }
+}
+
+ffi_fn! {
+    /// Copies the given message into the slot of the mailbox, waits while the slot is full
+    /// for at most `timeout_ms` milliseconds (`u64::MAX` waits indefinitely),
+    /// see [`ShmMailbox::send`].
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if `mailbox` is null, the bytes are invalid
+    /// or longer than the capacity, [`FfiStatus::Io`] if the wait timed out or failed.
+    ///
+    /// # Safety
+    ///
+    /// The given `mailbox` must be null or a handle of [`ffi_shm_mailbox_create`] or
+    /// [`ffi_shm_mailbox_open`]. The given bytes must be valid while this function is in process,
+    /// a null pointer is only valid with a length of 0.
+    #[cfg(feature = "shm")]
+    pub unsafe fn ffi_shm_mailbox_send(
+        mailbox: *const ShmMailbox,
+        ptr: *const u8,
+        len: usize,
+        timeout_ms: u64,
+    ) -> FfiStatus {
+        let (Some(mailbox), Some(src)) = (unsafe { mailbox.as_ref() }, unsafe { slice_ref(ptr, len) }) else {
+            return FfiBufferError::InvalidArgument("null `mailbox` or `ptr` with a non-zero length").report();
+        };
+
+        match mailbox.send(src, mailbox_timeout(timeout_ms)) {
+            Ok(()) => FfiStatus::Ok,
+            Err(error) => error.report(),
+        }
+    }
+}
+
+ffi_fn! {
+    /// Takes the message out of the slot of the mailbox into a new buffer written to `out`,
+    /// waits while the slot is empty for at most `timeout_ms` milliseconds (`u64::MAX` waits
+    /// indefinitely), see [`ShmMailbox::recv`]. The buffer is released with
+    /// [`ffi_byte_buffer_free`].
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if `mailbox` or `out` is null,
+    /// [`FfiStatus::Io`] if the wait timed out or failed, [`FfiStatus::Codec`] if the message
+    /// is invalid, [`FfiStatus::AllocationFailed`] if the allocation failed.
+    ///
+    /// # Safety
+    ///
+    /// The given `mailbox` must be null or a handle of [`ffi_shm_mailbox_create`] or
+    /// [`ffi_shm_mailbox_open`], `out` must be null or valid for writes.
+    #[cfg(feature = "shm")]
+    pub unsafe fn ffi_shm_mailbox_recv(
+        mailbox: *const ShmMailbox,
+        timeout_ms: u64,
+        out: *mut ByteBuffer,
+    ) -> FfiStatus {
+        if out.is_null() {
+            return FfiBufferError::InvalidArgument("null `out`").report();
+        }
+        let Some(mailbox) = (unsafe { mailbox.as_ref() }) else {
+            return FfiBufferError::InvalidArgument("null `mailbox`").report();
+        };
+
+        match mailbox.recv(mailbox_timeout(timeout_ms)) {
+            Ok(bytes) => {
+                unsafe { out.write(ByteBuffer::from_boxed_slice(bytes)) };
+                FfiStatus::Ok
+            }
+            Err(error) => error.report(),
+        }
+    }
+}
+
+ffi_fn! {
+    /// Releases the given mailbox, the creator removes its name.
+    ///
+    /// # Safety
+    ///
+    /// The given `mailbox` must be null or a handle of [`ffi_shm_mailbox_create`] or
+    /// [`ffi_shm_mailbox_open`], not used by another thread and not used afterwards.
+    #[cfg(feature = "shm")]
+    pub unsafe fn ffi_shm_mailbox_free(mailbox: *mut ShmMailbox) {
+        if !mailbox.is_null() {
+            drop(unsafe { Box::from_raw(mailbox) });
+        }
+    }
+}
+
+// Reads the given mailbox name, a failure is reported and returned as status.
+#[cfg(feature = "shm")]
+unsafe fn mailbox_name<'a>(name: *const c_char) -> Result<&'a str, FfiStatus> {
+    if name.is_null() {
+        return Err(FfiBufferError::InvalidArgument("null `name`").report());
+    }
+
+    unsafe { CStr::from_ptr(name) }
+        .to_str()
+        .map_err(|_| Error::new(FfiStatus::InvalidEncoding, "`name` is not valid UTF-8").report())
+}
+
+#[cfg(feature = "shm")]
+fn mailbox_timeout(timeout_ms: u64) -> Option<Duration> {
+    (timeout_ms != u64::MAX).then(|| Duration::from_millis(timeout_ms))
+}
+
+ffi_fn! {
+    /// Replaces the global pool with an empty pool of buffers with `buffer_size` bytes,
+    /// which keeps at most `capacity` idle buffers, see [`pool::configure_global`].
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if `buffer_size` is 0.
+    pub fn ffi_pool_configure(buffer_size: usize, capacity: usize) -> FfiStatus {
+        match pool::configure_global(buffer_size, capacity) {
+            Ok(()) => FfiStatus::Ok,
+            Err(error) => error.report(),
+        }
+    }
+}
+
+ffi_fn! {
+    /// Checks out a buffer of `len` bytes from the global pool and writes it to `out`,
+    /// see [`pool::BufferPool::acquire`]. The bytes of a recycled buffer are not zeroed.
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if `out` is null or `len` exceeds the buffer size
+    /// of the pool, [`FfiStatus::AllocationFailed`] if the allocation failed.
+    /// The buffer must be returned with [`ffi_pool_return`].
+    ///
+    /// # Safety
+    ///
+    /// The given `out` must be null or valid for writes.
+    pub unsafe fn ffi_pool_checkout(len: usize, out: *mut ByteBuffer) -> FfiStatus {
+        if out.is_null() {
+            return FfiBufferError::InvalidArgument("null `out`").report();
+        }
+
+        match pool::global().acquire(len) {
+            Ok(buffer) => {
+                unsafe { out.write(buffer.into_byte_buffer()) };
+                FfiStatus::Ok
+            }
+            Err(error) => error.report(),
+        }
+    }
+}
+
+ffi_fn! {
+    /// Returns the given buffer (see [`ffi_pool_checkout`]) into its pool,
+    /// see [`pool::return_byte_buffer`].
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if the buffer was not checked out from a pool.
+    pub fn ffi_pool_return(buffer: ByteBuffer) -> FfiStatus {
+        match pool::return_byte_buffer(buffer) {
+            Ok(()) => FfiStatus::Ok,
+            Err(_) => FfiBufferError::InvalidArgument("not a pooled buffer").report(),
+        }
+    }
+}
+
+ffi_fn! {
+    /// Writes the current statistics of the global pool (see [`pool::BufferPool::stats`])
+    /// to `out`.
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if `out` is null.
+    ///
+    /// # Safety
+    ///
+    /// The given `out` must be null or valid for writes.
+    pub unsafe fn ffi_pool_stats(out: *mut FfiPoolStats) -> FfiStatus {
+        if out.is_null() {
+            return FfiBufferError::InvalidArgument("null `out`").report();
+        }
+
+        unsafe { out.write(pool::global().stats()) };
+
+        FfiStatus::Ok
+    }
+}
+
+ffi_fn! {
+    /// Releases the idle buffers of all pools and returns the number of released bytes,
+    /// see [`pool::trim_all`].
+    pub fn ffi_pool_trim() -> usize {
+        pool::trim_all()
+    }
+}
+
+ffi_fn! {
+    /// Copies the given byte range into a buffer with the given `alignment`, pinned until it is
+    /// released with [`release_after_upload`], and writes it to `out`, see [`upload::pin_for_upload`].
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if `out` is null, the byte range is invalid,
+    /// `alignment` is not a power of two or the allocation failed.
+    ///
+    /// # Safety
+    ///
+    /// The byte range must be valid (not deallocated) while this function is in process,
+    /// a null pointer is only valid with a length of 0. The given `out` must be null or valid for writes.
+    pub unsafe fn pin_for_upload(
+        ptr: *const u8,
+        len: usize,
+        alignment: usize,
+        out: *mut FfiUploadBuffer,
+    ) -> FfiStatus {
+        if out.is_null() {
+            return FfiBufferError::InvalidArgument("null `out`").report();
+        }
+        let Some(buffer) =
+            unsafe { slice_ref(ptr, len) }.and_then(|bytes| upload::pin_for_upload(bytes, alignment))
+        else {
+            return FfiBufferError::InvalidArgument("invalid byte range or `alignment`, or the allocation failed").report();
+        };
+
+        unsafe { out.write(buffer) };
+
+        FfiStatus::Ok
+    }
+}
+
+ffi_fn! {
+    /// Releases the pinned buffer with the given `handle`, see [`upload::release_after_upload`].
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if the handle is unknown.
+    pub fn release_after_upload(handle: u64) -> FfiStatus {
+        if !upload::release_after_upload(handle) {
+            return FfiBufferError::InvalidArgument("unknown `handle`").report();
+        }
+
+        FfiStatus::Ok
+    }
+}
+
+ffi_fn! {
+    /// Enables the audit trail keeping the last `capacity` entries, 0 disables it,
+    /// see [`audit::enable`].
+    pub fn enable_audit_log(capacity: usize) {
+        audit::enable(capacity);
+    }
+}
+
+ffi_fn! {
+    /// Writes the serialized audit trail (see [`audit::serialize`]) as byte buffer to `out`.
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if `out` is null.
+    ///
+    /// # Safety
+    ///
+    /// The given `out` must be null or valid for writes.
+    pub unsafe fn get_audit_log(out: *mut ByteBuffer) -> FfiStatus {
+        if out.is_null() {
+            return FfiBufferError::InvalidArgument("null `out`").report();
+        }
+
+        unsafe { out.write(audit::serialize()) };
+
+        FfiStatus::Ok
+    }
+}
+
+ffi_fn! {
+    /// Enables or disables the tracking of exported buffers, see [`watchdog::enable`].
+    pub fn enable_watchdog(enabled: bool) {
+        watchdog::enable(enabled);
+    }
+}
+
+ffi_fn! {
+    /// Enables or disables the integrity checks of read-only buffers handed to the host,
+    /// see [`checked::enable`].
+    pub fn enable_checked_buffers(enabled: bool) {
+        checked::enable(enabled);
+    }
+}
+
+ffi_fn! {
+    /// Enables or disables panicking on a detected buffer corruption,
+    /// see [`checked::set_panic_on_corruption`].
+    pub fn set_panic_on_corruption(panic: bool) {
+        checked::set_panic_on_corruption(panic);
+    }
+}
+
+ffi_fn! {
+    /// Returns the number of buffer corruptions detected so far.
+    pub fn get_corruption_count() -> u64 {
+        checked::corruption_count()
+    }
+}
+
+ffi_fn! {
+    /// Flags (logs) the exported buffers not reclaimed for at least `max_age_ms` milliseconds
+    /// and returns their number, see [`watchdog::check_stale`].
+    pub fn check_stale_buffers(max_age_ms: u64) -> usize {
+        watchdog::check_stale(Duration::from_millis(max_age_ms)).len()
+    }
+}
+
+ffi_fn! {
+    /// Passes one line per live buffer to the given `callback`, see [`watchdog::dump_live_buffers`].
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if `callback` is null,
+    /// [`FfiStatus::Registry`] if the registry is locked.
+    ///
+    /// # Safety
+    ///
+    /// The given `ctx` must be valid, to be passed to `callback`, while this function is in process.
+    pub unsafe fn dump_live_buffers(ctx: *mut c_void, callback: Option<FfiDumpCallback>) -> FfiStatus {
+        let Some(callback) = callback else {
+            return FfiBufferError::InvalidArgument("null `callback`").report();
+        };
+
+        match watchdog::dump_live_buffers(|line| unsafe { callback(ctx, line.as_ptr(), line.len()) }) {
+            Some(_) => FfiStatus::Ok,
+            None => FfiBufferError::Registry("the registry is locked").report(),
+        }
+    }
+}
+
+ffi_fn! {
+    /// Writes the live buffers to a new file at the given UTF-8 `path`,
+    /// see [`watchdog::dump_live_buffers_to_path`].
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if `path` is null, [`FfiStatus::InvalidEncoding`]
+    /// if it is not UTF-8, [`FfiStatus::Io`] if the file can't be written,
+    /// [`FfiStatus::Registry`] if the registry is locked.
+    ///
+    /// # Safety
+    ///
+    /// The given `path` must be null or a valid NUL terminated string.
+    pub unsafe fn dump_live_buffers_to_path(path: *const c_char) -> FfiStatus {
+        if path.is_null() {
+            return FfiBufferError::InvalidArgument("null `path`").report();
+        }
+
+        let Ok(path) = unsafe { CStr::from_ptr(path) }.to_str() else {
+            return Error::new(FfiStatus::InvalidEncoding, "`path` is not valid UTF-8").report();
+        };
+
+        match watchdog::dump_live_buffers_to_path(path) {
+            Ok(_) => FfiStatus::Ok,
+            Err(error) => error.report(),
+        }
+    }
+}
+
+ffi_fn! {
+    /// Converts the interleaved samples of the source byte range into planar samples
+    /// written to the destination byte range, see [`audio::deinterleave_into`].
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if a byte range is invalid, `channels` or
+    /// `sample_width` is 0, the source is not made of whole frames or the lengths differ.
+    ///
+    /// # Safety
+    ///
+    /// Both byte ranges must be valid (not deallocated) and not overlap while this function is
+    /// in process, a null pointer is only valid with a length of 0.
+    pub unsafe fn audio_deinterleave(
+        src_ptr: *const u8,
+        src_len: usize,
+        dst_ptr: *mut u8,
+        dst_len: usize,
+        channels: usize,
+        sample_width: usize,
+    ) -> FfiStatus {
+        let (Some(src), Some(dst)) =
+            (unsafe { (slice_ref(src_ptr, src_len), slice_mut(dst_ptr, dst_len)) })
+        else {
+            return FfiBufferError::InvalidArgument("null `src_ptr` or `dst_ptr` with a non-zero length").report();
+        };
+        if !audio::deinterleave_into(src, dst, channels, sample_width) {
+            return FfiBufferError::InvalidArgument("invalid `channels`, `sample_width` or lengths").report();
+        }
+
+        FfiStatus::Ok
+    }
+}
+
+ffi_fn! {
+    /// Converts the planar samples of the source byte range into interleaved samples
+    /// written to the destination byte range, see [`audio::interleave_into`].
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if a byte range is invalid, `channels` or
+    /// `sample_width` is 0, the source is not made of whole frames or the lengths differ.
+    ///
+    /// # Safety
+    ///
+    /// Both byte ranges must be valid (not deallocated) and not overlap while this function is
+    /// in process, a null pointer is only valid with a length of 0.
+    pub unsafe fn audio_interleave(
+        src_ptr: *const u8,
+        src_len: usize,
+        dst_ptr: *mut u8,
+        dst_len: usize,
+        channels: usize,
+        sample_width: usize,
+    ) -> FfiStatus {
+        let (Some(src), Some(dst)) =
+            (unsafe { (slice_ref(src_ptr, src_len), slice_mut(dst_ptr, dst_len)) })
+        else {
+            return FfiBufferError::InvalidArgument("null `src_ptr` or `dst_ptr` with a non-zero length").report();
+        };
+        if !audio::interleave_into(src, dst, channels, sample_width) {
+            return FfiBufferError::InvalidArgument("invalid `channels`, `sample_width` or lengths").report();
+        }
+
+        FfiStatus::Ok
+    }
+}
+
+ffi_fn! {
+    /// Copies the rectangle of `width` x `height` bytes between the given byte ranges,
+    /// their rows starting `src_stride` and `dst_stride` bytes apart, see [`blit::copy_rect`].
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if a byte range is invalid, a row is wider than
+    /// its stride or a byte range is too short for the rectangle.
+    ///
+    /// # Safety
+    ///
+    /// Both byte ranges must be valid (not deallocated) and not overlap while this function is
+    /// in process, a null pointer is only valid with a length of 0.
+    pub unsafe fn copy_rect(
+        src_ptr: *const u8,
+        src_len: usize,
+        src_stride: usize,
+        dst_ptr: *mut u8,
+        dst_len: usize,
+        dst_stride: usize,
+        width: usize,
+        height: usize,
+    ) -> FfiStatus {
+        let (Some(src), Some(dst)) =
+            (unsafe { (slice_ref(src_ptr, src_len), slice_mut(dst_ptr, dst_len)) })
+        else {
+            return FfiBufferError::InvalidArgument("null `src_ptr` or `dst_ptr` with a non-zero length").report();
+        };
+        if !blit::copy_rect(src, src_stride, dst, dst_stride, width, height) {
+            return FfiBufferError::InvalidArgument("invalid rectangle of the byte ranges").report();
+        }
+
+        FfiStatus::Ok
+    }
+}
+
+ffi_fn! {
+    /// Copies `len` bytes at `src_off` of the source byte range to `dst_off` of the destination
+    /// byte range, with `memmove` semantics (the byte ranges may overlap).
+    ///
+    /// Both byte ranges must be buffers exported by this library with their exported length,
+    /// while the tracking is enabled (see [`enable_watchdog`]), or empty.
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if a byte range is invalid, not of a tracked
+    /// buffer or its length differs from the buffer's, [`FfiStatus::OutOfBounds`] if one of the
+    /// copied ranges exceeds its byte range.
+    ///
+    /// # Safety
+    ///
+    /// Both byte ranges must be valid (not deallocated) while this function is in process,
+    /// a null pointer is only valid with a length of 0.
+    pub unsafe fn buffer_copy(
+        dst_ptr: *mut u8,
+        dst_len: usize,
+        dst_off: usize,
+        src_ptr: *const u8,
+        src_len: usize,
+        src_off: usize,
+        len: usize,
+    ) -> FfiStatus {
+        if (dst_ptr.is_null() && dst_len != 0) || (src_ptr.is_null() && src_len != 0) {
+            return FfiBufferError::InvalidArgument("null `dst_ptr` or `src_ptr` with a non-zero length").report();
+        }
+        if let Err(status) = check_exported(dst_ptr, dst_len).and_then(|()| check_exported(src_ptr, src_len)) {
+            return status;
+        }
+        if !range_in_bounds(dst_off, len, dst_len) {
+            return out_of_bounds(dst_off, len, dst_len);
+        }
+        if !range_in_bounds(src_off, len, src_len) {
+            return out_of_bounds(src_off, len, src_len);
+        }
+
+        if len != 0 {
+            unsafe { std::ptr::copy(src_ptr.add(src_off), dst_ptr.add(dst_off), len) };
+        }
+
+        FfiStatus::Ok
+    }
+}
+
+ffi_fn! {
+    /// Sets `len` bytes at `off` of the given byte range to `value`, with `memset` semantics.
+    ///
+    /// The byte range must be a tracked buffer with its exported length, see [`buffer_copy`].
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if the byte range is invalid, not of a tracked
+    /// buffer or its length differs from the buffer's, [`FfiStatus::OutOfBounds`] if the filled
+    /// range exceeds the byte range.
+    ///
+    /// # Safety
+    ///
+    /// The byte range must be valid (not deallocated) while this function is in process,
+    /// a null pointer is only valid with a length of 0.
+    pub unsafe fn buffer_fill(
+        dst_ptr: *mut u8,
+        dst_len: usize,
+        off: usize,
+        len: usize,
+        value: u8,
+    ) -> FfiStatus {
+        let Some(dst) = (unsafe { slice_mut(dst_ptr, dst_len) }) else {
+            return FfiBufferError::InvalidArgument("null `dst_ptr` with a non-zero length").report();
+        };
+        if let Err(status) = check_exported(dst_ptr, dst_len) {
+            return status;
+        }
+        if !range_in_bounds(off, len, dst_len) {
+            return out_of_bounds(off, len, dst_len);
+        }
+
+        dst[off..off + len].fill(value);
+
+        FfiStatus::Ok
+    }
+}
+
+ffi_fn! {
+    /// Writes the byte at `offset` of the given byte range to `out`.
+    ///
+    /// The byte range must be a tracked buffer with its exported length, see [`buffer_copy`].
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if the byte range is invalid, not of a tracked
+    /// buffer or its length differs from the buffer's, or `out` is null,
+    /// [`FfiStatus::OutOfBounds`] if `offset` is not within the byte range.
+    ///
+    /// # Safety
+    ///
+    /// The byte range must be valid (not deallocated) while this function is in process,
+    /// a null pointer is only valid with a length of 0.
+    /// The given `out` must be null or valid for writes.
+    pub unsafe fn buffer_read_at(ptr: *const u8, len: usize, offset: usize, out: *mut u8) -> FfiStatus {
+        let Some(bytes) = (unsafe { slice_ref(ptr, len) }) else {
+            return FfiBufferError::InvalidArgument("null `ptr` with a non-zero length").report();
+        };
+        if out.is_null() {
+            return FfiBufferError::InvalidArgument("null `out`").report();
+        }
+        if let Err(status) = check_exported(ptr, len) {
+            return status;
+        }
+
+        match bytes.get(offset) {
+            Some(&byte) => {
+                unsafe { out.write(byte) };
+                FfiStatus::Ok
+            }
+            None => FfiBufferError::OutOfBounds { index: offset, len }.report(),
+        }
+    }
+}
+
+ffi_fn! {
+    /// Sets the byte at `offset` of the given byte range to `value`.
+    ///
+    /// The byte range must be a tracked buffer with its exported length, see [`buffer_copy`].
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if the byte range is invalid, not of a tracked
+    /// buffer or its length differs from the buffer's,
+    /// [`FfiStatus::OutOfBounds`] if `offset` is not within the byte range.
+    ///
+    /// # Safety
+    ///
+    /// The byte range must be valid (not deallocated) while this function is in process,
+    /// a null pointer is only valid with a length of 0.
+    pub unsafe fn buffer_write_at(ptr: *mut u8, len: usize, offset: usize, value: u8) -> FfiStatus {
+        let Some(bytes) = (unsafe { slice_mut(ptr, len) }) else {
+            return FfiBufferError::InvalidArgument("null `ptr` with a non-zero length").report();
+        };
+        if let Err(status) = check_exported(ptr, len) {
+            return status;
+        }
+
+        match bytes.get_mut(offset) {
+            Some(byte) => {
+                *byte = value;
+                FfiStatus::Ok
+            }
+            None => FfiBufferError::OutOfBounds { index: offset, len }.report(),
+        }
+    }
+}
+
+ffi_fn! {
+    /// Copies the `dst_len` bytes at `offset` of the given byte range to the destination byte range.
+    ///
+    /// The given byte range must be a tracked buffer with its exported length (see
+    /// [`buffer_copy`]), the destination byte range may be any memory of the host.
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if a byte range is invalid, the given byte range
+    /// is not of a tracked buffer or its length differs from the buffer's,
+    /// [`FfiStatus::OutOfBounds`] if the read range exceeds the byte range.
+    ///
+    /// # Safety
+    ///
+    /// Both byte ranges must be valid (not deallocated) and not overlap while this function is
+    /// in process, a null pointer is only valid with a length of 0.
+    pub unsafe fn buffer_read_range(
+        ptr: *const u8,
+        len: usize,
+        offset: usize,
+        dst_ptr: *mut u8,
+        dst_len: usize,
+    ) -> FfiStatus {
+        let (Some(bytes), Some(dst)) = (unsafe { (slice_ref(ptr, len), slice_mut(dst_ptr, dst_len)) })
+        else {
+            return FfiBufferError::InvalidArgument("null `ptr` or `dst_ptr` with a non-zero length").report();
+        };
+        if let Err(status) = check_exported(ptr, len) {
+            return status;
+        }
+        if !range_in_bounds(offset, dst_len, len) {
+            return out_of_bounds(offset, dst_len, len);
+        }
+
+        dst.copy_from_slice(&bytes[offset..offset + dst_len]);
+
+        FfiStatus::Ok
+    }
+}
+
+ffi_fn! {
+    /// Copies the source byte range to `offset` of the given byte range.
+    ///
+    /// The given byte range must be a tracked buffer with its exported length (see
+    /// [`buffer_copy`]), the source byte range may be any memory of the host.
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if a byte range is invalid, the given byte range
+    /// is not of a tracked buffer or its length differs from the buffer's,
+    /// [`FfiStatus::OutOfBounds`] if the written range exceeds the byte range.
+    ///
+    /// # Safety
+    ///
+    /// Both byte ranges must be valid (not deallocated) and not overlap while this function is
+    /// in process, a null pointer is only valid with a length of 0.
+    pub unsafe fn buffer_write_range(
+        ptr: *mut u8,
+        len: usize,
+        offset: usize,
+        src_ptr: *const u8,
+        src_len: usize,
+    ) -> FfiStatus {
+        let (Some(bytes), Some(src)) = (unsafe { (slice_mut(ptr, len), slice_ref(src_ptr, src_len)) })
+        else {
+            return FfiBufferError::InvalidArgument("null `ptr` or `src_ptr` with a non-zero length").report();
+        };
+        if let Err(status) = check_exported(ptr, len) {
+            return status;
+        }
+        if !range_in_bounds(offset, src_len, len) {
+            return out_of_bounds(offset, src_len, len);
+        }
+
+        bytes[offset..offset + src_len].copy_from_slice(src);
+
+        FfiStatus::Ok
+    }
+}
+
+// Returns true if `len` bytes at `off` are within a byte range of `bounds` bytes.
+fn range_in_bounds(off: usize, len: usize, bounds: usize) -> bool {
+    off.checked_add(len).is_some_and(|end| end <= bounds)
+}
+
+// Checks the given byte range against the length recorded for the exported buffer at `ptr`
+// (see `watchdog`), so a stale or wrong length can't reach beyond the buffer. A failure is
+// reported and returned as status, an empty null range is valid.
+fn check_exported(ptr: *const u8, len: usize) -> Result<(), FfiStatus> {
+    if ptr.is_null() && len == 0 {
+        return Ok(());
+    }
+
+    match watchdog::exported_len(ptr) {
+        None => Err(FfiBufferError::InvalidArgument("pointer of an untracked buffer").report()),
+        Some(exported) if exported != len => {
+            Err(FfiBufferError::InvalidArgument("length differs from the exported buffer").report())
+        }
+        Some(_) => Ok(()),
+    }
+}
+
+// Reports `len` bytes at `off` exceeding a byte range of `bounds` bytes as last error.
+fn out_of_bounds(off: usize, len: usize, bounds: usize) -> FfiStatus {
+    FfiBufferError::OutOfBounds {
+        index: off.saturating_add(len),
+        len: bounds,
+    }
+    .report()
+}
+
+ffi_fn! {
+    /// Writes a new byte buffer with all occurrences of the `needle` in the source byte range
+    /// replaced with the `replacement` to `out`, see [`replace::replace_all`].
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if a byte range is invalid, `needle` is empty
+    /// or `out` is null.
+    ///
+    /// # Safety
+    ///
+    /// All byte ranges must be valid (not deallocated) while this function is in process,
+    /// a null pointer is only valid with a length of 0.
+    /// The given `out` must be null or valid for writes.
+    pub unsafe fn buffer_replace_all(
+        src_ptr: *const u8,
+        src_len: usize,
+        needle_ptr: *const u8,
+        needle_len: usize,
+        replacement_ptr: *const u8,
+        replacement_len: usize,
+        out: *mut ByteBuffer,
+    ) -> FfiStatus {
+        let (Some(src), Some(needle), Some(replacement)) = (unsafe {
+            (
+                slice_ref(src_ptr, src_len),
+                slice_ref(needle_ptr, needle_len),
+                slice_ref(replacement_ptr, replacement_len),
+            )
+        }) else {
+            return FfiBufferError::InvalidArgument("null byte range with a non-zero length").report();
+        };
+        if out.is_null() {
+            return FfiBufferError::InvalidArgument("null `out`").report();
+        }
+
+        match replace::replace_all(src, needle, replacement) {
+            Ok(buffer) => {
+                unsafe { out.write(buffer) };
+                FfiStatus::Ok
+            }
+            Err(error) => error.report(),
+        }
+    }
+}
+
+ffi_fn! {
+    /// Reverses the byte order of each element of `element_size` bytes of the given byte range
+    /// in place, see [`endian::swap_bytes_in_place`].
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if the byte range is invalid, `element_size`
+    /// is not 2, 4, 8 or 16 or the byte range is not made of whole elements.
+    ///
+    /// # Safety
+    ///
+    /// The byte range must be valid (not deallocated) while this function is in process,
+    /// a null pointer is only valid with a length of 0.
+    pub unsafe fn buffer_swap_bytes(ptr: *mut u8, len: usize, element_size: usize) -> FfiStatus {
+        let Some(bytes) = (unsafe { slice_mut(ptr, len) }) else {
+            return FfiBufferError::InvalidArgument("null `ptr` with a non-zero length").report();
+        };
+        if !endian::swap_bytes_in_place(bytes, element_size) {
+            return FfiBufferError::InvalidArgument("invalid `element_size` of the byte range").report();
+        }
+
+        FfiStatus::Ok
+    }
+}
+
+ffi_fn! {
+    /// Frees the given byte buffer, created from an [`FfiBuffer`] (see [`FfiBuffer::into_byte_buffer`]).
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] (and leaves the bytes untouched)
+    /// if the byte buffer was not created from an [`FfiBuffer`].
+    pub fn free_ffi_buffer(buffer: ByteBuffer) -> FfiStatus {
+        match FfiBuffer::from_byte_buffer(buffer) {
+            Ok(buffer) => {
+                drop(buffer);
+                FfiStatus::Ok
+            }
+            Err(_) => FfiBufferError::InvalidArgument("not an `FfiBuffer`").report(),
+        }
+    }
+}
+
+ffi_fn! {
+    /// Allocates a new empty vector buffer with a capacity of exactly `cap` bytes, e.g. for
+    /// the host to fill the bytes and set `len`. The canonical empty buffer is returned
+    /// if `cap` is 0 or the allocation failed.
+    pub fn new_vec_buffer(cap: usize) -> OwnedVecBuffer {
+        let mut vec = Vec::new();
+        if vec.try_reserve_exact(cap).is_err() {
+            return OwnedVecBuffer::EMPTY;
+        }
+
+        OwnedVecBuffer::from_vec(vec)
+    }
+}
+
+ffi_fn! {
+    /// Frees the given vector buffer, created with [`OwnedVecBuffer::from_vec`],
+    /// with its original allocation.
+    ///
+    /// # Safety
+    ///
+    /// See [`OwnedVecBuffer::into_vec`].
+    pub unsafe fn free_vec_buffer(buffer: OwnedVecBuffer) {
+        drop(unsafe { buffer.into_vec() });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Exports a tracked buffer of `len` zeroed bytes, released with `ffi_byte_buffer_free`.
+    fn tracked_buffer(len: usize) -> ByteBuffer {
+        watchdog::enable(true);
+        ffi_byte_buffer_alloc(len)
+    }
+
+    #[test]
+    fn copy_and_fill_reject_untracked_pointers() {
+        watchdog::enable(true);
+        let mut bytes = [0u8; 4];
+
+        let status = unsafe { buffer_fill(bytes.as_mut_ptr(), 4, 0, 4, 0xff) };
+        assert_eq!(status, FfiStatus::InvalidArgument);
+        let status = unsafe { buffer_copy(bytes.as_mut_ptr(), 4, 0, bytes.as_ptr(), 4, 2, 2) };
+        assert_eq!(status, FfiStatus::InvalidArgument);
+        assert_eq!(bytes, [0; 4]);
+    }
+
+    #[test]
+    fn copy_and_fill_reject_wrong_lengths() {
+        let dst = tracked_buffer(4);
+        let src = tracked_buffer(4);
+
+        // A longer (e.g. stale) length can't write beyond the buffer.
+        let status = unsafe { buffer_fill(dst.ptr, 8, 0, 8, 0xff) };
+        assert_eq!(status, FfiStatus::InvalidArgument);
+        let status = unsafe { buffer_copy(dst.ptr, 4, 0, src.ptr, 16, 0, 4) };
+        assert_eq!(status, FfiStatus::InvalidArgument);
+
+        assert_eq!(
+            unsafe { buffer_fill(src.ptr, 4, 1, 2, 0xff) },
+            FfiStatus::Ok
+        );
+        assert_eq!(
+            unsafe { buffer_copy(dst.ptr, 4, 0, src.ptr, 4, 0, 4) },
+            FfiStatus::Ok
+        );
+        assert_eq!(
+            unsafe { std::slice::from_raw_parts(dst.ptr, 4) },
+            [0, 0xff, 0xff, 0]
+        );
+
+        unsafe {
+            ffi_byte_buffer_free(dst);
+            ffi_byte_buffer_free(src);
+        }
+    }
+
+    #[test]
+    fn accessors_reject_untracked_pointers() {
+        watchdog::enable(true);
+        let mut bytes = [1u8, 2, 3, 4];
+        let mut out = 0;
+
+        let status = unsafe { buffer_read_at(bytes.as_ptr(), 4, 0, &mut out) };
+        assert_eq!(status, FfiStatus::InvalidArgument);
+        let status = unsafe { buffer_write_at(bytes.as_mut_ptr(), 4, 0, 0xff) };
+        assert_eq!(status, FfiStatus::InvalidArgument);
+        let status = unsafe { buffer_read_range(bytes.as_ptr(), 4, 0, &mut out, 1) };
+        assert_eq!(status, FfiStatus::InvalidArgument);
+        assert_eq!((bytes, out), ([1, 2, 3, 4], 0));
+    }
+
+    #[test]
+    fn accessors_reject_wrong_lengths() {
+        let buffer = tracked_buffer(4);
+        let mut out = 0;
+
+        // The offset is within the given length, but beyond the buffer.
+        let status = unsafe { buffer_write_at(buffer.ptr, 64, 32, 0xff) };
+        assert_eq!(status, FfiStatus::InvalidArgument);
+        let status = unsafe { buffer_read_at(buffer.ptr, 64, 32, &mut out) };
+        assert_eq!(status, FfiStatus::InvalidArgument);
+        let status = unsafe { buffer_write_range(buffer.ptr, 8, 4, [7; 4].as_ptr(), 4) };
+        assert_eq!(status, FfiStatus::InvalidArgument);
+
+        assert_eq!(
+            unsafe { buffer_write_at(buffer.ptr, 4, 3, 0xff) },
+            FfiStatus::Ok
+        );
+        assert_eq!(
+            unsafe { buffer_read_at(buffer.ptr, 4, 3, &mut out) },
+            FfiStatus::Ok
+        );
+        assert_eq!(out, 0xff);
+        assert_eq!(
+            unsafe { buffer_write_at(buffer.ptr, 4, 4, 0) },
+            FfiStatus::OutOfBounds
+        );
+
+        unsafe { ffi_byte_buffer_free(buffer) };
+    }
+}
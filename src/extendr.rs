@@ -0,0 +1,56 @@
+//! R interop (extendr), converting byte buffers to/from R raw vectors and
+//! string buffers to/from R character vectors.
+//!
+//! R vectors are owned by the R garbage collector, so every conversion copies the bytes.
+
+use extendr_api::prelude::*;
+
+use crate::{ByteBuffer, FfiBufferArray};
+
+/// Returns a new R raw vector with a copy of the given bytes.
+pub fn bytes_to_raw(bytes: &[u8]) -> Raw {
+    Raw::from_bytes(bytes)
+}
+
+/// Returns a new R raw vector with a copy of the bytes of the given buffer
+/// and deallocates the buffer.
+///
+/// # Safety
+///
+/// The buffer must have been created by this crate and must not be used afterwards.
+pub unsafe fn byte_buffer_into_raw(buffer: ByteBuffer) -> Raw {
+    let bytes = unsafe { buffer.into_boxed_slice() };
+    Raw::from_bytes(&bytes)
+}
+
+/// Returns a new byte buffer with a copy of the bytes of the given R raw vector.
+pub fn raw_to_byte_buffer(src: &Raw) -> ByteBuffer {
+    ByteBuffer::from_boxed_slice(Box::from(src.as_slice()))
+}
+
+/// Returns a new R character vector of length 1 with a copy of the string of
+/// the given buffer and deallocates the buffer.
+///
+/// Invalid UTF-8 sequences are replaced with `U+FFFD`.
+///
+/// # Safety
+///
+/// The buffer must have been created by this crate and must not be used afterwards.
+pub unsafe fn string_buffer_into_strings(buffer: ByteBuffer) -> Strings {
+    let bytes = unsafe { buffer.into_boxed_slice() };
+    Strings::from_values([String::from_utf8_lossy(&bytes)])
+}
+
+/// Returns a new buffer array with a copy of every string of the given
+/// R character vector, `NA` strings become empty buffers.
+pub fn strings_to_buffer_array(src: &Strings) -> FfiBufferArray {
+    let items = src
+        .iter()
+        .map(|s| match s.is_na() {
+            true => Box::default(),
+            false => Box::from(s.as_bytes()),
+        })
+        .collect();
+
+    FfiBufferArray::from_boxed_slices(items)
+}
@@ -0,0 +1,84 @@
+//! "You allocate, I fill" - copying into buffers provided by the caller, for hosts which
+//! don't free rust allocations.
+//!
+//! Two-call pattern: the host calls once with a null pointer to query the required size,
+//! allocates a buffer of this size and calls again to get it filled.
+
+/// FFI compatible result of a fill, the number of bytes `written` into the caller's buffer
+/// and the number of bytes `required` to hold the whole data.
+///
+/// Note: `written` is 0 if the caller's buffer is too small (or null), nothing is copied then.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct FillResult {
+    pub written: usize,
+    pub required: usize,
+}
+
+impl FillResult {
+    /// Returns true if the whole data was written.
+    pub fn is_complete(&self) -> bool {
+        self.written == self.required
+    }
+}
+
+/// Copies `src` into the given caller-provided buffer, if the buffer holds all of `src`.
+///
+/// Call with a null `dst_ptr` (or a `dst_len` of 0) to query the required size.
+///
+/// # Safety
+///
+/// The given buffer must be null or valid for writes of `dst_len` bytes (and must not overlap
+/// with `src`) while this function is in process.
+pub unsafe fn copy_into_caller_buffer(src: &[u8], dst_ptr: *mut u8, dst_len: usize) -> FillResult {
+    let required = src.len();
+    if dst_ptr.is_null() || dst_len < required {
+        return FillResult {
+            written: 0,
+            required,
+        };
+    }
+
+    unsafe { std::ptr::copy_nonoverlapping(src.as_ptr(), dst_ptr, required) };
+
+    FillResult {
+        written: required,
+        required,
+    }
+}
+
+/// Copies `src` as NUL terminated string into the given caller-provided buffer,
+/// if the buffer holds all of `src` and the NUL byte, see [`copy_into_caller_buffer`].
+///
+/// The `required` size and the `written` bytes include the NUL byte.
+///
+/// Note: An interior NUL byte of `src` is copied as is, so C truncates the string there.
+///
+/// # Safety
+///
+/// The given buffer must be null or valid for writes of `dst_len` bytes (and must not overlap
+/// with `src`) while this function is in process.
+pub unsafe fn copy_str_into_caller_buffer(
+    src: &str,
+    dst_ptr: *mut u8,
+    dst_len: usize,
+) -> FillResult {
+    // Doesn't overflow, the length of a string is at most `isize::MAX`.
+    let required = src.len() + 1;
+    if dst_ptr.is_null() || dst_len < required {
+        return FillResult {
+            written: 0,
+            required,
+        };
+    }
+
+    unsafe {
+        std::ptr::copy_nonoverlapping(src.as_ptr(), dst_ptr, src.len());
+        dst_ptr.add(src.len()).write(0);
+    }
+
+    FillResult {
+        written: required,
+        required,
+    }
+}
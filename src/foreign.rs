@@ -0,0 +1,184 @@
+//! Buffers owned by a foreign party (the host or a C library), received without copying.
+
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    ops::{Deref, Index, Range},
+    slice::{Chunks, Iter, SliceIndex, Windows},
+};
+
+use crate::FfiBuffer;
+
+/// Bytes owned by a foreign party, which are released by a callback when dropped.
+pub struct ForeignBuffer {
+    ptr: *const u8,
+    len: usize,
+    release: Option<Box<dyn FnOnce() + Send>>,
+}
+
+// The creator guarantees that the bytes and the release callback can be used from any thread.
+unsafe impl Send for ForeignBuffer {}
+unsafe impl Sync for ForeignBuffer {}
+
+impl ForeignBuffer {
+    /// Adopts the given foreign bytes, `release` is called once when the buffer is dropped.
+    ///
+    /// # Safety
+    ///
+    /// The given bytes must be valid (not deallocated or written to) from any thread
+    /// until `release` is called. A null pointer is only valid with a length of 0.
+    pub unsafe fn new(ptr: *const u8, len: usize, release: impl FnOnce() + Send + 'static) -> Self {
+        Self {
+            ptr,
+            len,
+            release: Some(Box::new(release)),
+        }
+    }
+
+    /// Returns the bytes of the buffer.
+    pub fn as_slice(&self) -> &[u8] {
+        if self.len == 0 {
+            return &[];
+        }
+
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns a copy of the bytes in a new (rust owned) buffer labeled `ForeignBuffer`.
+    ///
+    /// Returns `None` if the allocation failed.
+    pub fn try_deep_clone(&self) -> Option<FfiBuffer> {
+        let mut clone = unsafe {
+            FfiBuffer::builder(self.len)
+                .label("ForeignBuffer")
+                .build_uninit()
+        }?;
+        clone.as_mut_slice().copy_from_slice(self.as_slice());
+
+        Some(clone)
+    }
+
+    /// Returns the byte at the given index, `None` if out of bounds.
+    pub fn get(&self, index: usize) -> Option<&u8> {
+        self.as_slice().get(index)
+    }
+
+    /// Returns the bytes of the given range, `None` if out of bounds.
+    pub fn get_range(&self, range: Range<usize>) -> Option<&[u8]> {
+        self.as_slice().get(range)
+    }
+
+    pub fn iter(&self) -> Iter<'_, u8> {
+        self.as_slice().iter()
+    }
+
+    /// Returns an iterator over `chunk_size` bytes at a time, see [`slice::chunks`].
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `chunk_size` is 0.
+    pub fn chunks(&self, chunk_size: usize) -> Chunks<'_, u8> {
+        self.as_slice().chunks(chunk_size)
+    }
+
+    /// Returns an iterator over all overlapping windows of `size` bytes, see [`slice::windows`].
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `size` is 0.
+    pub fn windows(&self, size: usize) -> Windows<'_, u8> {
+        self.as_slice().windows(size)
+    }
+}
+
+impl Drop for ForeignBuffer {
+    fn drop(&mut self) {
+        if let Some(release) = self.release.take() {
+            release();
+        }
+    }
+}
+
+impl fmt::Debug for ForeignBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ForeignBuffer")
+            .field("ptr", &self.ptr)
+            .field("len", &self.len)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Deref for ForeignBuffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl AsRef<[u8]> for ForeignBuffer {
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl Borrow<[u8]> for ForeignBuffer {
+    fn borrow(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+/// Panics if the index is out of bounds, see [`ForeignBuffer::get`] and [`ForeignBuffer::get_range`].
+impl<I: SliceIndex<[u8]>> Index<I> for ForeignBuffer {
+    type Output = I::Output;
+
+    fn index(&self, index: I) -> &I::Output {
+        &self.as_slice()[index]
+    }
+}
+
+impl<'a> IntoIterator for &'a ForeignBuffer {
+    type Item = &'a u8;
+    type IntoIter = Iter<'a, u8>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Compares the contents, consistent with `[u8]` (see [`Borrow`]).
+impl PartialEq for ForeignBuffer {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl Eq for ForeignBuffer {}
+
+impl PartialOrd for ForeignBuffer {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ForeignBuffer {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_slice().cmp(other.as_slice())
+    }
+}
+
+impl Hash for ForeignBuffer {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state);
+    }
+}
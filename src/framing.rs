@@ -0,0 +1,193 @@
+//! Length-prefixed framing of many small messages in one buffer, to reduce the number
+//! of FFI calls - each frame is its length followed by its bytes.
+
+use crate::{ByteBuffer, borrowed::c_bytes_as_slice_ref, error::FfiBufferError};
+
+/// FFI compatible encoding of the length prefix of a frame.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum FfiFrameLength {
+    /// Little endian `u32`.
+    #[default]
+    U32Le = 0,
+    /// Unsigned LEB128 varint, 1 byte for frames shorter than 128 bytes.
+    Varint = 1,
+}
+
+// Maximum length of an LEB128 encoded `u64`.
+const MAX_VARINT_LEN: usize = 10;
+
+/// Appends length-prefixed frames into one buffer.
+#[derive(Debug, Clone, Default)]
+pub struct FrameWriter {
+    bytes: Vec<u8>,
+    length: FfiFrameLength,
+    frames: usize,
+}
+
+impl FrameWriter {
+    pub fn new(length: FfiFrameLength) -> Self {
+        Self::with_capacity(length, 0)
+    }
+
+    /// Returns a writer with room for `capacity` bytes (including the length prefixes).
+    pub fn with_capacity(length: FfiFrameLength, capacity: usize) -> Self {
+        Self {
+            bytes: Vec::with_capacity(capacity),
+            length,
+            frames: 0,
+        }
+    }
+
+    /// Appends the given frame.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FfiBufferError::CapacityOverflow`] if the frame is longer than `u32::MAX`
+    /// bytes with [`FfiFrameLength::U32Le`].
+    pub fn push(&mut self, frame: &[u8]) -> Result<(), FfiBufferError> {
+        match self.length {
+            FfiFrameLength::U32Le => {
+                let len =
+                    u32::try_from(frame.len()).map_err(|_| FfiBufferError::CapacityOverflow)?;
+                self.bytes.extend_from_slice(&len.to_le_bytes());
+            }
+            FfiFrameLength::Varint => {
+                let mut len = frame.len() as u64;
+                while len >= 0x80 {
+                    self.bytes.push((len as u8) | 0x80);
+                    len >>= 7;
+                }
+                self.bytes.push(len as u8);
+            }
+        }
+
+        self.bytes.extend_from_slice(frame);
+        self.frames += 1;
+
+        Ok(())
+    }
+
+    /// Returns the number of appended frames.
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames == 0
+    }
+
+    /// Returns the framed bytes appended so far.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn into_boxed_slice(self) -> Box<[u8]> {
+        self.bytes.into_boxed_slice()
+    }
+
+    /// Converts the framed bytes into a byte buffer, to be passed to the host.
+    pub fn into_byte_buffer(self) -> ByteBuffer {
+        ByteBuffer::from_boxed_slice(self.into_boxed_slice())
+    }
+}
+
+/// Iterates the frames of a framed buffer, see [`FrameWriter`].
+///
+/// A malformed or truncated frame is returned as error, the iteration ends after it.
+#[derive(Debug, Clone)]
+pub struct FrameReader<'a> {
+    bytes: &'a [u8],
+    length: FfiFrameLength,
+}
+
+impl<'a> FrameReader<'a> {
+    pub fn new(bytes: &'a [u8], length: FfiFrameLength) -> Self {
+        Self { bytes, length }
+    }
+
+    /// Returns a reader of the given framed bytes, e.g. received from the host.
+    ///
+    /// # Errors
+    ///
+    /// See [`c_bytes_as_slice_ref`].
+    ///
+    /// # Safety
+    ///
+    /// The bytes must be valid (not deallocated) while the reader or a returned frame is used.
+    pub unsafe fn from_raw(
+        ptr: *const u8,
+        len: usize,
+        length: FfiFrameLength,
+    ) -> Result<Self, FfiBufferError> {
+        let bytes = unsafe { c_bytes_as_slice_ref(ptr, len) }?;
+        Ok(Self::new(bytes, length))
+    }
+
+    /// Returns the bytes which are not read yet.
+    pub fn remaining(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    // Returns the frame length and the size of its prefix.
+    fn read_length(&self) -> Result<(usize, usize), FfiBufferError> {
+        let truncated = FfiBufferError::Codec("truncated frame length".to_string());
+
+        match self.length {
+            FfiFrameLength::U32Le => {
+                let prefix = self.bytes.first_chunk::<4>().ok_or(truncated)?;
+                Ok((u32::from_le_bytes(*prefix) as usize, prefix.len()))
+            }
+            FfiFrameLength::Varint => {
+                let mut len = 0u64;
+                for (i, &byte) in self.bytes.iter().take(MAX_VARINT_LEN).enumerate() {
+                    // The 10th byte only holds the highest bit of a `u64`.
+                    if i == MAX_VARINT_LEN - 1 && byte > 1 {
+                        return Err(FfiBufferError::Codec("malformed frame length".to_string()));
+                    }
+                    len |= u64::from(byte & 0x7f) << (7 * i);
+                    if byte & 0x80 == 0 {
+                        let len =
+                            usize::try_from(len).map_err(|_| FfiBufferError::CapacityOverflow)?;
+                        return Ok((len, i + 1));
+                    }
+                }
+
+                if self.bytes.len() < MAX_VARINT_LEN {
+                    return Err(truncated);
+                }
+                Err(FfiBufferError::Codec("malformed frame length".to_string()))
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for FrameReader<'a> {
+    type Item = Result<&'a [u8], FfiBufferError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.bytes.is_empty() {
+            return None;
+        }
+
+        let frame = self.read_length().and_then(|(len, prefix_len)| {
+            let end = prefix_len
+                .checked_add(len)
+                .filter(|&end| end <= self.bytes.len())
+                .ok_or(FfiBufferError::OutOfBounds {
+                    index: prefix_len.saturating_add(len).saturating_sub(1),
+                    len: self.bytes.len(),
+                })?;
+
+            let frame = &self.bytes[prefix_len..end];
+            self.bytes = &self.bytes[end..];
+            Ok(frame)
+        });
+
+        if frame.is_err() {
+            self.bytes = &[];
+        }
+
+        Some(frame)
+    }
+}
@@ -0,0 +1,55 @@
+//! Godot interop (gdext), converting byte buffers to/from `PackedByteArray`
+//! and string buffers to/from `String` (`GString`).
+//!
+//! Godot values are owned by the engine, so every conversion copies the bytes once,
+//! directly between the buffer and the engine memory.
+
+use godot::{
+    builtin::{Encoding, GString, PackedByteArray},
+    meta::error::StringError,
+};
+
+use crate::ByteBuffer;
+
+/// Returns a new `PackedByteArray` with a copy of the given bytes.
+pub fn bytes_to_packed_byte_array(bytes: &[u8]) -> PackedByteArray {
+    PackedByteArray::from(bytes)
+}
+
+/// Returns a new `PackedByteArray` with a copy of the bytes of the given buffer
+/// and deallocates the buffer.
+///
+/// # Safety
+///
+/// The buffer must have been created by this crate and must not be used afterwards.
+pub unsafe fn byte_buffer_into_packed_byte_array(buffer: ByteBuffer) -> PackedByteArray {
+    let bytes = unsafe { buffer.into_boxed_slice() };
+    PackedByteArray::from(&bytes[..])
+}
+
+/// Returns a new byte buffer with a copy of the bytes of the given `PackedByteArray`.
+pub fn packed_byte_array_to_byte_buffer(src: &PackedByteArray) -> ByteBuffer {
+    ByteBuffer::from_boxed_slice(Box::from(src.as_slice()))
+}
+
+/// Returns a new `GString` from the UTF-8 string of the given buffer and
+/// deallocates the buffer.
+///
+/// # Errors
+///
+/// Returns an error if the buffer is not valid UTF-8 or contains a `NUL` character
+/// (not accepted by Godot).
+///
+/// # Safety
+///
+/// The buffer must have been created by this crate and must not be used afterwards.
+pub unsafe fn string_buffer_into_gstring(buffer: ByteBuffer) -> Result<GString, StringError> {
+    let bytes = unsafe { buffer.into_boxed_slice() };
+    GString::try_from_bytes(&bytes, Encoding::Utf8)
+}
+
+/// Returns a new byte buffer with the UTF-8 encoding of the given `GString`.
+pub fn gstring_to_string_buffer(src: &GString) -> ByteBuffer {
+    let string = String::from(src);
+    ByteBuffer::from_boxed_slice(string.into_bytes().into_boxed_slice())
+}
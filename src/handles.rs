@@ -0,0 +1,234 @@
+//! Registry of owned buffers behind opaque `u64` handles, for hosts which can't safely
+//! hold raw pointers (e.g. Lua, scripting VMs or sandboxed environments).
+//!
+//! A handle consists of the slot of the buffer (low 32 bits) and the generation of the slot
+//! (high 32 bits), which is incremented when the buffer is taken out, so a handle passed back
+//! after its buffer was freed is rejected with [`FfiBufferError::StaleHandle`] instead of
+//! resolving to a later buffer in the same slot.
+//!
+//! The registry is not locked while the bytes of a handle are in use (see [`get`]), the buffer
+//! is borrowed instead, so it can't be taken out meanwhile.
+
+use std::sync::Mutex;
+
+use crate::error::FfiBufferError;
+
+/// FFI compatible opaque handle of a registered buffer, 0 is never a valid handle.
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct BufferHandle(pub u64);
+
+impl BufferHandle {
+    pub const NULL: Self = Self(0);
+
+    fn new(index: u32, generation: u32) -> Self {
+        Self((u64::from(generation) << 32) | u64::from(index))
+    }
+
+    fn index(self) -> usize {
+        (self.0 & u64::from(u32::MAX)) as usize
+    }
+
+    fn generation(self) -> u32 {
+        (self.0 >> 32) as u32
+    }
+}
+
+// Borrow count of a slot while its buffer is borrowed mutably.
+const BORROWED_MUT: u32 = u32::MAX;
+
+struct Slot {
+    // Starts at 1, so no handle is 0.
+    generation: u32,
+    // Raw boxed byte slice, so the bytes stay valid while the slots are moved.
+    buffer: Option<*mut [u8]>,
+    // Number of `get` calls using the buffer, `BORROWED_MUT` during a `get_mut` call.
+    borrows: u32,
+}
+
+struct Registry {
+    slots: Vec<Slot>,
+    // Indices of the slots without a buffer.
+    free: Vec<u32>,
+}
+
+// The buffers are owned by the registry, they are only used through it.
+unsafe impl Send for Registry {}
+
+impl Registry {
+    // Returns the slot of the given handle, which holds a buffer.
+    fn slot_mut(&mut self, handle: BufferHandle) -> Result<&mut Slot, FfiBufferError> {
+        if handle == BufferHandle::NULL {
+            return Err(FfiBufferError::InvalidArgument("null handle"));
+        }
+
+        let slot = self
+            .slots
+            .get_mut(handle.index())
+            .ok_or(FfiBufferError::Registry("unknown handle"))?;
+
+        // A retired slot (generation 0) had handles of all generations.
+        if slot.generation == 0 || handle.generation() < slot.generation {
+            return Err(FfiBufferError::StaleHandle(handle.0));
+        }
+        if handle.generation() > slot.generation || slot.buffer.is_none() {
+            return Err(FfiBufferError::Registry("unknown handle"));
+        }
+
+        Ok(slot)
+    }
+}
+
+// Releases a borrow of the buffer in the given slot when dropped (also on a panic of `f`).
+struct Borrow {
+    index: usize,
+    exclusive: bool,
+}
+
+impl Drop for Borrow {
+    fn drop(&mut self) {
+        let mut registry = lock();
+        let slot = &mut registry.slots[self.index];
+        slot.borrows = if self.exclusive { 0 } else { slot.borrows - 1 };
+    }
+}
+
+fn lock() -> std::sync::MutexGuard<'static, Registry> {
+    REGISTRY.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// Borrows the buffer of the given handle and calls `f` with its raw parts `(ptr, len)`,
+// without holding the lock. The buffer can't be taken out until `f` returned.
+fn with_borrowed<R>(
+    handle: BufferHandle,
+    exclusive: bool,
+    f: impl FnOnce(*mut u8, usize) -> R,
+) -> Result<R, FfiBufferError> {
+    let (ptr, len) = {
+        let mut registry = lock();
+        let slot = registry.slot_mut(handle)?;
+        let in_use = if exclusive {
+            slot.borrows != 0
+        } else {
+            slot.borrows >= BORROWED_MUT - 1
+        };
+        if in_use {
+            return Err(FfiBufferError::Registry("buffer is in use"));
+        }
+
+        slot.borrows = if exclusive {
+            BORROWED_MUT
+        } else {
+            slot.borrows + 1
+        };
+        let buffer = slot.buffer.unwrap_or(&mut []);
+        (buffer.cast::<u8>(), buffer.len())
+    };
+
+    let _borrow = Borrow {
+        index: handle.index(),
+        exclusive,
+    };
+    Ok(f(ptr, len))
+}
+
+static REGISTRY: Mutex<Registry> = Mutex::new(Registry {
+    slots: Vec::new(),
+    free: Vec::new(),
+});
+
+/// Registers the given buffer and returns its handle, the buffer is owned by the registry
+/// until it is taken out with [`take`] or [`free`].
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::Registry`] if all `u32::MAX` slots are in use.
+pub fn register(buffer: Box<[u8]>) -> Result<BufferHandle, FfiBufferError> {
+    let mut registry = lock();
+
+    if let Some(index) = registry.free.pop() {
+        let slot = &mut registry.slots[index as usize];
+        slot.buffer = Some(Box::into_raw(buffer));
+        return Ok(BufferHandle::new(index, slot.generation));
+    }
+
+    let index = u32::try_from(registry.slots.len())
+        .ok()
+        .filter(|&index| index != u32::MAX)
+        .ok_or(FfiBufferError::Registry("handle registry is full"))?;
+    registry.slots.push(Slot {
+        generation: 1,
+        buffer: Some(Box::into_raw(buffer)),
+        borrows: 0,
+    });
+
+    Ok(BufferHandle::new(index, 1))
+}
+
+/// Calls `f` with the bytes of the given handle and returns its result.
+///
+/// The buffer is borrowed (not locked) while `f` is called, so `f` may use the registry,
+/// other `get` calls of the same handle are allowed meanwhile.
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::StaleHandle`] if the buffer of the handle was freed,
+/// [`FfiBufferError::Registry`] if the handle is unknown or its buffer is borrowed mutably
+/// (see [`get_mut`]), [`FfiBufferError::InvalidArgument`] if the handle is
+/// [`BufferHandle::NULL`].
+pub fn get<R>(handle: BufferHandle, f: impl FnOnce(&[u8]) -> R) -> Result<R, FfiBufferError> {
+    with_borrowed(handle, false, |ptr, len| {
+        f(unsafe { std::slice::from_raw_parts(ptr, len) })
+    })
+}
+
+/// Calls `f` with the mutable bytes of the given handle and returns its result.
+///
+/// The buffer is borrowed exclusively while `f` is called, see [`get`].
+///
+/// # Errors
+///
+/// See [`get`], returns [`FfiBufferError::Registry`] if the buffer is borrowed already.
+pub fn get_mut<R>(
+    handle: BufferHandle,
+    f: impl FnOnce(&mut [u8]) -> R,
+) -> Result<R, FfiBufferError> {
+    with_borrowed(handle, true, |ptr, len| {
+        f(unsafe { std::slice::from_raw_parts_mut(ptr, len) })
+    })
+}
+
+/// Takes the buffer of the given handle out of the registry, the handle is stale afterwards.
+///
+/// # Errors
+///
+/// See [`get`], returns [`FfiBufferError::Registry`] if the buffer is borrowed.
+pub fn take(handle: BufferHandle) -> Result<Box<[u8]>, FfiBufferError> {
+    let mut registry = lock();
+    let slot = registry.slot_mut(handle)?;
+    if slot.borrows != 0 {
+        return Err(FfiBufferError::Registry("buffer is in use"));
+    }
+
+    let buffer = slot
+        .buffer
+        .take()
+        .map(|buffer| unsafe { Box::from_raw(buffer) })
+        .unwrap_or_default();
+    // A slot whose generation would wrap around is retired instead of reused.
+    slot.generation = slot.generation.wrapping_add(1);
+    if slot.generation != 0 {
+        registry.free.push(handle.index() as u32);
+    }
+
+    Ok(buffer)
+}
+
+/// Drops the buffer of the given handle, the handle is stale afterwards.
+///
+/// # Errors
+///
+/// See [`get`].
+pub fn free(handle: BufferHandle) -> Result<(), FfiBufferError> {
+    take(handle).map(drop)
+}
@@ -0,0 +1,38 @@
+//! C header of the shared types and the allocation functions of the `export` feature,
+//! to be emitted by build scripts of downstream crates instead of maintaining their own.
+//!
+//! Strings cross the boundary as UTF-8 [`crate::ByteBuffer`]s, released with `ffi_string_free`.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// File name of the header.
+pub const HEADER_NAME: &str = "ffi_byte_buffer.h";
+
+/// Content of the header (`include/ffi_byte_buffer.h`).
+pub const HEADER: &str = include_str!("../include/ffi_byte_buffer.h");
+
+/// Writes the header into the given directory (e.g. `OUT_DIR` of a build script)
+/// and returns its path.
+///
+/// The file is only written if its content differs, so dependent builds are not
+/// triggered needlessly.
+///
+/// # Errors
+///
+/// Returns the I/O error if the directory can't be created or the file can't be written.
+pub fn write_header(dir: impl AsRef<Path>) -> io::Result<PathBuf> {
+    let dir = dir.as_ref();
+    fs::create_dir_all(dir)?;
+
+    let path = dir.join(HEADER_NAME);
+    if fs::read_to_string(&path).is_ok_and(|content| content == HEADER) {
+        return Ok(path);
+    }
+
+    fs::write(&path, HEADER)?;
+
+    Ok(path)
+}
@@ -0,0 +1,83 @@
+//! Interning of repeated byte payloads (e.g. identifiers), which maps equal payloads
+//! to the same stable handle, so they are stored once and compared by handle.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, LazyLock, Mutex},
+};
+
+use crate::{ByteBuffer, error::FfiBufferError};
+
+/// FFI compatible handle of an interned payload, valid for the lifetime of the process.
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InternHandle(pub u32);
+
+/// FFI compatible statistics of the interning pool.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FfiInternStats {
+    /// Number of distinct interned payloads.
+    pub interned: usize,
+    /// Sum of the lengths of all distinct interned payloads.
+    pub interned_bytes: usize,
+    /// Number of [`intern`] calls which found an existing payload.
+    pub hits: usize,
+    /// Number of [`intern`] calls which added a new payload.
+    pub misses: usize,
+}
+
+#[derive(Default)]
+struct Pool {
+    payloads: Vec<Arc<[u8]>>,
+    handles: HashMap<Arc<[u8]>, InternHandle>,
+    stats: FfiInternStats,
+}
+
+static POOL: LazyLock<Mutex<Pool>> = LazyLock::new(Default::default);
+
+/// Interns the given payload and returns its handle, equal payloads return the same handle.
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::Registry`] if `u32::MAX` distinct payloads are interned already.
+pub fn intern(bytes: &[u8]) -> Result<InternHandle, FfiBufferError> {
+    let mut pool = POOL.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(&handle) = pool.handles.get(bytes) {
+        pool.stats.hits += 1;
+        return Ok(handle);
+    }
+
+    let handle = u32::try_from(pool.payloads.len())
+        .map(InternHandle)
+        .map_err(|_| FfiBufferError::Registry("intern pool is full"))?;
+    let payload: Arc<[u8]> = bytes.into();
+    pool.payloads.push(payload.clone());
+    pool.handles.insert(payload, handle);
+
+    pool.stats.interned += 1;
+    pool.stats.interned_bytes += bytes.len();
+    pool.stats.misses += 1;
+
+    Ok(handle)
+}
+
+/// Returns the payload of the given handle, `None` if the handle is unknown.
+pub fn resolve(handle: InternHandle) -> Option<Arc<[u8]>> {
+    POOL.lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .payloads
+        .get(handle.0 as usize)
+        .cloned()
+}
+
+/// Returns a copy of the payload of the given handle as byte buffer, to be passed to the host,
+/// `None` if the handle is unknown.
+pub fn resolve_to_byte_buffer(handle: InternHandle) -> Option<ByteBuffer> {
+    resolve(handle).map(|payload| ByteBuffer::from_boxed_slice(payload.as_ref().into()))
+}
+
+/// Returns the current statistics of the interning pool.
+pub fn intern_stats() -> FfiInternStats {
+    POOL.lock().unwrap_or_else(|e| e.into_inner()).stats
+}
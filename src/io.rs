@@ -0,0 +1,169 @@
+//! `std::io` adapters for FFI buffers, so code written against [`Read`], [`Write`] and
+//! [`Seek`] consumes and produces them without copies.
+
+use std::io::{self, BufRead, Cursor, Read, Seek, SeekFrom, Write};
+
+use crate::{ByteBuffer, borrowed::c_bytes_as_slice_ref, error::FfiBufferError};
+
+/// [`Read`] and [`Seek`] over bytes, e.g. received and owned from C.
+#[derive(Debug, Clone)]
+pub struct RawBufferReader<'a> {
+    inner: Cursor<&'a [u8]>,
+}
+
+impl<'a> RawBufferReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            inner: Cursor::new(bytes),
+        }
+    }
+
+    /// Returns a reader of the given C-Bytes, received and owned from C.
+    ///
+    /// # Errors
+    ///
+    /// See [`c_bytes_as_slice_ref`].
+    ///
+    /// # Safety
+    ///
+    /// The given C-Bytes must be valid (not deallocated from the owning C side)
+    /// while the reader is used.
+    pub unsafe fn from_raw(ptr: *const u8, len: usize) -> Result<Self, FfiBufferError> {
+        let bytes = unsafe { c_bytes_as_slice_ref(ptr, len) }?;
+        Ok(Self::new(bytes))
+    }
+
+    pub fn position(&self) -> u64 {
+        self.inner.position()
+    }
+
+    pub fn get_ref(&self) -> &'a [u8] {
+        self.inner.get_ref()
+    }
+}
+
+impl Read for RawBufferReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf)
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        self.inner.read_exact(buf)
+    }
+}
+
+impl BufRead for RawBufferReader<'_> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.inner.consume(amount);
+    }
+}
+
+impl Seek for RawBufferReader<'_> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+#[derive(Debug)]
+enum Target<'a> {
+    Caller(Cursor<&'a mut [u8]>),
+    Owned(Cursor<Vec<u8>>),
+}
+
+/// [`Write`] and [`Seek`] into a caller-provided buffer (see [`RawBufferWriter::from_raw`])
+/// or into an internally grown buffer, which can be exported afterwards
+/// (see [`RawBufferWriter::growable`]).
+///
+/// Writing beyond the end of a caller-provided buffer fails with [`io::ErrorKind::WriteZero`].
+#[derive(Debug)]
+pub struct RawBufferWriter<'a> {
+    target: Target<'a>,
+    // Highest position written so far.
+    written: usize,
+}
+
+impl<'a> RawBufferWriter<'a> {
+    /// Returns a writer into the given caller-provided bytes.
+    pub fn new(bytes: &'a mut [u8]) -> Self {
+        Self {
+            target: Target::Caller(Cursor::new(bytes)),
+            written: 0,
+        }
+    }
+
+    /// Returns a writer into the given caller-provided buffer, e.g. owned by C.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FfiBufferError::InvalidArgument`] if the pointer is null with a length.
+    ///
+    /// # Safety
+    ///
+    /// The given buffer must be valid for `len` writes while the writer is used.
+    pub unsafe fn from_raw(ptr: *mut u8, len: usize) -> Result<Self, FfiBufferError> {
+        if len == 0 {
+            return Ok(Self::new(&mut []));
+        }
+        if ptr.is_null() {
+            return Err(FfiBufferError::InvalidArgument(
+                "null pointer with a length",
+            ));
+        }
+
+        Ok(Self::new(unsafe {
+            std::slice::from_raw_parts_mut(ptr, len)
+        }))
+    }
+
+    /// Returns a writer into an internally grown buffer.
+    pub fn growable() -> Self {
+        Self {
+            target: Target::Owned(Cursor::new(Vec::new())),
+            written: 0,
+        }
+    }
+
+    /// Returns the number of bytes written, up to the highest position written.
+    pub fn written(&self) -> usize {
+        self.written
+    }
+
+    /// Converts the internally grown buffer into a byte buffer, to be passed to the host.
+    ///
+    /// Returns `None` if the writer writes into a caller-provided buffer.
+    pub fn into_byte_buffer(self) -> Option<ByteBuffer> {
+        match self.target {
+            Target::Caller(_) => None,
+            Target::Owned(cursor) => Some(ByteBuffer::from(cursor.into_inner())),
+        }
+    }
+}
+
+impl Write for RawBufferWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let (written, position) = match &mut self.target {
+            Target::Caller(cursor) => (cursor.write(buf)?, cursor.position()),
+            Target::Owned(cursor) => (cursor.write(buf)?, cursor.position()),
+        };
+
+        self.written = self.written.max(position as usize);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Seek for RawBufferWriter<'_> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        match &mut self.target {
+            Target::Caller(cursor) => cursor.seek(pos),
+            Target::Owned(cursor) => cursor.seek(pos),
+        }
+    }
+}
@@ -0,0 +1,56 @@
+//! JSON convenience layer for scripting hosts (e.g. JS, Python or Lua), which exchange
+//! values as UTF-8 JSON text.
+//!
+//! Failures are reported through the last error of the current thread
+//! (see [`crate::error::take_last_error`]), so the host can read the message.
+
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::{
+    borrowed::c_bytes_as_str_ref,
+    error::{FfiBufferError, FfiStatus},
+    into_boxed_byte_slice_raw,
+};
+
+/// Serializes the given value as UTF-8 JSON text into a new buffer and returns its raw parts
+/// `(ptr, len)`, to be passed to the host.
+///
+/// Note: Null is returned if the value can't be serialized (e.g. a map with non-string keys),
+/// the reason is stored as last error with [`FfiStatus::Codec`].
+///
+/// # Safety
+///
+/// Later at some point, the buffer must be converted back with
+/// [`crate::from_boxed_byte_slice_raw`] (or freed with [`crate::free_boxed_byte_slice_raw`]).
+#[cfg_attr(any(feature = "debug-track", feature = "tracing"), track_caller)]
+pub fn json_into_boxed_byte_slice_raw<T: Serialize + ?Sized>(value: &T) -> (*const u8, usize) {
+    match serde_json::to_vec(value) {
+        Ok(json) => into_boxed_byte_slice_raw(json.into_boxed_slice()),
+        Err(error) => {
+            FfiBufferError::Codec(error.to_string()).report();
+            (std::ptr::null(), 0)
+        }
+    }
+}
+
+/// Deserializes a value from the given UTF-8 JSON text, e.g. received from the host.
+/// The bytes are only read, the caller keeps owning them.
+///
+/// # Errors
+///
+/// Returns the status of the failure, which is also stored as last error with its message:
+/// [`FfiStatus::InvalidEncoding`] if the bytes are not valid UTF-8, [`FfiStatus::Codec`] if
+/// the text is not valid JSON of the value, see [`crate::borrowed::c_bytes_as_slice_ref`]
+/// for the other errors.
+///
+/// # Safety
+///
+/// The byte range must be valid (not deallocated) while this function is in process.
+pub unsafe fn json_from_raw<T: DeserializeOwned>(
+    ptr: *const u8,
+    len: usize,
+) -> Result<T, FfiStatus> {
+    let json = unsafe { c_bytes_as_str_ref(ptr, len) }.map_err(FfiBufferError::report)?;
+
+    serde_json::from_str(json).map_err(|error| FfiBufferError::Codec(error.to_string()).report())
+}
@@ -0,0 +1,60 @@
+//! Julia interop, `extern "C"` functions with `ccall` friendly signatures.
+//!
+//! Every function passes buffers by value as [`ByteBuffer`], which maps to the
+//! `isbits` Julia struct:
+//!
+//! ```julia
+//! struct ByteBuffer
+//!     ptr::Ptr{UInt8}
+//!     len::Csize_t
+//! end
+//! ```
+//!
+//! The Julia module `julia/FfiByteBuffer.jl` wraps owned buffers in a mutable
+//! struct, whose finalizer calls [`ffi_julia_buffer_free`], and maps them to
+//! `Vector{UInt8}` views.
+
+use crate::{ByteBuffer, new_byte_buffer, poison, unwind::ffi_fn};
+
+ffi_fn! {
+    /// Returns a new zeroed buffer with the given `len`,
+    /// an empty buffer if the allocation failed.
+    ///
+    /// The buffer must be released with [`ffi_julia_buffer_free`].
+    pub fn ffi_julia_buffer_new(len: usize) -> ByteBuffer {
+        new_byte_buffer(len)
+    }
+}
+
+ffi_fn! {
+    /// Returns a new buffer with a copy of the given bytes.
+    ///
+    /// The buffer must be released with [`ffi_julia_buffer_free`].
+    ///
+    /// # Safety
+    ///
+    /// The given bytes must be valid while this function is in process.
+    /// A null pointer is only valid with a length of 0.
+    pub unsafe fn ffi_julia_buffer_from_bytes(ptr: *const u8, len: usize) -> ByteBuffer {
+        if len == 0 || ptr.is_null() {
+            return ByteBuffer::EMPTY;
+        }
+
+        let bytes = unsafe { std::slice::from_raw_parts(ptr, len) };
+        ByteBuffer::from_boxed_slice(Box::from(bytes))
+    }
+}
+
+ffi_fn! {
+    /// Releases the given buffer, an empty buffer is ignored.
+    ///
+    /// The signature fits a Julia finalizer, which calls it once with the wrapped buffer.
+    ///
+    /// # Safety
+    ///
+    /// The buffer must have been returned by one of the `ffi_julia_buffer_...` functions
+    /// and must not be used afterwards.
+    pub unsafe fn ffi_julia_buffer_free(buffer: ByteBuffer) {
+        poison::drop_poisoned(unsafe { buffer.into_boxed_slice() });
+    }
+}
@@ -1,10 +1,140 @@
 //! Provides byte buffer utilities to send bytes across FFI.
 //! As byte buffer boxed bytes slice is used `Box<[u8]>`
 
-use std::{
-    alloc::{Layout, alloc_zeroed},
-    mem::ManuallyDrop,
+use std::{alloc::Layout, mem::ManuallyDrop, ptr::NonNull, str::Utf8Error};
+
+use audit::FfiAuditEvent;
+use error::FfiBufferError;
+
+mod aligned;
+mod array;
+mod buffer;
+mod cow;
+mod cstring;
+mod ct;
+mod destructor;
+mod fill;
+mod foreign;
+mod hash;
+mod map;
+#[cfg(feature = "mlock")]
+mod mlock;
+mod owned;
+mod result;
+mod secure;
+mod shared;
+mod slice;
+mod typed;
+mod utf16;
+mod vec;
+mod volatile;
+
+pub mod allocator;
+#[cfg(feature = "allocator-api2")]
+pub mod allocator_api;
+pub mod arena;
+pub mod audio;
+pub mod audit;
+pub mod blit;
+pub mod borrowed;
+pub mod checked;
+#[cfg(any(feature = "lz4", feature = "zstd"))]
+pub mod compress;
+pub mod cursor;
+#[cfg(feature = "diff")]
+pub mod diff;
+#[cfg(all(target_os = "linux", feature = "dma-heap"))]
+pub mod dma;
+pub mod endian;
+pub mod envelope;
+pub mod error;
+#[cfg(feature = "export")]
+pub mod export;
+#[cfg(feature = "extendr")]
+pub mod extendr;
+pub mod framing;
+#[cfg(feature = "gdext")]
+pub mod gdext;
+pub mod handles;
+#[cfg(feature = "header")]
+pub mod header;
+pub mod intern;
+pub mod io;
+#[cfg(feature = "json")]
+pub mod json;
+#[cfg(feature = "julia")]
+pub mod julia;
+#[cfg(feature = "libuv")]
+pub mod libuv;
+pub mod lifecycle;
+pub mod logging;
+#[cfg(feature = "msgpack")]
+pub mod msgpack;
+pub mod net;
+pub mod oom;
+#[cfg(feature = "php")]
+pub mod php;
+pub mod poison;
+pub mod pool;
+#[cfg(feature = "prost")]
+pub mod protobuf;
+pub mod replace;
+#[cfg(feature = "serde")]
+pub mod serialize;
+#[cfg(feature = "shm")]
+pub mod shm;
+#[cfg(feature = "smallcache")]
+pub mod smallcache;
+pub mod stats;
+#[cfg(feature = "tokio")]
+pub mod tokio;
+#[cfg(feature = "debug-track")]
+pub mod track;
+#[cfg(feature = "unity")]
+pub mod unity;
+#[cfg(feature = "unreal")]
+pub mod unreal;
+pub mod unwind;
+pub mod upload;
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+pub mod uring;
+pub mod watchdog;
+#[cfg(feature = "wgpu")]
+pub mod wgpu;
+pub mod width;
+#[cfg(windows)]
+pub mod winsock;
+#[cfg(feature = "zmq")]
+pub mod zmq;
+
+pub use aligned::{from_aligned_byte_slice_raw, new_aligned_byte_buffer_raw};
+pub use array::{
+    FfiBufferArray, byte_vecs_from_raw, byte_vecs_into_raw, free_buffer_array_raw,
+    free_byte_vecs_raw, free_strings_raw, join_buffer_array_raw, split_boxed_byte_slice_raw,
+    split_joined_boxed_byte_slice_raw, strings_from_raw, strings_into_raw,
+};
+pub use buffer::{ByteBuffer, ByteBufferView};
+pub use cow::FfiCow;
+pub use cstring::{string_from_cstring_raw, string_into_cstring_raw};
+pub use ct::{ct_eq, ct_eq_raw};
+pub use destructor::{FfiDestructor, destroy_buffer, to_byte_slice_raw_with_destructor};
+pub use fill::{FillResult, copy_into_caller_buffer, copy_str_into_caller_buffer};
+pub use foreign::ForeignBuffer;
+pub use hash::hash64;
+pub use map::{FfiMapEntry, free_map_raw, map_from_raw, map_into_raw};
+pub use owned::{FfiBuffer, FfiBufferBuilder, FfiHeaderMode};
+pub use result::{FfiOption, FfiResult};
+#[cfg(feature = "mlock")]
+pub use secure::LockMode;
+pub use secure::{SecretBytes, SecureByteBuffer};
+pub use shared::{shared_clone_raw, shared_get_raw, shared_into_raw, shared_release_raw};
+pub use slice::{FfiSliceMut, FfiSliceRef};
+pub use typed::{FfiPod, from_boxed_slice_raw, into_boxed_slice_raw, new_boxed_slice_raw};
+pub use utf16::{
+    string_from_boxed_utf16_raw, string_into_boxed_utf16_raw, try_string_from_boxed_utf16_raw,
 };
+pub use vec::{OwnedVecBuffer, vec_from_raw_parts, vec_into_raw_parts};
+pub use volatile::{volatile_copy_from_foreign, volatile_copy_into_foreign};
 
 /// Allocates a new zeroed byte buffer (layout `Box<[u8]>`) with the given `length`
 /// and returns the pointer to the buffer.
@@ -16,28 +146,226 @@ use std::{
 ///
 /// Later at some point, after the buffer is filled, the buffer must be converted
 /// to rust managed boxed byte slice with one of the `from_...` functions.
+///
+/// Note: Null is returned if `length` is 0, the size overflows or the allocation failed,
+/// see [`try_new_boxed_byte_slice_buffer_raw`] for the reason.
+#[cfg_attr(any(feature = "debug-track", feature = "tracing"), track_caller)]
 pub fn new_boxed_byte_slice_buffer_raw(length: usize) -> *mut u8 {
+    try_new_boxed_byte_slice_buffer_raw(length).map_or(std::ptr::null_mut(), NonNull::as_ptr)
+}
+
+/// Allocates a new zeroed byte buffer (layout `Box<[u8]>`) with the given `length`
+/// and returns the pointer to the buffer, see [`new_boxed_byte_slice_buffer_raw`].
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::InvalidArgument`] if `length` is 0,
+/// [`FfiBufferError::CapacityOverflow`] if `length` exceeds `isize::MAX`,
+/// [`FfiBufferError::Alloc`] if the allocation failed.
+///
+/// # Safety
+///
+/// Later at some point, after the buffer is filled, the buffer must be converted
+/// to rust managed boxed byte slice with one of the `from_...` functions.
+#[cfg_attr(any(feature = "debug-track", feature = "tracing"), track_caller)]
+pub fn try_new_boxed_byte_slice_buffer_raw(length: usize) -> Result<NonNull<u8>, FfiBufferError> {
+    allocate_boxed_byte_slice_buffer_raw(length, true, "new_boxed_byte_slice_buffer_raw")
+}
+
+/// Allocates a new uninitialized byte buffer (layout `Box<[u8]>`) with the given `length`
+/// and returns the pointer to the buffer, see [`new_boxed_byte_slice_buffer_raw`].
+///
+/// Skips the zeroing, which is measurable for large buffers the host fills entirely
+/// (e.g. image frames).
+///
+/// Note: Null is returned if `length` is 0, the size overflows or the allocation failed.
+///
+/// # Safety
+///
+/// The host must write the whole buffer before it is converted to rust managed boxed byte
+/// slice with one of the `from_...` functions, reading uninitialized bytes is undefined behavior.
+#[cfg_attr(any(feature = "debug-track", feature = "tracing"), track_caller)]
+pub unsafe fn new_boxed_byte_slice_buffer_raw_uninit(length: usize) -> *mut u8 {
+    allocate_boxed_byte_slice_buffer_raw(length, false, "new_boxed_byte_slice_buffer_raw_uninit")
+        .map_or(std::ptr::null_mut(), NonNull::as_ptr)
+}
+
+#[cfg_attr(any(feature = "debug-track", feature = "tracing"), track_caller)]
+fn allocate_boxed_byte_slice_buffer_raw(
+    length: usize,
+    zeroed: bool,
+    label: &'static str,
+) -> Result<NonNull<u8>, FfiBufferError> {
     if length == 0 {
-        return std::ptr::null_mut();
+        return Err(FfiBufferError::InvalidArgument("buffer length of 0"));
     }
 
     // Basically the same as 'vec![0; 512].into_boxed_slice()', but with less conversion steps
     // involved and no 'ManuallyDrop' needed.
 
-    let layout = Layout::array::<u8>(length).unwrap_or_else(|_| panic!("capacity overflow"));
-    unsafe { alloc_zeroed(layout) }
+    let layout = Layout::array::<u8>(length).map_err(|_| FfiBufferError::CapacityOverflow)?;
+    #[cfg(feature = "smallcache")]
+    let cached = stats::within_limit(length)
+        .then(|| smallcache::take(length))
+        .flatten()
+        .inspect(|ptr| {
+            if zeroed {
+                unsafe { ptr.as_ptr().write_bytes(0, length) };
+            }
+        });
+    #[cfg(not(feature = "smallcache"))]
+    let cached = None;
+    let ptr = match cached {
+        Some(ptr) => ptr,
+        None => NonNull::new(oom::allocate(layout, zeroed))
+            .ok_or(FfiBufferError::Alloc { len: length })?,
+    };
+
+    stats::buffer_created(length);
+    audit::record(FfiAuditEvent::Allocate, ptr.as_ptr(), length, label);
+    #[cfg(feature = "debug-track")]
+    track::issue(ptr.as_ptr(), length, std::panic::Location::caller());
+
+    Ok(ptr)
+}
+
+/// Reallocates the given byte buffer (layout `Box<[u8]>`) from `old_len` to `new_len`
+/// and returns the pointer to the buffer, e.g. after the host discovered it is too small.
+///
+/// The contents are preserved up to the smaller length, additional bytes are zeroed.
+/// A null `ptr` (or an `old_len` of 0) allocates a new buffer,
+/// see [`new_boxed_byte_slice_buffer_raw`].
+///
+/// Note: Null is returned if `new_len` is 0 (the given buffer is freed then)
+/// or the size overflows or the reallocation failed (the given buffer stays valid then).
+///
+/// # Safety
+///
+/// The given buffer must have been created with one of the `..._raw` functions
+/// (or have the layout `Box<[u8]>`) with the length `old_len`. It must not be used afterwards,
+/// unless null is returned for a non-zero `new_len`.
+#[cfg_attr(any(feature = "debug-track", feature = "tracing"), track_caller)]
+pub unsafe fn grow_boxed_byte_slice_buffer_raw(
+    ptr: *mut u8,
+    old_len: usize,
+    new_len: usize,
+) -> *mut u8 {
+    if ptr.is_null() || old_len == 0 {
+        return new_boxed_byte_slice_buffer_raw(new_len);
+    }
+    if new_len == old_len {
+        return ptr;
+    }
+
+    if new_len == 0 {
+        free_boxed_byte_slice_raw(ptr, old_len);
+        return std::ptr::null_mut();
+    }
+    if Layout::array::<u8>(new_len).is_err() {
+        return std::ptr::null_mut();
+    }
+
+    let old_layout = unsafe { Layout::from_size_align_unchecked(old_len, 1) };
+    let new_ptr = unsafe { oom::reallocate(ptr, old_layout, new_len) };
+    if new_ptr.is_null() {
+        return new_ptr;
+    }
+    if new_len > old_len {
+        unsafe { new_ptr.add(old_len).write_bytes(0, new_len - old_len) };
+    }
+    checked::retag(ptr, new_ptr, new_len);
+
+    stats::buffer_reclaimed(old_len);
+    stats::buffer_created(new_len);
+    #[cfg(feature = "debug-track")]
+    {
+        track::reclaim(ptr, old_len);
+        track::issue(new_ptr, new_len, std::panic::Location::caller());
+    }
+    audit::record(
+        FfiAuditEvent::Free,
+        ptr,
+        old_len,
+        "grow_boxed_byte_slice_buffer_raw",
+    );
+    audit::record(
+        FfiAuditEvent::Allocate,
+        new_ptr,
+        new_len,
+        "grow_boxed_byte_slice_buffer_raw",
+    );
+
+    new_ptr
+}
+
+// Allocates a new zeroed boxed byte slice with the given `len`, to be filled in place.
+pub(crate) fn new_zeroed_boxed_byte_slice(len: usize) -> Result<Box<[u8]>, FfiBufferError> {
+    if len == 0 {
+        return Ok(Box::default());
+    }
+
+    let layout = Layout::array::<u8>(len).map_err(|_| FfiBufferError::CapacityOverflow)?;
+    let ptr = oom::allocate(layout, true);
+    if ptr.is_null() {
+        return Err(FfiBufferError::Alloc { len });
+    }
+
+    let slice_raw = std::ptr::slice_from_raw_parts_mut(ptr, len);
+    Ok(unsafe { Box::from_raw(slice_raw) })
+}
+
+// Shrinks the given boxed byte slice to its first `len` bytes (in place if the allocator can).
+pub(crate) fn truncate_boxed_byte_slice(src: Box<[u8]>, len: usize) -> Box<[u8]> {
+    if len >= src.len() {
+        return src;
+    }
+
+    let mut vec = src.into_vec();
+    vec.truncate(len);
+    vec.into_boxed_slice()
+}
+
+/// Allocates a new zeroed byte buffer with the given `length`, see
+/// [`new_boxed_byte_slice_buffer_raw`].
+///
+/// The returned buffer must be converted back with [`ByteBuffer::into_boxed_slice`] at some
+/// point. An empty buffer is returned if `length` is 0 or the allocation failed.
+#[cfg_attr(any(feature = "debug-track", feature = "tracing"), track_caller)]
+pub fn new_byte_buffer(length: usize) -> ByteBuffer {
+    ByteBuffer::from_raw(new_boxed_byte_slice_buffer_raw(length), length)
+}
+
+/// Converts the given string into a byte buffer, see [`string_into_boxed_byte_slice_raw`].
+#[cfg_attr(any(feature = "debug-track", feature = "tracing"), track_caller)]
+pub fn string_into_byte_buffer(src: String) -> ByteBuffer {
+    let (ptr, len) = string_into_boxed_byte_slice_raw(src);
+    ByteBuffer::from_raw(ptr.cast_mut(), len)
+}
+
+/// Converts the given byte buffer back into a string, see [`string_from_boxed_byte_slice_raw_unchecked`].
+///
+/// # Safety
+///
+/// The buffer must have been created with [`string_into_byte_buffer`] (or contain valid UTF-8
+/// with the layout `Box<[u8]>`) and must not be used afterwards.
+#[cfg_attr(any(feature = "debug-track", feature = "tracing"), track_caller)]
+pub unsafe fn string_from_byte_buffer(buffer: ByteBuffer, trim: bool) -> String {
+    let (ptr, len) = buffer.into_raw();
+    unsafe { string_from_boxed_byte_slice_raw_unchecked(ptr, len, trim) }
 }
 
+#[cfg_attr(any(feature = "debug-track", feature = "tracing"), track_caller)]
 pub fn string_into_boxed_byte_slice_raw(src: String) -> (*const u8, usize) {
     if src.is_empty() {
         return (std::ptr::null(), 0);
     }
 
-    let slice: Box<[u8]> = Box::from(src.as_str().as_bytes());
+    let slice: Box<[u8]> = Box::from(src.as_bytes());
 
     into_boxed_byte_slice_raw(slice)
 }
 
+#[cfg_attr(any(feature = "debug-track", feature = "tracing"), track_caller)]
 pub fn into_boxed_byte_slice_raw(src: Box<[u8]>) -> (*const u8, usize) {
     if src.is_empty() {
         return (std::ptr::null(), 0);
@@ -47,154 +375,157 @@ pub fn into_boxed_byte_slice_raw(src: Box<[u8]>) -> (*const u8, usize) {
     let ptr = src.as_ptr();
 
     let _ = ManuallyDrop::new(src);
+    stats::buffer_created(len);
+    audit::record(FfiAuditEvent::Export, ptr, len, "into_boxed_byte_slice_raw");
+    checked::tag(ptr, len);
+    #[cfg(feature = "debug-track")]
+    track::issue(ptr, len, std::panic::Location::caller());
 
     (ptr, len)
 }
 
+#[cfg_attr(any(feature = "debug-track", feature = "tracing"), track_caller)]
 pub fn from_boxed_byte_slice_raw(slice_ptr: *mut u8, length: usize) -> Box<[u8]> {
     if length == 0 {
         return Box::default();
     }
 
+    checked::verify_reported(slice_ptr, length, "from_boxed_byte_slice_raw");
+    #[cfg(feature = "debug-track")]
+    track::reclaim(slice_ptr, length);
+    stats::buffer_reclaimed(length);
+    audit::record(
+        FfiAuditEvent::Import,
+        slice_ptr,
+        length,
+        "from_boxed_byte_slice_raw",
+    );
+
     let slice_raw = std::ptr::slice_from_raw_parts_mut(slice_ptr, length);
     unsafe { Box::from_raw(slice_raw) }
 }
 
+/// Converts the given boxed byte slice back (see [`from_boxed_byte_slice_raw`]) and drops it,
+/// the bytes are poisoned before if enabled (see [`poison`]).
+#[cfg_attr(any(feature = "debug-track", feature = "tracing"), track_caller)]
+pub fn free_boxed_byte_slice_raw(slice_ptr: *mut u8, length: usize) {
+    poison::drop_poisoned(from_boxed_byte_slice_raw(slice_ptr, length));
+}
+
 // `trim` - if true leading and trailing whitespace will be removed.
+#[deprecated(
+    note = "invalid UTF-8 is undefined behavior, use `try_string_from_boxed_byte_slice_raw` or `string_from_boxed_byte_slice_raw_unchecked`"
+)]
+#[cfg_attr(any(feature = "debug-track", feature = "tracing"), track_caller)]
 pub fn string_from_boxed_byte_slice_raw(slice_ptr: *mut u8, length: usize, trim: bool) -> String {
-    if length == 0 {
-        return String::default();
-    }
-
     let slice = from_boxed_byte_slice_raw(slice_ptr, length);
-    let str = unsafe { std::str::from_boxed_utf8_unchecked(slice) };
-
-    if trim {
-        return str.trim().to_string();
-    }
-
-    str.to_string()
+    unsafe { string_from_utf8_unchecked(slice, trim) }
 }
 
-/*pub fn vec_from_boxed_byte_slice_raw(slice_ptr: *mut u8, length: usize) -> Vec<u8> {
-    from_boxed_byte_slice_raw(slice_ptr, length).to_vec()
-}*/
-
-/*// Returns a rust byte slice representation of the given
-/// C-Bytes, received and owned from C.
+/// Converts the given boxed byte slice back into a string, without checking for valid UTF-8.
 ///
-/// # Arguments
-/// - `c_bytes_ptr` - pointer to the C-Bytes
-/// - `c_bytes_len` - length of the C-Bytes
+/// `trim` - if true leading and trailing whitespace will be removed.
 ///
 /// # Safety
 ///
-/// The given C-Bytes must be valid (not deallocated from the owning C side)
-/// while the returned reference is used.
-///
-/// Note: The given C-Bytes are not deallocated or dropped in any form, that must be
-/// done by the owning C side.
-pub const unsafe fn c_bytes_as_slice_ref<'a>(
-    c_bytes_ptr: *const u8,
-    c_bytes_len: usize,
-) -> &'a [u8] {
-    slice::from_raw_parts(c_bytes_ptr, c_bytes_len)
+/// The bytes must be valid UTF-8, see [`try_string_from_boxed_byte_slice_raw`] otherwise.
+#[cfg_attr(any(feature = "debug-track", feature = "tracing"), track_caller)]
+pub unsafe fn string_from_boxed_byte_slice_raw_unchecked(
+    slice_ptr: *mut u8,
+    length: usize,
+    trim: bool,
+) -> String {
+    let slice = from_boxed_byte_slice_raw(slice_ptr, length);
+    unsafe { string_from_utf8_unchecked(slice, trim) }
 }
 
-/// Returns a rust string slice representation of the given
-/// C-Bytes, received and owned from C.
-///
-/// # Arguments
-/// - `c_bytes_ptr` - pointer to the C-Bytes
-/// - `c_bytes_len` - length of the C-Bytes
+/// Converts the given boxed byte slice back into a string.
 ///
-/// # Safety
+/// `trim` - if true leading and trailing whitespace will be removed.
 ///
-/// The given C-Bytes must be valid (not deallocated from the owning C side)
-/// while the returned reference is used and the bytes must be valid UTF-8.
+/// # Errors
 ///
-/// Note: The given C-Bytes are not deallocated or dropped in any form, that must be
-/// done by the owning C side.
-pub const unsafe fn c_bytes_as_str_ref<'a>(c_bytes_ptr: *const u8, c_bytes_len: usize) -> &'a str {
-    from_utf8_unchecked(c_bytes_as_slice_ref(c_bytes_ptr, c_bytes_len))
+/// Returns the reclaimed bytes with the [`Utf8Error`] if they are not valid UTF-8,
+/// so the caller can recover them.
+#[cfg_attr(any(feature = "debug-track", feature = "tracing"), track_caller)]
+pub fn try_string_from_boxed_byte_slice_raw(
+    slice_ptr: *mut u8,
+    length: usize,
+    trim: bool,
+) -> Result<String, (Box<[u8]>, Utf8Error)> {
+    let slice = from_boxed_byte_slice_raw(slice_ptr, length);
+    if let Err(error) = std::str::from_utf8(&slice) {
+        return Err((slice, error));
+    }
+
+    Ok(unsafe { string_from_utf8_unchecked(slice, trim) })
 }
 
-/// Returns a new rust string from the given C-Bytes, received and owned from C.
-///
-/// # Arguments
-/// - `c_bytes_ptr` - pointer to the C-Bytes
-/// - `c_bytes_len` - length of the C-Bytes
-///
-/// # Safety
+/// Converts the given boxed byte slice back into a string, invalid UTF-8 sequences are
+/// replaced with `U+FFFD` (see [`String::from_utf8_lossy`]), e.g. for diagnostic strings.
 ///
-/// The given C-Bytes must be valid (not deallocated from the owning C side)
-/// while this function is in process of creating the rust string and the bytes must be valid UTF-8.
+/// `trim` - if true leading and trailing whitespace will be removed.
 ///
-/// Note: The given C-Bytes are not deallocated or dropped in any form, that must be
-/// done by the owning C side.
-pub unsafe fn c_bytes_to_string(c_bytes_ptr: *const u8, c_bytes_len: usize) -> String {
-    c_bytes_as_str_ref(c_bytes_ptr, c_bytes_len).to_string()
+/// The bytes are deallocated in any case, valid UTF-8 is converted without a copy.
+#[cfg_attr(any(feature = "debug-track", feature = "tracing"), track_caller)]
+pub fn string_from_boxed_byte_slice_raw_lossy(
+    slice_ptr: *mut u8,
+    length: usize,
+    trim: bool,
+) -> String {
+    let slice = from_boxed_byte_slice_raw(slice_ptr, length);
+    if std::str::from_utf8(&slice).is_ok() {
+        return unsafe { string_from_utf8_unchecked(slice, trim) };
+    }
+
+    let string = String::from_utf8_lossy(&slice);
+
+    if trim {
+        return string.trim().to_string();
+    }
+
+    string.into_owned()
 }
 
-/// Returns a new `[u8; 6]` byte array from the given C-Bytes, received and owned from C.
-///
-/// Use cases are where mac address bytes (length of 6) are received from C, like:
-/// - `BTAddress` (Android - mac address type)
-/// - `BTSerial`
-///
-/// # Arguments
-/// - `c_bytes_ptr` - pointer to the C-Bytes
-/// - `c_bytes_len` - length of the C-Bytes
-///
-/// # Panics
-///
-/// This function will panic if the given C-Bytes have not at least a length of 6.
-/// Further bytes beyond 6 will be ignored if present.
-///
-/// # Safety
-///
-/// The given C-Bytes must be valid (not deallocated from the owning C side)
-/// while this function is in process of creating the rust array.
-///
-/// Note: The given C-Bytes are not deallocated or dropped in any form, that must be
-/// done by the owning C side.
-pub const unsafe fn c_bytes_to_6_bytes_cap_array(
-    c_bytes_ptr: *const u8,
-    c_bytes_len: usize,
-) -> [u8; 6] {
-    let b = c_bytes_as_slice_ref(c_bytes_ptr, c_bytes_len);
-    [b[0], b[1], b[2], b[3], b[4], b[5]]
+// Converts the given UTF-8 bytes into a string, `trim` removes leading and trailing whitespace.
+unsafe fn string_from_utf8_unchecked(slice: Box<[u8]>, trim: bool) -> String {
+    let str = unsafe { std::str::from_boxed_utf8_unchecked(slice) };
+
+    if trim {
+        return str.trim().to_string();
+    }
+
+    str.into_string()
 }
 
-/// Returns a new `[u8; 16]` byte array from the given C-Bytes, received and owned from C.
-///
-/// Use cases are where uuid bytes (length of 16) are received from C, like:
-/// - `BTAddress` (IOS - uuid type)
-/// - `BTUuid`
-///
-/// # Arguments
-/// - `c_bytes_ptr` - pointer to the C-Bytes
-/// - `c_bytes_len` - length of the C-Bytes
-///
-/// # Panics
-///
-/// This function will panic if the given C-Bytes have not at least a length of 16.
-/// Further bytes beyond 16 will be ignored if present.
-///
-/// # Safety
-///
-/// The given C-Bytes must be valid (not deallocated from the owning C side)
-/// while this function is in process of creating the rust array.
-///
-/// Note: The given C-Bytes are not deallocated or dropped in any form, that must be
-/// done by the owning C side.
-pub const unsafe fn c_bytes_to_16_bytes_cap_array(
-    c_bytes_ptr: *const u8,
-    c_bytes_len: usize,
-) -> [u8; 16] {
-    let b = c_bytes_as_slice_ref(c_bytes_ptr, c_bytes_len);
-    [
-        b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7], b[8], b[9], b[10], b[11], b[12], b[13],
-        b[14], b[15],
-    ]
+// Returns the given elements as slice, `None` if a null pointer has a length.
+#[cfg(any(feature = "export", feature = "unity", feature = "unreal"))]
+pub(crate) unsafe fn slice_ref<'a, T>(ptr: *const T, len: usize) -> Option<&'a [T]> {
+    if len == 0 {
+        return Some(&[]);
+    }
+
+    if ptr.is_null() {
+        return None;
+    }
+
+    Some(unsafe { std::slice::from_raw_parts(ptr, len) })
+}
+
+// Returns the given elements as mutable slice, `None` if a null pointer has a length.
+#[cfg(feature = "export")]
+pub(crate) unsafe fn slice_mut<'a, T>(ptr: *mut T, len: usize) -> Option<&'a mut [T]> {
+    if len == 0 {
+        return Some(&mut []);
+    }
+
+    if ptr.is_null() {
+        return None;
+    }
+
+    Some(unsafe { std::slice::from_raw_parts_mut(ptr, len) })
+}
+
+/*pub fn vec_from_boxed_byte_slice_raw(slice_ptr: *mut u8, length: usize) -> Vec<u8> {
+    from_boxed_byte_slice_raw(slice_ptr, length).to_vec()
 }*/
@@ -0,0 +1,239 @@
+//! libuv interop, converting between `uv_buf_t` and the buffers of this crate.
+//!
+//! Buffers written with `uv_write` must stay alive until the write callback fires,
+//! [`UvWriteBuffers`] owns them until then.
+
+use std::ffi::c_char;
+
+use crate::{
+    ByteBuffer,
+    error::{FfiBufferError, FfiStatus},
+    unwind::ffi_fn,
+};
+
+/// FFI compatible `uv_buf_t` (unix layout).
+#[cfg(not(windows))]
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct UvBuf {
+    pub base: *mut c_char,
+    pub len: usize,
+}
+
+/// FFI compatible `uv_buf_t` (windows layout, matching `WSABUF`).
+#[cfg(windows)]
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct UvBuf {
+    pub len: u32,
+    pub base: *mut c_char,
+}
+
+impl Default for UvBuf {
+    fn default() -> Self {
+        Self {
+            base: std::ptr::null_mut(),
+            len: 0,
+        }
+    }
+}
+
+impl UvBuf {
+    /// Returns a `uv_buf_t` pointing to the given bytes.
+    ///
+    /// # Errors
+    ///
+    /// On windows returns [`FfiBufferError::CapacityOverflow`] if `len` is greater than
+    /// `u32::MAX`.
+    pub fn new(ptr: *mut u8, len: usize) -> Result<Self, FfiBufferError> {
+        #[cfg(windows)]
+        let len = u32::try_from(len).map_err(|_| FfiBufferError::CapacityOverflow)?;
+
+        Ok(Self {
+            base: ptr.cast(),
+            len,
+        })
+    }
+
+    #[cfg(not(windows))]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[cfg(windows)]
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the first `nread` bytes of the buffer, e.g. in a read callback.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FfiBufferError::OutOfBounds`] if `nread` is greater than the length
+    /// of the buffer.
+    ///
+    /// # Safety
+    ///
+    /// The buffer must be valid (not deallocated) while the returned reference is used.
+    pub unsafe fn as_slice(&self, nread: usize) -> Result<&[u8], FfiBufferError> {
+        if nread > self.len() {
+            return Err(FfiBufferError::OutOfBounds {
+                index: nread,
+                len: self.len(),
+            });
+        }
+
+        if nread == 0 {
+            return Ok(&[]);
+        }
+
+        Ok(unsafe { std::slice::from_raw_parts(self.base.cast::<u8>(), nread) })
+    }
+
+    /// Converts the given byte buffer into a `uv_buf_t`, e.g. in an alloc callback.
+    ///
+    /// The buffer must be converted back with [`UvBuf::into_byte_buffer`] at some point.
+    ///
+    /// # Errors
+    ///
+    /// See [`UvBuf::new`], the buffer is returned back then.
+    pub fn from_byte_buffer(buffer: ByteBuffer) -> Result<Self, ByteBuffer> {
+        Self::new(buffer.ptr, buffer.len).map_err(|_| buffer)
+    }
+
+    /// Converts the `uv_buf_t` back to a byte buffer.
+    ///
+    /// # Safety
+    ///
+    /// The `uv_buf_t` must have been created with [`UvBuf::from_byte_buffer`].
+    pub unsafe fn into_byte_buffer(self) -> ByteBuffer {
+        ByteBuffer {
+            ptr: self.base.cast(),
+            len: self.len(),
+        }
+    }
+}
+
+/// Returns the given `uv_buf_t` array as slices.
+///
+/// # Safety
+///
+/// The array and all of its buffers must be valid (not deallocated) while the
+/// returned references are used. A null pointer is only valid with `nbufs` of 0.
+pub unsafe fn uv_bufs_as_slices<'a>(bufs: *const UvBuf, nbufs: u32) -> Vec<&'a [u8]> {
+    if nbufs == 0 || bufs.is_null() {
+        return Vec::new();
+    }
+
+    let bufs = unsafe { std::slice::from_raw_parts(bufs, nbufs as usize) };
+    bufs.iter()
+        .map(|buf| unsafe {
+            std::slice::from_raw_parts(buf.base.cast::<u8>().cast_const(), buf.len())
+        })
+        .collect()
+}
+
+/// Buffers handed to `uv_write`, owned until the write callback fires.
+#[derive(Debug)]
+pub struct UvWriteBuffers {
+    buffers: Vec<Box<[u8]>>,
+    bufs: Box<[UvBuf]>,
+}
+
+impl UvWriteBuffers {
+    /// # Errors
+    ///
+    /// Returns [`FfiBufferError::CapacityOverflow`] if there are more than `u32::MAX` buffers
+    /// (or on windows a buffer is longer than `u32::MAX`).
+    pub fn new(mut buffers: Vec<Box<[u8]>>) -> Result<Self, FfiBufferError> {
+        if u32::try_from(buffers.len()).is_err() {
+            return Err(FfiBufferError::CapacityOverflow);
+        }
+
+        let bufs = buffers
+            .iter_mut()
+            .map(|buffer| UvBuf::new(buffer.as_mut_ptr(), buffer.len()))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { buffers, bufs })
+    }
+
+    /// Returns the `bufs` and `nbufs` arguments for `uv_write`.
+    pub fn bufs(&self) -> (*const UvBuf, u32) {
+        // The count was checked in `new`.
+        (self.bufs.as_ptr(), self.bufs.len() as u32)
+    }
+
+    pub fn buffers(&self) -> &[Box<[u8]>] {
+        &self.buffers
+    }
+
+    /// Converts the buffers into an opaque handle, to be stored in `uv_write_t.data`.
+    ///
+    /// The handle must be released with [`ffi_uv_write_buffers_free`] (or
+    /// [`UvWriteBuffers::from_raw`]) in the write callback.
+    pub fn into_raw(self) -> *mut UvWriteBuffers {
+        Box::into_raw(Box::new(self))
+    }
+
+    /// Converts the handle back to rust managed buffers.
+    ///
+    /// # Safety
+    ///
+    /// The handle must have been created with [`UvWriteBuffers::into_raw`] and must not
+    /// be used afterwards.
+    pub unsafe fn from_raw(handle: *mut UvWriteBuffers) -> Self {
+        *unsafe { Box::from_raw(handle) }
+    }
+}
+
+ffi_fn! {
+    /// Writes the `bufs` and `nbufs` arguments for `uv_write` of the given handle to
+    /// `out_bufs` and `out_nbufs`.
+    ///
+    /// # Safety
+    ///
+    /// The given `handle` must be null or valid (not released).
+    /// The given `out_bufs` and `out_nbufs` must be null or valid for writes.
+    pub unsafe fn ffi_uv_write_buffers_bufs(
+        handle: *const UvWriteBuffers,
+        out_bufs: *mut *const UvBuf,
+        out_nbufs: *mut u32,
+    ) -> FfiStatus {
+        let Some(buffers) = (unsafe { handle.as_ref() }) else {
+            return FfiBufferError::InvalidArgument("null `handle`").report();
+        };
+
+        if out_bufs.is_null() || out_nbufs.is_null() {
+            return FfiBufferError::InvalidArgument("null `out_bufs` or `out_nbufs`").report();
+        }
+
+        let (bufs, nbufs) = buffers.bufs();
+        unsafe {
+            out_bufs.write(bufs);
+            out_nbufs.write(nbufs);
+        }
+
+        FfiStatus::Ok
+    }
+}
+
+ffi_fn! {
+    /// Releases the given write buffers, to be called in the write callback.
+    /// A null handle is ignored.
+    ///
+    /// # Safety
+    ///
+    /// The given `handle` must be null or valid (not released) and must not be used afterwards.
+    pub unsafe fn ffi_uv_write_buffers_free(handle: *mut UvWriteBuffers) {
+        if handle.is_null() {
+            return;
+        }
+
+        drop(unsafe { UvWriteBuffers::from_raw(handle) });
+    }
+}
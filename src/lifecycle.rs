@@ -0,0 +1,189 @@
+//! Library initialization and shutdown, giving hosts one well-defined
+//! lifecycle to call into.
+
+use std::{
+    fmt::Write as _,
+    panic::{self, PanicHookInfo},
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    error::{Error, FfiBufferError, FfiStatus, set_last_error},
+    logging::{self, FfiLogLevel},
+    pool, stats, watchdog,
+};
+
+type PanicHook = Box<dyn Fn(&PanicHookInfo<'_>) + Sync + Send + 'static>;
+
+/// FFI compatible configuration of [`init`].
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FfiInitConfig {
+    /// If true a panic hook is installed, which stores the panic message as
+    /// last error (see [`crate::error`]) of the panicking thread and logs it
+    /// (see [`crate::logging`]).
+    pub install_panic_hook: bool,
+    /// Buffer size of the global pool (see [`pool::configure_global`]).
+    pub pool_buffer_size: usize,
+    /// Number of idle buffers the global pool keeps at most.
+    pub pool_capacity: usize,
+    /// Limit of the live bytes (see [`stats::set_memory_limit`]), 0 means no limit.
+    pub memory_limit: usize,
+    /// If true the exported buffers are tracked (see [`watchdog::enable`]), so the leak report
+    /// of [`shutdown`] lists them with their pointer, length and label.
+    pub track_buffers: bool,
+}
+
+impl Default for FfiInitConfig {
+    fn default() -> Self {
+        Self {
+            install_panic_hook: true,
+            pool_buffer_size: pool::DEFAULT_BUFFER_SIZE,
+            pool_capacity: pool::DEFAULT_CAPACITY,
+            memory_limit: 0,
+            track_buffers: false,
+        }
+    }
+}
+
+struct State {
+    // The installed panic hook and the hook it replaced, restored on shutdown.
+    panic_hook: Option<InstalledHook>,
+}
+
+struct InstalledHook {
+    // Address of the installed hook, to find out if it is still the current one.
+    addr: usize,
+    previous: Arc<PanicHook>,
+}
+
+static STATE: Mutex<Option<State>> = Mutex::new(None);
+
+/// Initializes the library with the given `config`: the registries of the buffers handed to
+/// the host are initialized, the global pool and the memory limit are configured
+/// and the panic hook is installed.
+///
+/// Calling `init` again without [`shutdown`] in between has no effect.
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::InvalidArgument`] if the pool buffer size is 0,
+/// nothing is initialized then.
+pub fn init(config: &FfiInitConfig) -> Result<(), FfiBufferError> {
+    let mut state = STATE.lock().unwrap_or_else(|e| e.into_inner());
+    if state.is_some() {
+        return Ok(());
+    }
+
+    pool::configure_global(config.pool_buffer_size, config.pool_capacity)?;
+    pool::init();
+    watchdog::init();
+    #[cfg(feature = "debug-track")]
+    crate::track::init();
+    stats::set_memory_limit((config.memory_limit != 0).then_some(config.memory_limit));
+    watchdog::enable(config.track_buffers);
+
+    let panic_hook = config.install_panic_hook.then(install_panic_hook);
+
+    *state = Some(State { panic_hook });
+
+    Ok(())
+}
+
+/// Shuts the library down, undoing [`init`].
+///
+/// Buffers which are still live (see [`crate::stats`]) are reported as leaks, listed with their
+/// pointer, length and label if tracked (and where they were issued with `debug-track`).
+/// The replaced panic hook is restored, unless another hook was installed since.
+///
+/// Calling `shutdown` without [`init`] before has no effect.
+pub fn shutdown() {
+    let mut state = STATE.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(state) = state.take() else {
+        return;
+    };
+
+    if let Some(hook) = state.panic_hook {
+        restore_panic_hook(hook);
+    }
+
+    report_leaks();
+}
+
+// Logs the buffers which are still live on shutdown.
+fn report_leaks() {
+    let report = stats::memory_report();
+    if report.live_buffers == 0 {
+        return;
+    }
+
+    let mut message = format!(
+        "{} buffers ({} bytes) not reclaimed on shutdown",
+        report.live_buffers, report.live_bytes
+    );
+    for (ptr, len, label) in watchdog::live_buffers() {
+        let _ = write!(message, "\n  {ptr:#x} ({len} bytes) {label}");
+    }
+    #[cfg(feature = "debug-track")]
+    for buffer in crate::track::outstanding_buffers() {
+        let _ = write!(
+            message,
+            "\n  {:#x} ({} bytes) issued at {}",
+            buffer.ptr, buffer.len, buffer.location
+        );
+    }
+
+    logging::log(FfiLogLevel::Warn, &message);
+}
+
+// Installs the panic hook, which calls the replaced one.
+fn install_panic_hook() -> InstalledHook {
+    let previous: Arc<PanicHook> = Arc::new(panic::take_hook());
+
+    let hook: PanicHook = Box::new({
+        let previous = Arc::clone(&previous);
+        move |info| {
+            let message = panic_message(info);
+            logging::log(FfiLogLevel::Error, &message);
+            set_last_error(Error::new(FfiStatus::Panic, message));
+            previous(info);
+        }
+    });
+    let addr = hook_addr(&hook);
+    panic::set_hook(hook);
+
+    InstalledHook { addr, previous }
+}
+
+// Restores the hook replaced by the installed panic hook, if it is still the current one.
+fn restore_panic_hook(hook: InstalledHook) {
+    let current = panic::take_hook();
+    if hook_addr(&current) != hook.addr {
+        panic::set_hook(current);
+        return;
+    }
+
+    // Dropping the installed hook releases its reference to the replaced one.
+    drop(current);
+    let previous = Arc::try_unwrap(hook.previous)
+        .unwrap_or_else(|previous| Box::new(move |info| previous(info)));
+    panic::set_hook(previous);
+}
+
+fn hook_addr(hook: &PanicHook) -> usize {
+    std::ptr::from_ref(&**hook).cast::<()>() as usize
+}
+
+fn panic_message(info: &PanicHookInfo<'_>) -> String {
+    let payload = info
+        .payload()
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| info.payload().downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic payload");
+
+    match info.location() {
+        Some(location) => format!("panicked at {location}: {payload}"),
+        None => format!("panicked: {payload}"),
+    }
+}
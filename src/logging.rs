@@ -0,0 +1,62 @@
+//! Delivery of diagnostics to the host's logging system.
+//!
+//! Without a registered callback diagnostics are written to stderr.
+
+use std::{ffi::c_void, sync::RwLock};
+
+/// FFI compatible log level of a diagnostic.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FfiLogLevel {
+    Error = 1,
+    Warn = 2,
+    Info = 3,
+    Debug = 4,
+}
+
+/// Host callback receiving diagnostics.
+///
+/// # Arguments
+/// - `ctx` - the context given at registration
+/// - `level` - log level of the diagnostic
+/// - `msg_ptr` - pointer to the UTF-8 message bytes (without NUL terminator)
+/// - `msg_len` - length of the message bytes
+///
+/// Note: The message bytes are only valid during the call.
+pub type FfiLogCallback =
+    unsafe extern "C" fn(ctx: *mut c_void, level: FfiLogLevel, msg_ptr: *const u8, msg_len: usize);
+
+#[derive(Clone, Copy)]
+struct Logger {
+    ctx: *mut c_void,
+    callback: FfiLogCallback,
+}
+
+// The host guarantees that `ctx` can be used from any thread (see `set_log_callback`).
+unsafe impl Send for Logger {}
+unsafe impl Sync for Logger {}
+
+static LOGGER: RwLock<Option<Logger>> = RwLock::new(None);
+
+/// Registers the given `callback` to receive all diagnostics,
+/// `None` restores the default (stderr).
+///
+/// # Safety
+///
+/// The given `ctx` must be valid, to be passed to `callback` from any thread,
+/// until another callback is registered.
+pub unsafe fn set_log_callback(ctx: *mut c_void, callback: Option<FfiLogCallback>) {
+    let logger = callback.map(|callback| Logger { ctx, callback });
+    *LOGGER.write().unwrap_or_else(|e| e.into_inner()) = logger;
+}
+
+/// Delivers the given diagnostic `msg` to the registered callback or stderr.
+pub(crate) fn log(level: FfiLogLevel, msg: &str) {
+    // Copied out, so the callback may register another callback without deadlocking.
+    let logger = *LOGGER.read().unwrap_or_else(|e| e.into_inner());
+
+    match logger {
+        Some(logger) => unsafe { (logger.callback)(logger.ctx, level, msg.as_ptr(), msg.len()) },
+        None => eprintln!("ffi-byte-buffer [{level:?}]: {msg}"),
+    }
+}
@@ -0,0 +1,87 @@
+//! Key/value maps (e.g. configuration or metadata dictionaries) as array of entries,
+//! so a whole map is passed in one call and released in one call.
+
+use std::collections::HashMap;
+
+use crate::{ByteBuffer, error::FfiBufferError};
+
+/// FFI compatible entry of a map, the `key` is a UTF-8 string buffer.
+#[repr(C)]
+#[derive(Debug)]
+pub struct FfiMapEntry {
+    pub key: ByteBuffer,
+    pub value: ByteBuffer,
+}
+
+/// Converts the given map into an array of entries and returns its raw parts `(ptr, len)`,
+/// the order of the entries is unspecified.
+///
+/// The map must be converted back with [`map_from_raw`] or released with [`free_map_raw`]
+/// at some point.
+pub fn map_into_raw(src: HashMap<String, Vec<u8>>) -> (*mut FfiMapEntry, usize) {
+    if src.is_empty() {
+        return (std::ptr::null_mut(), 0);
+    }
+
+    let entries: Box<[FfiMapEntry]> = src
+        .into_iter()
+        .map(|(key, value)| FfiMapEntry {
+            key: ByteBuffer::from(key),
+            value: ByteBuffer::from(value),
+        })
+        .collect();
+
+    let len = entries.len();
+    (Box::into_raw(entries).cast::<FfiMapEntry>(), len)
+}
+
+/// Converts the given array of entries back into a map, the array and all of its entries
+/// are reclaimed in any case. A later entry replaces an earlier entry with the same key.
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::Utf8`] if a key is not valid UTF-8, e.g. if it was modified
+/// by the host.
+///
+/// # Safety
+///
+/// The array must have been created with [`map_into_raw`] and must not be used afterwards.
+pub unsafe fn map_from_raw(
+    ptr: *mut FfiMapEntry,
+    len: usize,
+) -> Result<HashMap<String, Vec<u8>>, FfiBufferError> {
+    // All entries are reclaimed before the conversion, so none leaks on an error.
+    let entries = unsafe { reclaim_entries(ptr, len) }
+        .into_iter()
+        .map(|entry| unsafe { (entry.key.into_boxed_slice(), entry.value.into_boxed_slice()) })
+        .collect::<Vec<_>>();
+
+    entries
+        .into_iter()
+        .map(|(key, value)| {
+            let key = String::from_utf8(key.into_vec()).map_err(|e| e.utf8_error())?;
+            Ok((key, value.into_vec()))
+        })
+        .collect()
+}
+
+/// Releases the given array of entries, including all of its keys and values.
+///
+/// # Safety
+///
+/// The array must have been created with [`map_into_raw`] and must not be used afterwards.
+pub unsafe fn free_map_raw(ptr: *mut FfiMapEntry, len: usize) {
+    for entry in unsafe { reclaim_entries(ptr, len) } {
+        drop(unsafe { entry.key.into_boxed_slice() });
+        drop(unsafe { entry.value.into_boxed_slice() });
+    }
+}
+
+unsafe fn reclaim_entries(ptr: *mut FfiMapEntry, len: usize) -> Box<[FfiMapEntry]> {
+    if ptr.is_null() || len == 0 {
+        return Box::default();
+    }
+
+    let entries_raw = std::ptr::slice_from_raw_parts_mut(ptr, len);
+    unsafe { Box::from_raw(entries_raw) }
+}
@@ -0,0 +1,249 @@
+//! Page-locked allocations for secrets, which can't be swapped to disk
+//! (`mlock` on unix, `VirtualLock` on windows).
+//!
+//! The pages are mapped separately for each allocation, so unlocking one never unlocks
+//! the pages of another. Exported allocations are kept in a registry keyed by the data
+//! pointer, so a [`crate::SecureByteBuffer`] is released the same way it was allocated.
+
+use std::{
+    collections::HashMap,
+    io,
+    ptr::NonNull,
+    sync::{LazyLock, Mutex},
+};
+
+use crate::{
+    audit::{self, FfiAuditEvent},
+    error::FfiBufferError,
+    stats,
+};
+
+// Lengths of the exported allocations.
+static LOCKED: LazyLock<Mutex<HashMap<usize, usize>>> = LazyLock::new(Default::default);
+
+/// Zeroed, page-locked mapping of `len` (non-zero) bytes, unlocked and unmapped when dropped.
+pub(crate) struct LockedPages {
+    ptr: NonNull<u8>,
+    len: usize,
+}
+
+// The mapping is owned by the allocation, the pointer is not shared.
+unsafe impl Send for LockedPages {}
+unsafe impl Sync for LockedPages {}
+
+impl LockedPages {
+    /// Maps and locks `len` (non-zero) zeroed bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FfiBufferError::Alloc`] if the mapping failed or exceeds the memory limit,
+    /// [`FfiBufferError::LockLimit`] if locking exceeds the limit of locked memory
+    /// (e.g. `RLIMIT_MEMLOCK`), [`FfiBufferError::Io`] if locking failed otherwise.
+    pub(crate) fn allocate(len: usize) -> Result<Self, FfiBufferError> {
+        if !stats::within_limit(len) {
+            stats::allocation_failed();
+            return Err(FfiBufferError::Alloc { len });
+        }
+
+        let Some(ptr) = NonNull::new(unsafe { os::map(len) }) else {
+            stats::allocation_failed();
+            return Err(FfiBufferError::Alloc { len });
+        };
+        if let Err(error) = unsafe { os::lock(ptr.as_ptr(), len) } {
+            unsafe { os::unmap(ptr.as_ptr(), len) };
+            return Err(lock_error(len, error));
+        }
+
+        Ok(Self { ptr, len })
+    }
+
+    pub(crate) fn as_slice(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+
+    pub(crate) fn as_mut_slice(&mut self) -> &mut [u8] {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+
+    /// Hands the allocation off to the host and returns its raw parts `(ptr, len)`,
+    /// it must be taken back with [`LockedPages::import`].
+    #[cfg_attr(any(feature = "debug-track", feature = "tracing"), track_caller)]
+    pub(crate) fn export(self) -> (*mut u8, usize) {
+        let (ptr, len) = (self.ptr.as_ptr(), self.len);
+        std::mem::forget(self);
+
+        LOCKED
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(ptr as usize, len);
+        stats::buffer_created(len);
+        audit::record(FfiAuditEvent::Export, ptr, len, "SecureByteBuffer (locked)");
+        #[cfg(feature = "debug-track")]
+        crate::track::issue(ptr, len, std::panic::Location::caller());
+
+        (ptr, len)
+    }
+
+    /// Takes back the allocation exported at the given pointer, returns None if the pointer
+    /// is not of an exported allocation.
+    #[cfg_attr(any(feature = "debug-track", feature = "tracing"), track_caller)]
+    pub(crate) fn import(ptr: *mut u8) -> Option<Self> {
+        let len = LOCKED
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&(ptr as usize))?;
+
+        #[cfg(feature = "debug-track")]
+        crate::track::reclaim(ptr, len);
+        stats::buffer_reclaimed(len);
+        audit::record(FfiAuditEvent::Import, ptr, len, "SecureByteBuffer (locked)");
+
+        Some(Self {
+            ptr: NonNull::new(ptr)?,
+            len,
+        })
+    }
+}
+
+impl Drop for LockedPages {
+    fn drop(&mut self) {
+        unsafe {
+            os::unlock(self.ptr.as_ptr(), self.len);
+            os::unmap(self.ptr.as_ptr(), self.len);
+        }
+    }
+}
+
+// Maps the error of locking `len` bytes, exceeding the limit is reported separately.
+fn lock_error(len: usize, error: io::Error) -> FfiBufferError {
+    if os::is_lock_limit(&error) {
+        FfiBufferError::LockLimit { len }
+    } else {
+        FfiBufferError::Io(error)
+    }
+}
+
+#[cfg(unix)]
+mod os {
+    use std::io;
+
+    // Returns null if the mapping failed.
+    pub(super) unsafe fn map(len: usize) -> *mut u8 {
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+                -1,
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return std::ptr::null_mut();
+        }
+
+        ptr.cast()
+    }
+
+    pub(super) unsafe fn unmap(ptr: *mut u8, len: usize) {
+        unsafe { libc::munmap(ptr.cast(), len) };
+    }
+
+    pub(super) unsafe fn lock(ptr: *mut u8, len: usize) -> io::Result<()> {
+        if unsafe { libc::mlock(ptr.cast(), len) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(())
+    }
+
+    pub(super) unsafe fn unlock(ptr: *mut u8, len: usize) {
+        unsafe { libc::munlock(ptr.cast(), len) };
+    }
+
+    // `ENOMEM` beyond `RLIMIT_MEMLOCK`, `EPERM` if the limit is 0 (unprivileged).
+    pub(super) fn is_lock_limit(error: &io::Error) -> bool {
+        matches!(error.raw_os_error(), Some(libc::ENOMEM | libc::EPERM))
+    }
+}
+
+#[cfg(windows)]
+mod os {
+    use std::{ffi::c_void, io};
+
+    const MEM_COMMIT: u32 = 0x1000;
+    const MEM_RESERVE: u32 = 0x2000;
+    const MEM_RELEASE: u32 = 0x8000;
+    const PAGE_READWRITE: u32 = 0x04;
+    const ERROR_WORKING_SET_QUOTA: i32 = 1453;
+
+    #[link(name = "kernel32")]
+    unsafe extern "system" {
+        fn VirtualAlloc(
+            address: *mut c_void,
+            size: usize,
+            allocation_type: u32,
+            protect: u32,
+        ) -> *mut c_void;
+        fn VirtualFree(address: *mut c_void, size: usize, free_type: u32) -> i32;
+        fn VirtualLock(address: *mut c_void, size: usize) -> i32;
+        fn VirtualUnlock(address: *mut c_void, size: usize) -> i32;
+    }
+
+    // Returns null if the mapping failed.
+    pub(super) unsafe fn map(len: usize) -> *mut u8 {
+        unsafe {
+            VirtualAlloc(
+                std::ptr::null_mut(),
+                len,
+                MEM_COMMIT | MEM_RESERVE,
+                PAGE_READWRITE,
+            )
+        }
+        .cast()
+    }
+
+    pub(super) unsafe fn unmap(ptr: *mut u8, _len: usize) {
+        unsafe { VirtualFree(ptr.cast(), 0, MEM_RELEASE) };
+    }
+
+    pub(super) unsafe fn lock(ptr: *mut u8, len: usize) -> io::Result<()> {
+        if unsafe { VirtualLock(ptr.cast(), len) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(())
+    }
+
+    pub(super) unsafe fn unlock(ptr: *mut u8, len: usize) {
+        unsafe { VirtualUnlock(ptr.cast(), len) };
+    }
+
+    // The working set of the process is too small to lock more pages.
+    pub(super) fn is_lock_limit(error: &io::Error) -> bool {
+        error.raw_os_error() == Some(ERROR_WORKING_SET_QUOTA)
+    }
+}
+
+// Locking is not supported, the heap fallback is used if allowed.
+#[cfg(not(any(unix, windows)))]
+mod os {
+    use std::io;
+
+    pub(super) unsafe fn map(_len: usize) -> *mut u8 {
+        std::ptr::null_mut()
+    }
+
+    pub(super) unsafe fn unmap(_ptr: *mut u8, _len: usize) {}
+
+    pub(super) unsafe fn lock(_ptr: *mut u8, _len: usize) -> io::Result<()> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+
+    pub(super) unsafe fn unlock(_ptr: *mut u8, _len: usize) {}
+
+    pub(super) fn is_lock_limit(_error: &io::Error) -> bool {
+        false
+    }
+}
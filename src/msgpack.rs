@@ -0,0 +1,67 @@
+//! MessagePack convenience layer, mirroring [`crate::json`] with payloads several times
+//! smaller, for hosts with MessagePack decoders (e.g. C# or Python).
+//!
+//! Structs are serialized as maps with their field names, so hosts decode them without
+//! knowing the field order. Failures are reported through the last error of the current
+//! thread (see [`crate::error::take_last_error`]), so the host can read the message.
+
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::{
+    borrowed::c_bytes_as_slice_ref,
+    error::{FfiBufferError, FfiStatus},
+    into_boxed_byte_slice_raw,
+};
+
+/// Serializes the given value as MessagePack into a new buffer and returns its raw parts
+/// `(ptr, len)`, to be passed to the host.
+///
+/// Note: Null is returned if the value can't be serialized,
+/// the reason is stored as last error with [`FfiStatus::Codec`].
+///
+/// # Safety
+///
+/// Later at some point, the buffer must be converted back with
+/// [`crate::from_boxed_byte_slice_raw`] (or freed with [`crate::free_boxed_byte_slice_raw`]).
+#[cfg_attr(any(feature = "debug-track", feature = "tracing"), track_caller)]
+pub fn msgpack_into_boxed_byte_slice_raw<T: Serialize + ?Sized>(value: &T) -> (*const u8, usize) {
+    match rmp_serde::to_vec_named(value) {
+        Ok(bytes) => into_boxed_byte_slice_raw(bytes.into_boxed_slice()),
+        Err(error) => {
+            FfiBufferError::Codec(error.to_string()).report();
+            (std::ptr::null(), 0)
+        }
+    }
+}
+
+/// Deserializes a value from the given MessagePack bytes, e.g. received from the host.
+/// Structs are accepted as maps or as arrays of their fields.
+/// The bytes are only read, the caller keeps owning them.
+///
+/// # Errors
+///
+/// Returns the status of the failure, which is also stored as last error with its message:
+/// [`FfiStatus::Codec`] if the bytes are not valid MessagePack of the value (or have trailing
+/// bytes),
+/// see [`c_bytes_as_slice_ref`] for the other errors.
+///
+/// # Safety
+///
+/// The byte range must be valid (not deallocated) while this function is in process.
+pub unsafe fn msgpack_from_raw<T: DeserializeOwned>(
+    ptr: *const u8,
+    len: usize,
+) -> Result<T, FfiStatus> {
+    let bytes = unsafe { c_bytes_as_slice_ref(ptr, len) }.map_err(FfiBufferError::report)?;
+
+    let mut deserializer = rmp_serde::Deserializer::new(bytes);
+    let value = T::deserialize(&mut deserializer)
+        .map_err(|error| FfiBufferError::Codec(error.to_string()).report())?;
+
+    let rest = deserializer.get_ref().len();
+    if rest != 0 {
+        return Err(FfiBufferError::Codec(format!("{rest} trailing bytes")).report());
+    }
+
+    Ok(value)
+}
@@ -0,0 +1,110 @@
+//! Socket receive straight into byte buffers, so network payloads destined for the host
+//! skip an intermediate staging buffer.
+//!
+//! Each function allocates a buffer of `max_len` bytes, receives into it and shrinks it
+//! to the received bytes, [`recv_into_pooled_buffer`] recycles pooled buffers instead.
+//! See the `tokio` module (`tokio` feature) for the async variants.
+
+use std::{
+    io::Read,
+    net::{SocketAddr, UdpSocket},
+};
+
+use crate::{
+    ByteBuffer,
+    error::FfiBufferError,
+    new_zeroed_boxed_byte_slice,
+    pool::{BufferPool, PooledBuffer},
+    truncate_boxed_byte_slice,
+};
+
+/// Receives a datagram from the connected `socket` into a new byte buffer,
+/// see [`UdpSocket::recv`].
+///
+/// Bytes of a datagram longer than `max_len` are discarded.
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::InvalidArgument`] if `max_len` is 0, [`FfiBufferError::Alloc`]
+/// if the allocation failed, [`FfiBufferError::Io`] with the error of the socket.
+pub fn recv_into_ffi_buffer(
+    socket: &UdpSocket,
+    max_len: usize,
+) -> Result<ByteBuffer, FfiBufferError> {
+    receive(max_len, |bytes| Ok((socket.recv(bytes)?, ()))).map(|(buffer, ())| buffer)
+}
+
+/// Receives a datagram from the `socket` into a new byte buffer and returns it with
+/// the address of the sender, see [`UdpSocket::recv_from`].
+///
+/// Bytes of a datagram longer than `max_len` are discarded.
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::InvalidArgument`] if `max_len` is 0, [`FfiBufferError::Alloc`]
+/// if the allocation failed, [`FfiBufferError::Io`] with the error of the socket.
+pub fn recv_from_into_ffi_buffer(
+    socket: &UdpSocket,
+    max_len: usize,
+) -> Result<(ByteBuffer, SocketAddr), FfiBufferError> {
+    receive(max_len, |bytes| socket.recv_from(bytes))
+}
+
+/// Reads up to `max_len` bytes from the `reader` (e.g. a `TcpStream`) into a new byte buffer,
+/// see [`Read::read`].
+///
+/// An empty buffer is returned at the end of the stream.
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::InvalidArgument`] if `max_len` is 0, [`FfiBufferError::Alloc`]
+/// if the allocation failed, [`FfiBufferError::Io`] with the error of the reader.
+pub fn read_into_ffi_buffer(
+    reader: &mut impl Read,
+    max_len: usize,
+) -> Result<ByteBuffer, FfiBufferError> {
+    receive(max_len, |bytes| Ok((reader.read(bytes)?, ()))).map(|(buffer, ())| buffer)
+}
+
+/// Receives a datagram from the connected `socket` into a buffer acquired from the `pool`,
+/// see [`UdpSocket::recv`]. The length is set to the received bytes.
+///
+/// Bytes of a datagram longer than the buffer size of the pool are discarded.
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::Alloc`] if the allocation failed, [`FfiBufferError::Io`] with
+/// the error of the socket.
+pub fn recv_into_pooled_buffer(
+    socket: &UdpSocket,
+    pool: &BufferPool,
+) -> Result<PooledBuffer, FfiBufferError> {
+    let mut buffer = pool.acquire(0)?;
+    let len = socket.recv(buffer.as_whole_mut_slice())?;
+    buffer.set_len(len)?;
+
+    Ok(buffer)
+}
+
+// Allocates a buffer of `max_len` bytes, fills it with `recv` and shrinks it to the received bytes,
+// `recv` returns their length and whatever else it received (e.g. the sender address).
+fn receive<T>(
+    max_len: usize,
+    recv: impl FnOnce(&mut [u8]) -> std::io::Result<(usize, T)>,
+) -> Result<(ByteBuffer, T), FfiBufferError> {
+    let mut bytes = alloc_receive_buffer(max_len)?;
+    let (len, received) = recv(&mut bytes)?;
+
+    let bytes = truncate_boxed_byte_slice(bytes, len);
+
+    Ok((ByteBuffer::from_boxed_slice(bytes), received))
+}
+
+// Allocates the zeroed receive buffer of `max_len` bytes.
+pub(crate) fn alloc_receive_buffer(max_len: usize) -> Result<Box<[u8]>, FfiBufferError> {
+    if max_len == 0 {
+        return Err(FfiBufferError::InvalidArgument("buffer length of 0"));
+    }
+
+    new_zeroed_boxed_byte_slice(max_len)
+}
@@ -0,0 +1,122 @@
+//! Host controlled handling of failed allocations.
+//!
+//! Without a registered handler a failed allocation fails right away
+//! (see [`FfiOomAction::Fail`]).
+
+use std::{
+    alloc::{Layout, alloc, alloc_zeroed, handle_alloc_error, realloc},
+    ffi::c_void,
+    sync::RwLock,
+};
+
+use crate::{pool, stats};
+
+/// FFI compatible action to take after an allocation failed.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FfiOomAction {
+    /// The allocation fails, as if no handler is registered.
+    Fail = 0,
+    /// The allocation is retried, after the handler released memory (e.g. dropped caches)
+    /// and the idle buffers of all pools are released (see [`crate::pool::trim_all`]).
+    TrimAndRetry = 1,
+    /// The process is aborted (see [`std::alloc::handle_alloc_error`]).
+    Abort = 2,
+}
+
+/// Host callback deciding what to do after an allocation of `requested_len` bytes failed.
+///
+/// Note: The callback is called again for each failed retry - it must eventually return
+/// [`FfiOomAction::Fail`] or [`FfiOomAction::Abort`] if no memory can be released.
+pub type FfiOomHandler =
+    unsafe extern "C" fn(ctx: *mut c_void, requested_len: usize) -> FfiOomAction;
+
+#[derive(Clone, Copy)]
+struct Handler {
+    ctx: *mut c_void,
+    callback: FfiOomHandler,
+}
+
+// The host guarantees that `ctx` can be used from any thread (see `set_oom_handler`).
+unsafe impl Send for Handler {}
+unsafe impl Sync for Handler {}
+
+static HANDLER: RwLock<Option<Handler>> = RwLock::new(None);
+
+/// Registers the given `handler` to be called after an allocation failed,
+/// `None` restores the default (fail).
+///
+/// # Safety
+///
+/// The given `ctx` must be valid, to be passed to `handler` from any thread,
+/// until another handler is registered.
+pub unsafe fn set_oom_handler(ctx: *mut c_void, handler: Option<FfiOomHandler>) {
+    let handler = handler.map(|callback| Handler { ctx, callback });
+    *HANDLER.write().unwrap_or_else(|e| e.into_inner()) = handler;
+}
+
+/// Allocates with the given non-zero sized `layout`, consulting the registered handler
+/// each time the allocation fails. Returns null if the allocation finally failed.
+///
+/// Fails right away if the allocation exceeds the memory limit, see [`stats::set_memory_limit`].
+pub(crate) fn allocate(layout: Layout, zeroed: bool) -> *mut u8 {
+    if !stats::within_limit(layout.size()) {
+        stats::allocation_failed();
+        return std::ptr::null_mut();
+    }
+
+    retry(layout, || unsafe {
+        if zeroed {
+            alloc_zeroed(layout)
+        } else {
+            alloc(layout)
+        }
+    })
+}
+
+/// Reallocates the given block with the `layout` to the non-zero `new_size`, consulting the
+/// registered handler each time the reallocation fails. Returns null if the reallocation
+/// finally failed, the given block stays valid then.
+///
+/// Fails right away if the growth exceeds the memory limit, see [`stats::set_memory_limit`].
+///
+/// # Safety
+///
+/// See [`std::alloc::realloc`].
+pub(crate) unsafe fn reallocate(ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+    if !stats::within_limit(new_size.saturating_sub(layout.size())) {
+        stats::allocation_failed();
+        return std::ptr::null_mut();
+    }
+
+    let new_layout = unsafe { Layout::from_size_align_unchecked(new_size, layout.align()) };
+    retry(new_layout, || unsafe { realloc(ptr, layout, new_size) })
+}
+
+/// Calls `attempt` until it returns non-null or the handler gives up on the `layout`,
+/// e.g. for allocations with another allocator. Returns null if the allocation finally failed.
+pub(crate) fn retry(layout: Layout, mut attempt: impl FnMut() -> *mut u8) -> *mut u8 {
+    loop {
+        let ptr = attempt();
+        if !ptr.is_null() {
+            return ptr;
+        }
+
+        stats::allocation_failed();
+
+        // Copied out, so the handler may register another handler without deadlocking.
+        let handler = *HANDLER.read().unwrap_or_else(|e| e.into_inner());
+        let action = match handler {
+            Some(handler) => unsafe { (handler.callback)(handler.ctx, layout.size()) },
+            None => FfiOomAction::Fail,
+        };
+
+        match action {
+            FfiOomAction::Fail => return std::ptr::null_mut(),
+            FfiOomAction::TrimAndRetry => {
+                pool::trim_all();
+            }
+            FfiOomAction::Abort => handle_alloc_error(layout),
+        }
+    }
+}
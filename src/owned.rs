@@ -0,0 +1,575 @@
+//! Buffers allocated with a combination of options (alignment, label, zeroing on drop,
+//! length header), configured with [`FfiBuffer::builder`].
+
+use std::{
+    alloc::{Layout, dealloc},
+    borrow::Borrow,
+    cmp::Ordering,
+    collections::HashMap,
+    hash::{Hash, Hasher},
+    mem::ManuallyDrop,
+    ops::{Deref, DerefMut, Index, IndexMut, Range},
+    slice::{Chunks, Iter, SliceIndex, Windows},
+    str::Utf8Error,
+    sync::{LazyLock, Mutex},
+};
+
+use crate::{
+    ByteBuffer,
+    audit::{self, FfiAuditEvent},
+    error::FfiBufferError,
+    oom, stats,
+};
+
+// Size of the length header, see `FfiHeaderMode::LengthPrefix`.
+const LENGTH_PREFIX_SIZE: usize = size_of::<u64>();
+
+/// FFI compatible header mode of a buffer.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum FfiHeaderMode {
+    /// The buffer only contains the payload.
+    #[default]
+    None = 0,
+    /// The payload is preceded by its length as little endian `u64`, padded with zeros to
+    /// a multiple of the alignment (see [`FfiBufferBuilder::alignment`]), so the payload
+    /// is aligned and always the last `length` bytes of the buffer.
+    LengthPrefix = 1,
+}
+
+/// Options of an [`FfiBuffer`], see [`FfiBuffer::builder`].
+///
+/// Note: Pooled buffers are not an option, they have a fixed size and no header
+/// (see [`crate::pool`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[must_use]
+pub struct FfiBufferBuilder {
+    len: usize,
+    alignment: usize,
+    label: &'static str,
+    sensitive: bool,
+    header: FfiHeaderMode,
+}
+
+impl FfiBufferBuilder {
+    /// Sets the alignment of the payload and of the start of the buffer (the header,
+    /// if there is one), default is 1.
+    pub fn alignment(mut self, alignment: usize) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    /// Sets the label recorded with the lifecycle events of the buffer
+    /// (see [`crate::audit`] and [`crate::watchdog`]), default is `FfiBuffer`.
+    pub fn label(mut self, label: &'static str) -> Self {
+        self.label = label;
+        self
+    }
+
+    /// If true the bytes are zeroed with volatile writes before they are deallocated,
+    /// default is false.
+    pub fn sensitive(mut self, sensitive: bool) -> Self {
+        self.sensitive = sensitive;
+        self
+    }
+
+    /// Sets the header mode, default is [`FfiHeaderMode::None`].
+    pub fn header(mut self, header: FfiHeaderMode) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// Allocates the buffer with zeroed payload.
+    ///
+    /// Returns `None` if the alignment is not a power of two, the length overflows
+    /// or the allocation failed.
+    pub fn build(self) -> Option<FfiBuffer> {
+        self.allocate(true)
+    }
+
+    /// Allocates the buffer without initializing the payload.
+    ///
+    /// Returns `None` if the alignment is not a power of two, the length overflows
+    /// or the allocation failed.
+    ///
+    /// # Safety
+    ///
+    /// The payload must be written (e.g. with [`FfiBuffer::as_mut_ptr`]) before it is read.
+    pub unsafe fn build_uninit(self) -> Option<FfiBuffer> {
+        self.allocate(false)
+    }
+
+    fn allocate(self, zeroed: bool) -> Option<FfiBuffer> {
+        if !self.alignment.is_power_of_two() {
+            return None;
+        }
+        let header_len = match self.header {
+            FfiHeaderMode::None => 0,
+            FfiHeaderMode::LengthPrefix => LENGTH_PREFIX_SIZE.next_multiple_of(self.alignment),
+        };
+        let layout =
+            Layout::from_size_align(self.len.checked_add(header_len)?, self.alignment).ok()?;
+
+        let ptr = if layout.size() == 0 {
+            std::ptr::null_mut()
+        } else {
+            let ptr = oom::allocate(layout, zeroed);
+            if ptr.is_null() {
+                return None;
+            }
+
+            stats::buffer_created(layout.size());
+            audit::record(FfiAuditEvent::Allocate, ptr, layout.size(), self.label);
+            ptr
+        };
+
+        if header_len != 0 {
+            if !zeroed {
+                unsafe { ptr.write_bytes(0, header_len) };
+            }
+            let prefix = (self.len as u64).to_le_bytes();
+            unsafe { ptr.copy_from_nonoverlapping(prefix.as_ptr(), LENGTH_PREFIX_SIZE) };
+        }
+
+        Some(FfiBuffer {
+            ptr,
+            layout,
+            header_len,
+            label: self.label,
+            sensitive: self.sensitive,
+        })
+    }
+}
+
+/// Buffer allocated with the options of an [`FfiBufferBuilder`].
+///
+/// The bytes are deallocated when the buffer is dropped.
+#[derive(Debug)]
+pub struct FfiBuffer {
+    ptr: *mut u8,
+    layout: Layout,
+    header_len: usize,
+    label: &'static str,
+    sensitive: bool,
+}
+
+// The allocation is owned by the buffer, the pointer is not shared.
+unsafe impl Send for FfiBuffer {}
+unsafe impl Sync for FfiBuffer {}
+
+// Exported buffers, by pointer, until they are converted back.
+static EXPORTED: LazyLock<Mutex<HashMap<usize, ExportedBuffer>>> = LazyLock::new(Default::default);
+
+struct ExportedBuffer {
+    layout: Layout,
+    header_len: usize,
+    label: &'static str,
+    sensitive: bool,
+}
+
+impl FfiBuffer {
+    /// Returns a builder for a buffer with a payload of the given length.
+    pub fn builder(len: usize) -> FfiBufferBuilder {
+        FfiBufferBuilder {
+            len,
+            alignment: 1,
+            label: "FfiBuffer",
+            sensitive: false,
+            header: FfiHeaderMode::None,
+        }
+    }
+
+    /// Returns the payload (without header).
+    pub fn as_slice(&self) -> &[u8] {
+        if self.is_empty() {
+            return &[];
+        }
+
+        unsafe { std::slice::from_raw_parts(self.as_ptr(), self.len()) }
+    }
+
+    /// Returns the payload (without header).
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        if self.is_empty() {
+            return &mut [];
+        }
+
+        unsafe { std::slice::from_raw_parts_mut(self.as_mut_ptr(), self.len()) }
+    }
+
+    /// Returns the pointer to the payload, null if the buffer is empty.
+    pub fn as_ptr(&self) -> *const u8 {
+        self.as_mut_ptr_inner()
+    }
+
+    /// Returns the pointer to the payload, null if the buffer is empty.
+    pub fn as_mut_ptr(&mut self) -> *mut u8 {
+        self.as_mut_ptr_inner()
+    }
+
+    /// Returns the length of the payload (without header).
+    pub fn len(&self) -> usize {
+        self.layout.size() - self.header_len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the byte at the given index, `None` if out of bounds.
+    pub fn get(&self, index: usize) -> Option<&u8> {
+        self.as_slice().get(index)
+    }
+
+    /// Returns the bytes of the given range, `None` if out of bounds.
+    pub fn get_range(&self, range: Range<usize>) -> Option<&[u8]> {
+        self.as_slice().get(range)
+    }
+
+    pub fn iter(&self) -> Iter<'_, u8> {
+        self.as_slice().iter()
+    }
+
+    /// Returns an iterator over `chunk_size` bytes at a time, see [`slice::chunks`].
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `chunk_size` is 0.
+    pub fn chunks(&self, chunk_size: usize) -> Chunks<'_, u8> {
+        self.as_slice().chunks(chunk_size)
+    }
+
+    /// Returns an iterator over all overlapping windows of `size` bytes, see [`slice::windows`].
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `size` is 0.
+    pub fn windows(&self, size: usize) -> Windows<'_, u8> {
+        self.as_slice().windows(size)
+    }
+
+    pub fn label(&self) -> &'static str {
+        self.label
+    }
+
+    /// Returns a copy of the buffer in a new allocation with the same options
+    /// (alignment, label, sensitivity, header mode).
+    ///
+    /// Returns `None` if the allocation failed.
+    ///
+    /// Note: `Clone` is not implemented on purpose, so every copy of a payload is explicit.
+    pub fn try_deep_clone(&self) -> Option<Self> {
+        let header = match self.header_len {
+            0 => FfiHeaderMode::None,
+            _ => FfiHeaderMode::LengthPrefix,
+        };
+        let builder = Self::builder(self.len())
+            .alignment(self.layout.align())
+            .label(self.label)
+            .sensitive(self.sensitive)
+            .header(header);
+
+        let mut clone = unsafe { builder.build_uninit() }?;
+        clone.as_mut_slice().copy_from_slice(self.as_slice());
+
+        Some(clone)
+    }
+
+    /// Converts the buffer into a byte buffer (including the header), to be passed to the host.
+    ///
+    /// The bytes stay alive until the byte buffer is converted back with
+    /// [`FfiBuffer::from_byte_buffer`].
+    pub fn into_byte_buffer(self) -> ByteBuffer {
+        let buffer = ManuallyDrop::new(self);
+        if buffer.ptr.is_null() {
+            return ByteBuffer::EMPTY;
+        }
+
+        EXPORTED.lock().unwrap_or_else(|e| e.into_inner()).insert(
+            buffer.ptr as usize,
+            ExportedBuffer {
+                layout: buffer.layout,
+                header_len: buffer.header_len,
+                label: buffer.label,
+                sensitive: buffer.sensitive,
+            },
+        );
+        audit::record(
+            FfiAuditEvent::Export,
+            buffer.ptr,
+            buffer.layout.size(),
+            buffer.label,
+        );
+
+        ByteBuffer {
+            ptr: buffer.ptr,
+            len: buffer.layout.size(),
+        }
+    }
+
+    /// Converts the given byte buffer back into the buffer.
+    ///
+    /// Returns the byte buffer unchanged if it was not created by [`FfiBuffer::into_byte_buffer`].
+    pub fn from_byte_buffer(buffer: ByteBuffer) -> Result<Self, ByteBuffer> {
+        if buffer.len == 0 {
+            return Ok(Self::default());
+        }
+
+        let Some(exported) = EXPORTED
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&(buffer.ptr as usize))
+        else {
+            return Err(buffer);
+        };
+        audit::record(
+            FfiAuditEvent::Import,
+            buffer.ptr,
+            buffer.len,
+            exported.label,
+        );
+
+        Ok(Self {
+            ptr: buffer.ptr,
+            layout: exported.layout,
+            header_len: exported.header_len,
+            label: exported.label,
+            sensitive: exported.sensitive,
+        })
+    }
+
+    // Takes over the allocation of the given bytes (shrunk to their length) as a buffer
+    // with the default options.
+    pub(crate) fn from_vec(bytes: Vec<u8>) -> Result<Self, FfiBufferError> {
+        if bytes.is_empty() {
+            return Ok(Self::default());
+        }
+
+        let mut bytes = ManuallyDrop::new(bytes);
+        let (mut ptr, len, cap) = (bytes.as_mut_ptr(), bytes.len(), bytes.capacity());
+        if len < cap {
+            let old_layout = unsafe { Layout::from_size_align_unchecked(cap, 1) };
+            ptr = unsafe { oom::reallocate(ptr, old_layout, len) };
+            if ptr.is_null() {
+                drop(ManuallyDrop::into_inner(bytes));
+                return Err(FfiBufferError::Alloc { len });
+            }
+        }
+
+        let buffer = Self {
+            ptr,
+            layout: unsafe { Layout::from_size_align_unchecked(len, 1) },
+            ..Self::default()
+        };
+        stats::buffer_created(len);
+        audit::record(FfiAuditEvent::Allocate, ptr, len, buffer.label);
+
+        Ok(buffer)
+    }
+
+    // Takes ownership of the given allocation of the `layout` (without header), which was
+    // allocated by a buffer with the default options and the `label`.
+    pub(crate) unsafe fn from_raw_parts(ptr: *mut u8, layout: Layout, label: &'static str) -> Self {
+        audit::record(FfiAuditEvent::Import, ptr, layout.size(), label);
+
+        Self {
+            ptr,
+            layout,
+            header_len: 0,
+            label,
+            sensitive: false,
+        }
+    }
+
+    fn as_mut_ptr_inner(&self) -> *mut u8 {
+        if self.is_empty() {
+            return std::ptr::null_mut();
+        }
+
+        unsafe { self.ptr.add(self.header_len) }
+    }
+}
+
+/// Returns an empty buffer with the default options (no allocation).
+impl Default for FfiBuffer {
+    fn default() -> Self {
+        Self {
+            ptr: std::ptr::null_mut(),
+            layout: Layout::new::<()>(),
+            header_len: 0,
+            label: "FfiBuffer",
+            sensitive: false,
+        }
+    }
+}
+
+impl Drop for FfiBuffer {
+    fn drop(&mut self) {
+        if self.ptr.is_null() {
+            return;
+        }
+
+        if self.sensitive {
+            for i in 0..self.layout.size() {
+                unsafe { self.ptr.add(i).write_volatile(0) };
+            }
+        }
+
+        stats::buffer_reclaimed(self.layout.size());
+        audit::record(
+            FfiAuditEvent::Free,
+            self.ptr,
+            self.layout.size(),
+            self.label,
+        );
+        unsafe { dealloc(self.ptr, self.layout) };
+    }
+}
+
+/// Copies the payload.
+impl From<&FfiBuffer> for Vec<u8> {
+    fn from(src: &FfiBuffer) -> Self {
+        src.as_slice().to_vec()
+    }
+}
+
+/// Copies the payload, fails if it is not valid UTF-8.
+impl TryFrom<&FfiBuffer> for String {
+    type Error = Utf8Error;
+
+    fn try_from(src: &FfiBuffer) -> Result<Self, Self::Error> {
+        std::str::from_utf8(src.as_slice()).map(str::to_string)
+    }
+}
+
+impl<'a> TryFrom<&'a FfiBuffer> for &'a str {
+    type Error = Utf8Error;
+
+    fn try_from(src: &'a FfiBuffer) -> Result<Self, Self::Error> {
+        std::str::from_utf8(src.as_slice())
+    }
+}
+
+/// Fails (returning the byte buffer) if it was not created by [`FfiBuffer::into_byte_buffer`].
+impl TryFrom<ByteBuffer> for FfiBuffer {
+    type Error = ByteBuffer;
+
+    fn try_from(src: ByteBuffer) -> Result<Self, Self::Error> {
+        Self::from_byte_buffer(src)
+    }
+}
+
+impl From<FfiBuffer> for ByteBuffer {
+    fn from(src: FfiBuffer) -> Self {
+        src.into_byte_buffer()
+    }
+}
+
+impl Deref for FfiBuffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl DerefMut for FfiBuffer {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        self.as_mut_slice()
+    }
+}
+
+impl AsMut<[u8]> for FfiBuffer {
+    fn as_mut(&mut self) -> &mut [u8] {
+        self.as_mut_slice()
+    }
+}
+
+impl AsRef<[u8]> for FfiBuffer {
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl Borrow<[u8]> for FfiBuffer {
+    fn borrow(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+/// Panics if the index is out of bounds, see [`FfiBuffer::get`] and [`FfiBuffer::get_range`].
+impl<I: SliceIndex<[u8]>> Index<I> for FfiBuffer {
+    type Output = I::Output;
+
+    fn index(&self, index: I) -> &I::Output {
+        &self.as_slice()[index]
+    }
+}
+
+impl<I: SliceIndex<[u8]>> IndexMut<I> for FfiBuffer {
+    fn index_mut(&mut self, index: I) -> &mut I::Output {
+        &mut self.as_mut_slice()[index]
+    }
+}
+
+impl<'a> IntoIterator for &'a FfiBuffer {
+    type Item = &'a u8;
+    type IntoIter = Iter<'a, u8>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Collects the bytes into a buffer with the default options. The bytes are collected into
+/// a `Vec` first, whose allocation the buffer takes over after shrinking it to the collected
+/// length (which may copy the bytes, depending on the allocator).
+///
+/// Note: The length of a buffer is fixed, bytes are appended with `Extend<u8>` of the
+/// growable [`crate::cursor::ByteWriter`], which collects the same bytes.
+///
+/// # Panics
+///
+/// This function will panic if an allocation failed (instead of aborting the process).
+impl FromIterator<u8> for FfiBuffer {
+    fn from_iter<I: IntoIterator<Item = u8>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let mut bytes = Vec::new();
+        let _ = bytes.try_reserve(iter.size_hint().0);
+        for byte in iter {
+            if bytes.len() == bytes.capacity() && bytes.try_reserve(1).is_err() {
+                panic!("allocation of {} bytes failed", bytes.len() + 1);
+            }
+            bytes.push(byte);
+        }
+
+        Self::from_vec(bytes).unwrap_or_else(|error| panic!("{error}"))
+    }
+}
+
+/// Compares the contents, consistent with `[u8]` (see [`Borrow`]).
+impl PartialEq for FfiBuffer {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl Eq for FfiBuffer {}
+
+impl PartialOrd for FfiBuffer {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for FfiBuffer {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_slice().cmp(other.as_slice())
+    }
+}
+
+impl Hash for FfiBuffer {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state);
+    }
+}
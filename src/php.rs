@@ -0,0 +1,74 @@
+//! PHP extension interop (ext-php-rs), converting byte buffers to/from
+//! PHP strings (`zend_string`).
+//!
+//! PHP strings are owned by the Zend memory manager, so every conversion copies
+//! the bytes. Buffers which should stay rust owned while PHP uses them can be
+//! wrapped in [`PhpByteBuffer`], which is dropped by PHP's object destructor.
+
+use ext_php_rs::{binary::Binary, boxed::ZBox, prelude::*, types::ZendStr};
+
+use crate::ByteBuffer;
+
+/// Returns a new (request-bound, non-persistent) PHP string with a copy of the given bytes.
+pub fn bytes_to_zend_string(bytes: &[u8]) -> ZBox<ZendStr> {
+    ZendStr::new(bytes, false)
+}
+
+/// Returns a new (request-bound, non-persistent) PHP string with a copy of the
+/// bytes of the given buffer and deallocates the buffer.
+///
+/// # Safety
+///
+/// The buffer must have been created by this crate and must not be used afterwards.
+pub unsafe fn byte_buffer_into_zend_string(buffer: ByteBuffer) -> ZBox<ZendStr> {
+    let bytes = unsafe { buffer.into_boxed_slice() };
+    ZendStr::new(&bytes, false)
+}
+
+/// Returns a new byte buffer with a copy of the bytes of the given PHP string.
+pub fn zend_string_to_byte_buffer(src: &ZendStr) -> ByteBuffer {
+    ByteBuffer::from_boxed_slice(Box::from(src.as_bytes()))
+}
+
+/// PHP class (`FfiByteBuffer`) owning a rust byte buffer.
+///
+/// The buffer is dropped when PHP destroys the object, so ownership can be
+/// handed to PHP without copying. The class must be registered in the
+/// `#[php_module]` of the extension with `module.class::<PhpByteBuffer>()`.
+#[php_class]
+#[php(name = "FfiByteBuffer")]
+pub struct PhpByteBuffer {
+    bytes: Box<[u8]>,
+}
+
+impl PhpByteBuffer {
+    pub fn new(bytes: Box<[u8]>) -> Self {
+        Self { bytes }
+    }
+
+    /// Wraps the given buffer, which is deallocated when PHP destroys the object.
+    ///
+    /// # Safety
+    ///
+    /// The buffer must have been created by this crate and must not be used afterwards.
+    pub unsafe fn from_byte_buffer(buffer: ByteBuffer) -> Self {
+        Self::new(unsafe { buffer.into_boxed_slice() })
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+#[php_impl]
+impl PhpByteBuffer {
+    /// Returns the length of the buffer in bytes.
+    pub fn length(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Returns a PHP string with a copy of the bytes of the buffer.
+    pub fn to_bytes(&self) -> Binary<u8> {
+        Binary::new(self.bytes.to_vec())
+    }
+}
@@ -0,0 +1,36 @@
+//! Poisoning of freed buffers with [`POISON_BYTE`], so a host which keeps reading a freed
+//! pointer reads a recognizable pattern instead of stale valid-looking data.
+//! Enabled by default in debug builds only, see [`enable`].
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Byte the freed buffers are filled with.
+pub const POISON_BYTE: u8 = 0xDD;
+
+static ENABLED: AtomicBool = AtomicBool::new(cfg!(debug_assertions));
+
+/// Enables or disables the poisoning of the buffers freed by this library,
+/// e.g. [`crate::free_boxed_byte_slice_raw`] or the exported `..._free` functions.
+pub fn enable(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+// Drops the given bytes, filled with the poison byte first if enabled.
+// With the `smallcache` feature small bytes are cached instead, see `crate::smallcache`.
+pub(crate) fn drop_poisoned(mut bytes: Box<[u8]>) {
+    if is_enabled() {
+        // Volatile, so the writes are not optimized away right before the deallocation.
+        for byte in bytes.iter_mut() {
+            unsafe { std::ptr::write_volatile(byte, POISON_BYTE) };
+        }
+    }
+
+    #[cfg(feature = "smallcache")]
+    crate::smallcache::recycle(bytes);
+    #[cfg(not(feature = "smallcache"))]
+    drop(bytes);
+}
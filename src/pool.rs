@@ -0,0 +1,377 @@
+//! Pools of fixed-size buffers, which are recycled instead of allocated and freed per call,
+//! e.g. for audio callbacks crossing the boundary a thousand times per second.
+//!
+//! Buffers idle in a pool don't count as live (see [`crate::stats`]), but are reported
+//! separately in the memory report. A pooled buffer handed to the host counts as live
+//! until it is returned.
+
+use std::{
+    collections::HashMap,
+    ops::{Deref, DerefMut},
+    sync::{Arc, LazyLock, Mutex, RwLock, Weak},
+};
+
+use crate::{
+    ByteBuffer,
+    audit::{self, FfiAuditEvent},
+    error::FfiBufferError,
+    new_zeroed_boxed_byte_slice, stats,
+};
+
+/// Buffer size of the global pool, until it is configured.
+pub const DEFAULT_BUFFER_SIZE: usize = 4096;
+
+/// Capacity of the global pool, until it is configured.
+pub const DEFAULT_CAPACITY: usize = 64;
+
+/// FFI compatible statistics of a pool.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FfiPoolStats {
+    /// Size of each buffer of the pool.
+    pub buffer_size: usize,
+    /// Maximum number of idle buffers kept by the pool.
+    pub capacity: usize,
+    /// Number of idle buffers.
+    pub idle: usize,
+    /// Number of acquired buffers, which are not released yet.
+    pub outstanding: usize,
+    /// Number of acquires which recycled an idle buffer.
+    pub hits: usize,
+    /// Number of acquires which allocated a new buffer.
+    pub misses: usize,
+}
+
+#[derive(Default)]
+struct State {
+    idle: Vec<Box<[u8]>>,
+    outstanding: usize,
+    hits: usize,
+    misses: usize,
+}
+
+struct Inner {
+    buffer_size: usize,
+    capacity: usize,
+    state: Mutex<State>,
+}
+
+/// Thread-safe pool of buffers of a fixed size, cheap to clone (the clones share the pool).
+///
+/// At most `capacity` released buffers are kept for reuse, further ones are deallocated.
+#[derive(Clone)]
+pub struct BufferPool {
+    inner: Arc<Inner>,
+}
+
+// All pools alive, to be trimmed and reported together.
+static POOLS: Mutex<Vec<Weak<Inner>>> = Mutex::new(Vec::new());
+
+// Pooled buffers handed to the host by their pointer, to be returned to their pool.
+static EXPORTED: LazyLock<Mutex<HashMap<usize, BufferPool>>> = LazyLock::new(Default::default);
+
+static GLOBAL: LazyLock<RwLock<BufferPool>> = LazyLock::new(|| {
+    let pool = BufferPool::new(DEFAULT_BUFFER_SIZE, DEFAULT_CAPACITY).expect("valid default");
+    RwLock::new(pool)
+});
+
+impl BufferPool {
+    /// Creates an empty pool of buffers with `buffer_size` bytes, which keeps at most
+    /// `capacity` idle buffers. Nothing is allocated until a buffer is acquired.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FfiBufferError::InvalidArgument`] if `buffer_size` is 0.
+    pub fn new(buffer_size: usize, capacity: usize) -> Result<Self, FfiBufferError> {
+        if buffer_size == 0 {
+            return Err(FfiBufferError::InvalidArgument("buffer size of 0"));
+        }
+
+        let inner = Arc::new(Inner {
+            buffer_size,
+            capacity,
+            state: Mutex::default(),
+        });
+
+        let mut pools = POOLS.lock().unwrap_or_else(|e| e.into_inner());
+        pools.retain(|pool| pool.strong_count() > 0);
+        pools.push(Arc::downgrade(&inner));
+
+        Ok(Self { inner })
+    }
+
+    pub fn buffer_size(&self) -> usize {
+        self.inner.buffer_size
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity
+    }
+
+    /// Acquires a buffer of `len` bytes, an idle buffer is recycled if there is one.
+    ///
+    /// Note: A recycled buffer keeps the bytes of its previous use.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FfiBufferError::InvalidArgument`] if `len` exceeds the buffer size of the pool,
+    /// [`FfiBufferError::Alloc`] if the allocation of a new buffer failed.
+    pub fn acquire(&self, len: usize) -> Result<PooledBuffer, FfiBufferError> {
+        if len > self.inner.buffer_size {
+            return Err(FfiBufferError::InvalidArgument(
+                "length exceeds the buffer size of the pool",
+            ));
+        }
+
+        let recycled = {
+            let mut state = self.inner.state.lock().unwrap_or_else(|e| e.into_inner());
+            let recycled = state.idle.pop();
+            match recycled {
+                Some(_) => state.hits += 1,
+                None => state.misses += 1,
+            }
+            recycled
+        };
+
+        // Allocated without holding the lock, so an out of memory handler may trim the pool.
+        let bytes = match recycled {
+            Some(bytes) => bytes,
+            None => new_zeroed_boxed_byte_slice(self.inner.buffer_size)?,
+        };
+        self.inner
+            .state
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .outstanding += 1;
+
+        Ok(PooledBuffer {
+            bytes,
+            len,
+            pool: self.clone(),
+        })
+    }
+
+    /// Releases the given buffer into the pool, same as dropping it.
+    pub fn release(&self, buffer: PooledBuffer) {
+        drop(buffer);
+    }
+
+    /// Deallocates all idle buffers and returns the number of released bytes.
+    pub fn trim(&self) -> usize {
+        let idle = std::mem::take(
+            &mut self
+                .inner
+                .state
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .idle,
+        );
+        idle.len() * self.inner.buffer_size
+    }
+
+    /// Returns the current statistics of the pool.
+    pub fn stats(&self) -> FfiPoolStats {
+        let state = self.inner.state.lock().unwrap_or_else(|e| e.into_inner());
+        FfiPoolStats {
+            buffer_size: self.inner.buffer_size,
+            capacity: self.inner.capacity,
+            idle: state.idle.len(),
+            outstanding: state.outstanding,
+            hits: state.hits,
+            misses: state.misses,
+        }
+    }
+
+    fn recycle(&self, bytes: Box<[u8]>) {
+        let mut state = self.inner.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.outstanding = state.outstanding.saturating_sub(1);
+        if state.idle.len() < self.inner.capacity {
+            state.idle.push(bytes);
+        }
+    }
+}
+
+impl std::fmt::Debug for BufferPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BufferPool")
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+/// Buffer acquired from a [`BufferPool`], which is released into its pool when dropped.
+///
+/// Derefs to its first `len` bytes, the whole buffer has the buffer size of the pool.
+#[derive(Debug)]
+pub struct PooledBuffer {
+    bytes: Box<[u8]>,
+    len: usize,
+    pool: BufferPool,
+}
+
+impl PooledBuffer {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the size of the whole buffer, the buffer size of its pool.
+    pub fn capacity(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Sets the length, e.g. to the number of bytes received into the whole buffer.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FfiBufferError::OutOfBounds`] if `len` exceeds the capacity,
+    /// the length is left unchanged.
+    pub fn set_len(&mut self, len: usize) -> Result<(), FfiBufferError> {
+        if len > self.capacity() {
+            return Err(FfiBufferError::OutOfBounds {
+                index: len,
+                len: self.capacity(),
+            });
+        }
+
+        self.len = len;
+        Ok(())
+    }
+
+    /// Returns the whole buffer, including the bytes beyond `len`.
+    pub fn as_whole_mut_slice(&mut self) -> &mut [u8] {
+        &mut self.bytes
+    }
+
+    /// Converts the buffer into a byte buffer of `len` bytes, to be passed to the host.
+    ///
+    /// The buffer must be returned with [`return_byte_buffer`] at some point, it must not be
+    /// released with any other function.
+    pub fn into_byte_buffer(mut self) -> ByteBuffer {
+        // Taken, so dropping the outstanding buffer doesn't release it.
+        let bytes = std::mem::take(&mut self.bytes);
+        let (len, pool) = (self.len, self.pool.clone());
+        drop(self);
+
+        let ptr = Box::into_raw(bytes).cast::<u8>();
+        stats::buffer_created(pool.inner.buffer_size);
+        audit::record(
+            FfiAuditEvent::Export,
+            ptr,
+            pool.inner.buffer_size,
+            "PooledBuffer::into_byte_buffer",
+        );
+        EXPORTED
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(ptr as usize, pool);
+
+        // Never the canonical empty buffer, the pointer identifies the buffer on return.
+        ByteBuffer { ptr, len }
+    }
+}
+
+impl Drop for PooledBuffer {
+    fn drop(&mut self) {
+        if !self.bytes.is_empty() {
+            self.pool.recycle(std::mem::take(&mut self.bytes));
+        }
+    }
+}
+
+impl Deref for PooledBuffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+impl DerefMut for PooledBuffer {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        &mut self.bytes[..self.len]
+    }
+}
+
+/// Returns the given byte buffer (see [`PooledBuffer::into_byte_buffer`]) into its pool.
+///
+/// Returns the byte buffer unchanged if it is not a pooled buffer handed to the host.
+pub fn return_byte_buffer(buffer: ByteBuffer) -> Result<(), ByteBuffer> {
+    let Some(pool) = EXPORTED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&(buffer.ptr as usize))
+    else {
+        return Err(buffer);
+    };
+
+    let buffer_size = pool.inner.buffer_size;
+    stats::buffer_reclaimed(buffer_size);
+    audit::record(
+        FfiAuditEvent::Import,
+        buffer.ptr,
+        buffer_size,
+        "pool::return_byte_buffer",
+    );
+
+    let slice_raw = std::ptr::slice_from_raw_parts_mut(buffer.ptr, buffer_size);
+    pool.recycle(unsafe { Box::from_raw(slice_raw) });
+
+    Ok(())
+}
+
+/// Returns the global pool, used by the exported functions.
+pub fn global() -> BufferPool {
+    GLOBAL.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Replaces the global pool with an empty pool with the given options, see [`BufferPool::new`].
+///
+/// Buffers acquired from the previous global pool are still released into it.
+///
+/// # Errors
+///
+/// See [`BufferPool::new`].
+pub fn configure_global(buffer_size: usize, capacity: usize) -> Result<(), FfiBufferError> {
+    let pool = BufferPool::new(buffer_size, capacity)?;
+    *GLOBAL.write().unwrap_or_else(|e| e.into_inner()) = pool;
+    Ok(())
+}
+
+// Initializes the registry of the pooled buffers handed to the host, see `crate::lifecycle::init`.
+pub(crate) fn init() {
+    LazyLock::force(&EXPORTED);
+}
+
+/// Deallocates the idle buffers of all pools and returns the number of released bytes.
+pub fn trim_all() -> usize {
+    pools().iter().map(BufferPool::trim).sum()
+}
+
+/// Returns the number of idle buffers of all pools and the sum of their sizes.
+pub(crate) fn occupancy() -> (usize, usize) {
+    pools().iter().fold((0, 0), |(buffers, bytes), pool| {
+        let idle = pool
+            .inner
+            .state
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .idle
+            .len();
+        (buffers + idle, bytes + idle * pool.inner.buffer_size)
+    })
+}
+
+// Upgraded, so the registry isn't locked while the pools are.
+fn pools() -> Vec<BufferPool> {
+    POOLS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .filter_map(Weak::upgrade)
+        .map(|inner| BufferPool { inner })
+        .collect()
+}
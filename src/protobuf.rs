@@ -0,0 +1,56 @@
+//! Protobuf message marshalling with prost, so message types (e.g. of a gRPC API) cross
+//! the FFI boundary with one call on each side.
+
+use prost::Message;
+
+use crate::{
+    borrowed::c_bytes_as_slice_ref, error::FfiBufferError, into_boxed_byte_slice_raw,
+    new_zeroed_boxed_byte_slice,
+};
+
+/// Encodes the given message into a new buffer of exactly its encoded length and returns
+/// its raw parts `(ptr, len)`, to be passed to the host.
+///
+/// Note: A message with all fields at their defaults encodes to 0 bytes, which results in
+/// a null `ptr` and a `len` of 0.
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::Alloc`] if the allocation failed.
+///
+/// # Safety
+///
+/// Later at some point, the buffer must be converted back with
+/// [`crate::from_boxed_byte_slice_raw`] (or freed with [`crate::free_boxed_byte_slice_raw`]).
+#[cfg_attr(any(feature = "debug-track", feature = "tracing"), track_caller)]
+pub fn message_into_raw<M: Message>(message: &M) -> Result<(*const u8, usize), FfiBufferError> {
+    let mut bytes = new_zeroed_boxed_byte_slice(message.encoded_len())?;
+    message
+        .encode(&mut &mut bytes[..])
+        .map_err(|error| FfiBufferError::Codec(error.to_string()))?;
+
+    Ok(into_boxed_byte_slice_raw(bytes))
+}
+
+/// Decodes a message from the given byte range, e.g. received from the host.
+/// The bytes are only read, the caller keeps owning them.
+///
+/// Note: A `len` of 0 decodes the message with all fields at their defaults.
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::Codec`] if the bytes are not a valid encoding of the message,
+/// see [`c_bytes_as_slice_ref`] for the other errors. The error can be reported to the host
+/// with [`FfiBufferError::report`].
+///
+/// # Safety
+///
+/// The byte range must be valid (not deallocated) while this function is in process.
+pub unsafe fn message_from_raw<M: Message + Default>(
+    ptr: *const u8,
+    len: usize,
+) -> Result<M, FfiBufferError> {
+    let bytes = unsafe { c_bytes_as_slice_ref(ptr, len) }?;
+
+    M::decode(bytes).map_err(|error| FfiBufferError::Codec(error.to_string()))
+}
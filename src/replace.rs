@@ -0,0 +1,103 @@
+//! Search and replace of byte sequences, e.g. for token substitution in payloads.
+//!
+//! Occurrences are found left to right and do not overlap.
+
+use crate::{ByteBuffer, error::FfiBufferError, new_zeroed_boxed_byte_slice};
+
+/// Returns the offset of the first occurrence of `needle` in `haystack`,
+/// `None` if there is none or `needle` is empty.
+pub fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    if needle.is_empty() {
+        return None;
+    }
+
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// Returns a new byte buffer with all occurrences of `needle` in `src` replaced
+/// with `replacement`.
+///
+/// The new buffer is allocated once with its final length.
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::InvalidArgument`] if `needle` is empty,
+/// [`FfiBufferError::CapacityOverflow`] if the length of the new buffer overflows,
+/// [`FfiBufferError::Alloc`] if the allocation failed.
+pub fn replace_all(
+    src: &[u8],
+    needle: &[u8],
+    replacement: &[u8],
+) -> Result<ByteBuffer, FfiBufferError> {
+    if needle.is_empty() {
+        return Err(FfiBufferError::InvalidArgument("empty needle"));
+    }
+
+    let offsets = occurrences(src, needle);
+    let len = (src.len() - offsets.len() * needle.len())
+        .checked_add(
+            offsets
+                .len()
+                .checked_mul(replacement.len())
+                .ok_or(FfiBufferError::CapacityOverflow)?,
+        )
+        .ok_or(FfiBufferError::CapacityOverflow)?;
+
+    let mut dst = new_zeroed_boxed_byte_slice(len)?;
+    let (mut read, mut written) = (0, 0);
+    for offset in offsets {
+        let kept = offset - read;
+        dst[written..written + kept].copy_from_slice(&src[read..offset]);
+        written += kept;
+
+        dst[written..written + replacement.len()].copy_from_slice(replacement);
+        written += replacement.len();
+        read = offset + needle.len();
+    }
+    dst[written..].copy_from_slice(&src[read..]);
+
+    Ok(ByteBuffer::from_boxed_slice(dst))
+}
+
+/// Replaces all occurrences of `needle` in `bytes` with `replacement` in place
+/// and returns the number of replaced occurrences.
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::InvalidArgument`] (and leaves the bytes untouched) if `needle`
+/// is empty or `replacement` does not have the length of `needle`.
+pub fn replace_all_in_place(
+    bytes: &mut [u8],
+    needle: &[u8],
+    replacement: &[u8],
+) -> Result<usize, FfiBufferError> {
+    if needle.is_empty() {
+        return Err(FfiBufferError::InvalidArgument("empty needle"));
+    }
+    if replacement.len() != needle.len() {
+        return Err(FfiBufferError::InvalidArgument(
+            "replacement length differs from the needle length",
+        ));
+    }
+
+    let offsets = occurrences(bytes, needle);
+    for &offset in &offsets {
+        bytes[offset..offset + needle.len()].copy_from_slice(replacement);
+    }
+
+    Ok(offsets.len())
+}
+
+// Returns the offsets of all non-overlapping occurrences of the non-empty `needle`.
+fn occurrences(haystack: &[u8], needle: &[u8]) -> Vec<usize> {
+    let mut offsets = Vec::new();
+    let mut start = 0;
+    while let Some(offset) = find(&haystack[start..], needle) {
+        offsets.push(start + offset);
+        start += offset + needle.len();
+    }
+
+    offsets
+}
@@ -0,0 +1,137 @@
+//! FFI compatible tagged types for "this call may fail" and "this value may be absent",
+//! a discriminant followed by the payload.
+//!
+//! The payload of an error or an absent value is `T::default()`, so the payload is always
+//! initialized and can be read by the host unconditionally.
+
+use crate::error::{FfiBufferError, FfiStatus};
+
+/// FFI compatible result, the `value` is only meaningful if `status` is [`FfiStatus::Ok`].
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FfiResult<T> {
+    pub status: FfiStatus,
+    pub value: T,
+}
+
+impl<T> FfiResult<T> {
+    pub fn ok(value: T) -> Self {
+        Self {
+            status: FfiStatus::Ok,
+            value,
+        }
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.status == FfiStatus::Ok
+    }
+
+    /// Converts into a result, the status is the error (if not [`FfiStatus::Ok`]).
+    pub fn into_result(self) -> Result<T, FfiStatus> {
+        match self.status {
+            FfiStatus::Ok => Ok(self.value),
+            status => Err(status),
+        }
+    }
+}
+
+impl<T: Default> FfiResult<T> {
+    /// Returns a failed result with the given status and the default value.
+    ///
+    /// Note: [`FfiStatus::Ok`] results in a successful result with the default value.
+    pub fn err(status: FfiStatus) -> Self {
+        Self {
+            status,
+            value: T::default(),
+        }
+    }
+}
+
+/// Returns a successful result with the default value.
+impl<T: Default> Default for FfiResult<T> {
+    fn default() -> Self {
+        Self::ok(T::default())
+    }
+}
+
+/// The error is converted into its status code, see [`FfiBufferError::code`].
+impl<T: Default> From<Result<T, FfiBufferError>> for FfiResult<T> {
+    fn from(src: Result<T, FfiBufferError>) -> Self {
+        match src {
+            Ok(value) => Self::ok(value),
+            Err(error) => Self::err(error.code()),
+        }
+    }
+}
+
+impl<T: Default> From<Result<T, FfiStatus>> for FfiResult<T> {
+    fn from(src: Result<T, FfiStatus>) -> Self {
+        match src {
+            Ok(value) => Self::ok(value),
+            Err(status) => Self::err(status),
+        }
+    }
+}
+
+impl<T> From<FfiResult<T>> for Result<T, FfiStatus> {
+    fn from(src: FfiResult<T>) -> Self {
+        src.into_result()
+    }
+}
+
+/// FFI compatible option, the `value` is only meaningful if `is_some` is true.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FfiOption<T> {
+    pub is_some: bool,
+    pub value: T,
+}
+
+impl<T> FfiOption<T> {
+    pub fn some(value: T) -> Self {
+        Self {
+            is_some: true,
+            value,
+        }
+    }
+
+    pub fn is_some(&self) -> bool {
+        self.is_some
+    }
+
+    pub fn into_option(self) -> Option<T> {
+        self.is_some.then_some(self.value)
+    }
+}
+
+impl<T: Default> FfiOption<T> {
+    /// Returns an absent value with the default value as payload.
+    pub fn none() -> Self {
+        Self {
+            is_some: false,
+            value: T::default(),
+        }
+    }
+}
+
+/// Returns an absent value.
+impl<T: Default> Default for FfiOption<T> {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+impl<T: Default> From<Option<T>> for FfiOption<T> {
+    fn from(src: Option<T>) -> Self {
+        match src {
+            Some(value) => Self::some(value),
+            None => Self::none(),
+        }
+    }
+}
+
+impl<T> From<FfiOption<T>> for Option<T> {
+    fn from(src: FfiOption<T>) -> Self {
+        src.into_option()
+    }
+}
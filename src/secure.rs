@@ -0,0 +1,264 @@
+//! Buffers for secrets (e.g. key material or tokens), which are zeroized with volatile writes
+//! before they are deallocated, so secrets don't linger in freed heap memory.
+//!
+//! With the `zeroize` feature the bytes are wiped with the `zeroize` crate and
+//! [`SecretBytes`] implements its `Zeroize` and `ZeroizeOnDrop` traits. With the `mlock`
+//! feature secrets can be allocated page-locked, so they can't be swapped to disk.
+
+use std::ops::{Deref, DerefMut};
+
+#[cfg(feature = "mlock")]
+use crate::mlock::LockedPages;
+use crate::{ByteBuffer, error::FfiBufferError, new_zeroed_boxed_byte_slice};
+
+/// How a page-locked allocation (see [`SecretBytes::new_locked`]) reacts if the pages
+/// can't be locked, e.g. beyond the limit of locked memory.
+#[cfg(feature = "mlock")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockMode {
+    /// The allocation fails.
+    Required,
+    /// The allocation falls back to unlocked heap memory, see [`SecretBytes::is_locked`].
+    BestEffort,
+}
+
+/// FFI compatible representation of a boxed byte slice `Box<[u8]>` holding a secret,
+/// the layout is the same as [`ByteBuffer`].
+///
+/// An empty buffer is always represented by a null `ptr` and a `len` of 0.
+///
+/// Note: The buffer does not drop its bytes - it must be converted back with
+/// [`SecureByteBuffer::into_secret`] or released with [`SecureByteBuffer::free`]
+/// (exported as `free_secure`), which zeroize the bytes.
+#[repr(C)]
+#[derive(Debug)]
+pub struct SecureByteBuffer {
+    pub ptr: *mut u8,
+    pub len: usize,
+}
+
+impl SecureByteBuffer {
+    /// The canonical empty buffer, a null `ptr` and a `len` of 0.
+    pub const EMPTY: Self = Self {
+        ptr: std::ptr::null_mut(),
+        len: 0,
+    };
+
+    /// Allocates a new zeroed buffer with the given `len`, e.g. for the host to write
+    /// a secret into.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FfiBufferError::CapacityOverflow`] if `len` exceeds `isize::MAX`,
+    /// [`FfiBufferError::Alloc`] if the allocation failed.
+    #[cfg_attr(any(feature = "debug-track", feature = "tracing"), track_caller)]
+    pub fn new(len: usize) -> Result<Self, FfiBufferError> {
+        new_zeroed_boxed_byte_slice(len).map(Self::from_boxed_slice)
+    }
+
+    /// Allocates a new zeroed page-locked buffer with the given `len`,
+    /// see [`SecretBytes::new_locked`].
+    ///
+    /// # Errors
+    ///
+    /// See [`SecretBytes::new_locked`].
+    #[cfg(feature = "mlock")]
+    #[cfg_attr(any(feature = "debug-track", feature = "tracing"), track_caller)]
+    pub fn new_locked(len: usize, mode: LockMode) -> Result<Self, FfiBufferError> {
+        SecretBytes::new_locked(len, mode).map(SecretBytes::into_secure_byte_buffer)
+    }
+
+    /// Converts the given boxed byte slice into a secure buffer.
+    ///
+    /// Note: No checksum of the secret is recorded (see [`crate::checked`]).
+    #[cfg_attr(any(feature = "debug-track", feature = "tracing"), track_caller)]
+    pub fn from_boxed_slice(src: Box<[u8]>) -> Self {
+        let (ptr, len) = ByteBuffer::from_boxed_slice_writable(src).into_raw();
+        Self { ptr, len }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Converts the buffer back to rust managed bytes, which are zeroized when dropped.
+    ///
+    /// # Safety
+    ///
+    /// The buffer must have been created by this library (or have the layout `Box<[u8]>`)
+    /// and must not be used afterwards.
+    #[cfg_attr(any(feature = "debug-track", feature = "tracing"), track_caller)]
+    pub unsafe fn into_secret(self) -> SecretBytes {
+        #[cfg(feature = "mlock")]
+        if let Some(pages) = LockedPages::import(self.ptr) {
+            return SecretBytes(Storage::Locked(pages));
+        }
+
+        let buffer = ByteBuffer::from_raw(self.ptr, self.len);
+        SecretBytes(Storage::Heap(unsafe { buffer.into_boxed_slice() }))
+    }
+
+    /// Zeroizes and releases the buffer.
+    ///
+    /// # Safety
+    ///
+    /// See [`SecureByteBuffer::into_secret`].
+    #[cfg_attr(any(feature = "debug-track", feature = "tracing"), track_caller)]
+    pub unsafe fn free(self) {
+        drop(unsafe { self.into_secret() });
+    }
+}
+
+impl Default for SecureByteBuffer {
+    fn default() -> Self {
+        Self::EMPTY
+    }
+}
+
+impl From<SecretBytes> for SecureByteBuffer {
+    fn from(src: SecretBytes) -> Self {
+        src.into_secure_byte_buffer()
+    }
+}
+
+/// Rust managed bytes of a secret, which are zeroized when dropped.
+#[derive(Default)]
+pub struct SecretBytes(Storage);
+
+enum Storage {
+    Heap(Box<[u8]>),
+    #[cfg(feature = "mlock")]
+    Locked(LockedPages),
+}
+
+impl Default for Storage {
+    fn default() -> Self {
+        Self::Heap(Box::default())
+    }
+}
+
+impl SecretBytes {
+    /// Takes the given bytes, which are zeroized when dropped.
+    ///
+    /// Note: Copies made before (e.g. when a `Vec` grew) are not zeroized.
+    pub fn new(bytes: Box<[u8]>) -> Self {
+        Self(Storage::Heap(bytes))
+    }
+
+    /// Allocates `len` zeroed bytes in pages locked in memory, which can't be swapped to disk.
+    /// The pages are unlocked and unmapped after the bytes were zeroized.
+    ///
+    /// Note: Each allocation maps whole pages (e.g. 4 KiB), so small secrets should be
+    /// allocated together.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FfiBufferError::Alloc`] if the allocation failed. With [`LockMode::Required`]
+    /// returns [`FfiBufferError::LockLimit`] if locking exceeds the limit of locked memory
+    /// (e.g. `RLIMIT_MEMLOCK`), [`FfiBufferError::Io`] if locking failed otherwise.
+    #[cfg(feature = "mlock")]
+    pub fn new_locked(len: usize, mode: LockMode) -> Result<Self, FfiBufferError> {
+        if len == 0 {
+            return Ok(Self::default());
+        }
+
+        match LockedPages::allocate(len) {
+            Ok(pages) => Ok(Self(Storage::Locked(pages))),
+            Err(_) if mode == LockMode::BestEffort => {
+                new_zeroed_boxed_byte_slice(len).map(Self::new)
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Returns true if the bytes are in pages locked in memory.
+    pub fn is_locked(&self) -> bool {
+        match &self.0 {
+            #[cfg(feature = "mlock")]
+            Storage::Locked(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Converts the bytes into a secure buffer, to be passed to the host.
+    #[cfg_attr(any(feature = "debug-track", feature = "tracing"), track_caller)]
+    pub fn into_secure_byte_buffer(mut self) -> SecureByteBuffer {
+        match std::mem::take(&mut self.0) {
+            Storage::Heap(bytes) => SecureByteBuffer::from_boxed_slice(bytes),
+            #[cfg(feature = "mlock")]
+            Storage::Locked(pages) => {
+                let (ptr, len) = pages.export();
+                SecureByteBuffer { ptr, len }
+            }
+        }
+    }
+}
+
+/// The secret is not printed.
+impl std::fmt::Debug for SecretBytes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SecretBytes([REDACTED; {}])", self.len())
+    }
+}
+
+impl Deref for SecretBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match &self.0 {
+            Storage::Heap(bytes) => bytes,
+            #[cfg(feature = "mlock")]
+            Storage::Locked(pages) => pages.as_slice(),
+        }
+    }
+}
+
+impl DerefMut for SecretBytes {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        match &mut self.0 {
+            Storage::Heap(bytes) => bytes,
+            #[cfg(feature = "mlock")]
+            Storage::Locked(pages) => pages.as_mut_slice(),
+        }
+    }
+}
+
+impl Drop for SecretBytes {
+    fn drop(&mut self) {
+        wipe(self);
+    }
+}
+
+impl From<Box<[u8]>> for SecretBytes {
+    fn from(src: Box<[u8]>) -> Self {
+        Self::new(src)
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for SecretBytes {
+    fn zeroize(&mut self) {
+        wipe(self);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for SecretBytes {}
+
+// Zeroes the given bytes, so the writes are not optimized away right before the deallocation.
+fn wipe(bytes: &mut [u8]) {
+    #[cfg(feature = "zeroize")]
+    zeroize::Zeroize::zeroize(bytes);
+
+    #[cfg(not(feature = "zeroize"))]
+    {
+        for byte in bytes.iter_mut() {
+            unsafe { std::ptr::write_volatile(byte, 0) };
+        }
+        std::sync::atomic::compiler_fence(std::sync::atomic::Ordering::SeqCst);
+    }
+}
@@ -0,0 +1,163 @@
+//! serde support for the buffer types, serializing their contents as bytes.
+//!
+//! The raw types ([`ByteBuffer`], [`FfiBufferArray`]) can't guarantee valid bytes,
+//! so they are serialized through an explicitly created view, see [`raw`].
+//!
+//! Any serializable value can be serialized straight into a buffer with [`to_ffi_buffer`]
+//! in one of the [`Format`]s enabled by the `bincode` and `postcard` features.
+
+#[cfg(any(feature = "bincode", feature = "postcard"))]
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error as _, ser::SerializeSeq};
+use serde_bytes::ByteBuf;
+
+use crate::{ByteBuffer, FfiBuffer, FfiBufferArray, ForeignBuffer};
+#[cfg(any(feature = "bincode", feature = "postcard"))]
+use crate::{borrowed::c_bytes_as_slice_ref, error::FfiBufferError};
+
+/// Binary format of [`to_ffi_buffer`] and [`from_ffi_buffer`].
+#[cfg(any(feature = "bincode", feature = "postcard"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// bincode with its standard configuration (varint integers, little endian).
+    #[cfg(feature = "bincode")]
+    Bincode,
+    /// postcard.
+    #[cfg(feature = "postcard")]
+    Postcard,
+}
+
+/// Serializes the given value in the given format into a new buffer, to be passed to the host.
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::Codec`] if the value can't be serialized.
+#[cfg(any(feature = "bincode", feature = "postcard"))]
+#[cfg_attr(any(feature = "debug-track", feature = "tracing"), track_caller)]
+pub fn to_ffi_buffer<T: Serialize + ?Sized>(
+    value: &T,
+    format: Format,
+) -> Result<ByteBuffer, FfiBufferError> {
+    let bytes: Vec<u8> = match format {
+        #[cfg(feature = "bincode")]
+        Format::Bincode => bincode::serde::encode_to_vec(value, bincode::config::standard())
+            .map_err(|error| FfiBufferError::Codec(error.to_string()))?,
+        #[cfg(feature = "postcard")]
+        Format::Postcard => postcard::to_allocvec(value)
+            .map_err(|error| FfiBufferError::Codec(error.to_string()))?,
+    };
+
+    Ok(ByteBuffer::from(bytes))
+}
+
+/// Deserializes a value in the given format from the given byte range, e.g. received from
+/// the host. The bytes are only read, the caller keeps owning them.
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::Codec`] if the bytes are not a valid value (or have trailing bytes),
+/// see [`c_bytes_as_slice_ref`] for the other errors.
+///
+/// # Safety
+///
+/// The byte range must be valid (not deallocated) while this function is in process.
+#[cfg(any(feature = "bincode", feature = "postcard"))]
+pub unsafe fn from_ffi_buffer<T: DeserializeOwned>(
+    ptr: *const u8,
+    len: usize,
+    format: Format,
+) -> Result<T, FfiBufferError> {
+    let bytes = unsafe { c_bytes_as_slice_ref(ptr, len) }?;
+
+    let (value, rest) = match format {
+        #[cfg(feature = "bincode")]
+        Format::Bincode => {
+            let (value, read) =
+                bincode::serde::decode_from_slice(bytes, bincode::config::standard())
+                    .map_err(|error| FfiBufferError::Codec(error.to_string()))?;
+            (value, bytes.len() - read)
+        }
+        #[cfg(feature = "postcard")]
+        Format::Postcard => {
+            let (value, rest) = postcard::take_from_bytes(bytes)
+                .map_err(|error| FfiBufferError::Codec(error.to_string()))?;
+            (value, rest.len())
+        }
+    };
+    if rest != 0 {
+        return Err(FfiBufferError::Codec(format!("{rest} trailing bytes")));
+    }
+
+    Ok(value)
+}
+
+/// View of a raw buffer type, which serializes its contents.
+#[derive(Debug, Clone, Copy)]
+pub struct Raw<'a, T>(&'a T);
+
+/// Returns a serializable view of the given raw buffer type.
+///
+/// # Safety
+///
+/// The given value must be valid (not deallocated or released) while the view is used.
+pub unsafe fn raw<T>(value: &T) -> Raw<'_, T> {
+    Raw(value)
+}
+
+impl Serialize for Raw<'_, ByteBuffer> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(unsafe { self.0.as_slice() })
+    }
+}
+
+impl Serialize for Raw<'_, FfiBufferArray> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let items = unsafe { self.0.items() };
+
+        let mut seq = serializer.serialize_seq(Some(items.len()))?;
+        for item in items {
+            seq.serialize_element(serde_bytes::Bytes::new(unsafe { item.as_slice() }))?;
+        }
+        seq.end()
+    }
+}
+
+impl Serialize for FfiBuffer {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(self.as_slice())
+    }
+}
+
+impl Serialize for ForeignBuffer {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(self.as_slice())
+    }
+}
+
+/// Deserializes into a new buffer, to be converted back with [`ByteBuffer::into_boxed_slice`].
+impl<'de> Deserialize<'de> for ByteBuffer {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bytes = ByteBuf::deserialize(deserializer)?;
+        Ok(ByteBuffer::from(bytes.into_vec()))
+    }
+}
+
+/// Deserializes into a new array where every item owns its bytes,
+/// to be released with [`crate::free_buffer_array_raw`].
+impl<'de> Deserialize<'de> for FfiBufferArray {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let items = Vec::<ByteBuf>::deserialize(deserializer)?;
+        Ok(FfiBufferArray::from(
+            items.into_iter().map(ByteBuf::into_vec).collect::<Vec<_>>(),
+        ))
+    }
+}
+
+/// Deserializes into a new buffer with the default options, which takes over the
+/// deserialized bytes. A failed allocation is a deserialization error.
+impl<'de> Deserialize<'de> for FfiBuffer {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bytes = ByteBuf::deserialize(deserializer)?;
+        FfiBuffer::from_vec(bytes.into_vec()).map_err(D::Error::custom)
+    }
+}
@@ -0,0 +1,105 @@
+//! Reference-counted shared buffers (`Arc<[u8]>`), so the same payload is handed to many
+//! host-side consumers without copies and freed when the last side releases it.
+//!
+//! The exported references are counted in a registry keyed by the data pointer,
+//! the payload is kept alive while the host holds at least one reference.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, LazyLock, Mutex},
+};
+
+use crate::{
+    audit::{self, FfiAuditEvent},
+    stats,
+};
+
+struct Shared {
+    payload: Arc<[u8]>,
+    // Number of references held by the host.
+    refs: usize,
+}
+
+static SHARED: LazyLock<Mutex<HashMap<usize, Shared>>> = LazyLock::new(Default::default);
+
+/// Exports a reference of the given payload and returns its raw parts `(ptr, len)`,
+/// an empty payload is represented by a null `ptr` and a `len` of 0.
+///
+/// Exporting the same payload again adds a reference. Each reference must be released
+/// with [`shared_release_raw`] at some point.
+pub fn shared_into_raw(src: Arc<[u8]>) -> (*const u8, usize) {
+    if src.is_empty() {
+        return (std::ptr::null(), 0);
+    }
+
+    let (ptr, len) = (src.as_ptr(), src.len());
+    let mut registry = SHARED.lock().unwrap_or_else(|e| e.into_inner());
+    match registry.get_mut(&(ptr as usize)) {
+        Some(shared) => shared.refs += 1,
+        None => {
+            registry.insert(
+                ptr as usize,
+                Shared {
+                    payload: src,
+                    refs: 1,
+                },
+            );
+            stats::buffer_created(len);
+            audit::record(FfiAuditEvent::Export, ptr, len, "shared_into_raw");
+        }
+    }
+
+    (ptr, len)
+}
+
+/// Adds a reference of the given exported payload, e.g. for another host-side consumer.
+///
+/// Returns false if the pointer is not an exported payload (or null).
+pub fn shared_clone_raw(ptr: *const u8) -> bool {
+    match SHARED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get_mut(&(ptr as usize))
+    {
+        Some(shared) => {
+            shared.refs += 1;
+            true
+        }
+        None => false,
+    }
+}
+
+/// Releases a reference of the given exported payload, the payload is freed if this was
+/// the last reference (and rust holds no reference either).
+///
+/// Returns false if the pointer is not an exported payload (or null).
+pub fn shared_release_raw(ptr: *const u8) -> bool {
+    let mut registry = SHARED.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(shared) = registry.get_mut(&(ptr as usize)) else {
+        return false;
+    };
+
+    shared.refs -= 1;
+    if shared.refs == 0 {
+        let shared = registry.remove(&(ptr as usize)).expect("entry exists");
+        drop(registry);
+
+        let len = shared.payload.len();
+        stats::buffer_reclaimed(len);
+        audit::record(FfiAuditEvent::Free, ptr, len, "shared_release_raw");
+    }
+
+    true
+}
+
+/// Returns the payload of the given exported pointer, e.g. received back from the host,
+/// without changing the references held by the host.
+///
+/// Returns `None` if the pointer is not an exported payload (or null).
+pub fn shared_get_raw(ptr: *const u8) -> Option<Arc<[u8]>> {
+    SHARED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&(ptr as usize))
+        .map(|shared| shared.payload.clone())
+}
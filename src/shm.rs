@@ -0,0 +1,708 @@
+//! Shared-memory mailbox, to hand buffers between the rust core and an out-of-process host
+//! without busy-polling.
+//!
+//! A mailbox is a named shared-memory region with a single slot for one message of up to
+//! its capacity. [`ShmMailbox::send`] blocks while the slot is full, [`ShmMailbox::recv`]
+//! blocks while it is empty. The waiting is process-shared: a `pthread` mutex and condition
+//! variable created with `PTHREAD_PROCESS_SHARED` in the region on unix (POSIX shared memory
+//! `/<name>`), two named auto-reset events `<name>.empty` and `<name>.full` on windows
+//! (a `Local\<name>` file mapping).
+//!
+//! The region starts with a header, all integers are native endian: the [`MAGIC`] bytes
+//! (written last by the creator), `u32` flags (0, reserved), the `u64` capacity and the `u64`
+//! length of the message in the slot, followed by the slot at offset 64 (after the
+//! synchronization on unix).
+//!
+//! Note: Both sides copy the message, the slot itself is never handed out. A process which
+//! dies while holding the lock on unix (only during a copy) blocks the other side.
+
+use std::{
+    io,
+    sync::atomic::{AtomicU32, Ordering},
+    time::Duration,
+};
+
+use crate::{error::FfiBufferError, new_zeroed_boxed_byte_slice};
+
+/// Magic bytes a mailbox region starts with, once it is initialized.
+pub const MAGIC: [u8; 4] = *b"FBBM";
+
+// Length of the header, the slot follows it.
+const HEADER_LEN: usize = 64;
+
+#[repr(C)]
+struct Header {
+    magic: AtomicU32,
+    flags: u32,
+    capacity: u64,
+    len: u64,
+}
+
+/// One-slot mailbox in a named shared-memory region, see the [module](self) docs.
+///
+/// The creator removes the name when the mailbox is dropped, a process which opened the
+/// mailbox keeps using the region until it drops its mailbox.
+pub struct ShmMailbox {
+    region: os::Region,
+    capacity: usize,
+}
+
+// The region is only accessed while holding the slot (see `os::Region::exchange`).
+unsafe impl Send for ShmMailbox {}
+unsafe impl Sync for ShmMailbox {}
+
+impl ShmMailbox {
+    /// Creates a new mailbox with the given name and a slot of `capacity` bytes.
+    ///
+    /// The name must not be empty or contain slashes, backslashes or NUL bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FfiBufferError::InvalidArgument`] if the name is invalid,
+    /// [`FfiBufferError::CapacityOverflow`] if the region length overflows,
+    /// [`FfiBufferError::Io`] if the region or its synchronization can't be created
+    /// (e.g. `AlreadyExists` if a mailbox with the name exists).
+    pub fn create(name: &str, capacity: usize) -> Result<Self, FfiBufferError> {
+        check_name(name)?;
+        let len = HEADER_LEN
+            .checked_add(capacity)
+            .filter(|len| *len <= isize::MAX as usize)
+            .ok_or(FfiBufferError::CapacityOverflow)?;
+
+        let region = os::Region::create(name, len)?;
+        let header = region.data().cast::<Header>();
+        unsafe {
+            (&raw mut (*header).capacity).write(capacity as u64);
+            (*header)
+                .magic
+                .store(u32::from_ne_bytes(MAGIC), Ordering::Release);
+        }
+
+        Ok(Self { region, capacity })
+    }
+
+    /// Opens the mailbox with the given name, created by another process (or this one).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FfiBufferError::InvalidArgument`] if the name is invalid,
+    /// [`FfiBufferError::InvalidMagic`] if the region is not (yet) an initialized mailbox,
+    /// [`FfiBufferError::Codec`] if its capacity exceeds the region,
+    /// [`FfiBufferError::Io`] if the region can't be opened (e.g. `NotFound`).
+    pub fn open(name: &str) -> Result<Self, FfiBufferError> {
+        check_name(name)?;
+
+        let region = os::Region::open(name)?;
+        if region.data_len() < HEADER_LEN {
+            return Err(FfiBufferError::InvalidMagic([0; 4]));
+        }
+        let header = region.data().cast::<Header>();
+        let magic = unsafe { &(*header).magic }.load(Ordering::Acquire);
+        if magic.to_ne_bytes() != MAGIC {
+            return Err(FfiBufferError::InvalidMagic(magic.to_ne_bytes()));
+        }
+
+        let capacity = unsafe { (&raw const (*header).capacity).read() };
+        let capacity = usize::try_from(capacity)
+            .ok()
+            .filter(|capacity| *capacity <= region.data_len() - HEADER_LEN)
+            .ok_or_else(|| {
+                FfiBufferError::Codec(format!(
+                    "mailbox capacity {capacity} exceeds the region of {} bytes",
+                    region.data_len()
+                ))
+            })?;
+
+        Ok(Self { region, capacity })
+    }
+
+    /// Returns the maximum length of a message.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Copies the given message into the slot, waits while the slot is full (at most `timeout`,
+    /// indefinitely if None).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FfiBufferError::InvalidArgument`] if the message is longer than the capacity,
+    /// [`FfiBufferError::Io`] if the wait timed out (`TimedOut`) or failed.
+    pub fn send(&self, bytes: &[u8], timeout: Option<Duration>) -> Result<(), FfiBufferError> {
+        if bytes.len() > self.capacity {
+            return Err(FfiBufferError::InvalidArgument(
+                "message longer than the mailbox capacity",
+            ));
+        }
+
+        self.region.exchange(false, timeout, || {
+            let header = self.region.data().cast::<Header>();
+            unsafe {
+                std::ptr::copy_nonoverlapping(bytes.as_ptr(), self.slot(), bytes.len());
+                (&raw mut (*header).len).write(bytes.len() as u64);
+            }
+            Ok(())
+        })
+    }
+
+    /// Takes the message out of the slot into a new boxed byte slice, waits while the slot
+    /// is empty (at most `timeout`, indefinitely if None).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FfiBufferError::Io`] if the wait timed out (`TimedOut`) or failed,
+    /// [`FfiBufferError::Codec`] if the message length exceeds the capacity,
+    /// [`FfiBufferError::Alloc`] if the allocation failed (the message stays in the slot).
+    pub fn recv(&self, timeout: Option<Duration>) -> Result<Box<[u8]>, FfiBufferError> {
+        self.region.exchange(true, timeout, || {
+            let header = self.region.data().cast::<Header>();
+            let len = unsafe { (&raw const (*header).len).read() };
+            let len = usize::try_from(len)
+                .ok()
+                .filter(|len| *len <= self.capacity)
+                .ok_or_else(|| {
+                    FfiBufferError::Codec(format!(
+                        "message length {len} exceeds the mailbox capacity {}",
+                        self.capacity
+                    ))
+                })?;
+
+            let mut bytes = new_zeroed_boxed_byte_slice(len)?;
+            unsafe { std::ptr::copy_nonoverlapping(self.slot(), bytes.as_mut_ptr(), len) };
+            Ok(bytes)
+        })
+    }
+
+    fn slot(&self) -> *mut u8 {
+        unsafe { self.region.data().add(HEADER_LEN) }
+    }
+}
+
+impl std::fmt::Debug for ShmMailbox {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ShmMailbox")
+            .field("capacity", &self.capacity)
+            .finish_non_exhaustive()
+    }
+}
+
+fn check_name(name: &str) -> Result<(), FfiBufferError> {
+    if name.is_empty() || name.contains(['/', '\\', '\0']) {
+        return Err(FfiBufferError::InvalidArgument("invalid mailbox name"));
+    }
+
+    Ok(())
+}
+
+fn timed_out() -> FfiBufferError {
+    FfiBufferError::Io(io::ErrorKind::TimedOut.into())
+}
+
+#[cfg(unix)]
+mod os {
+    use std::{
+        ffi::CString,
+        io,
+        time::{Duration, SystemTime},
+    };
+
+    use crate::error::FfiBufferError;
+
+    // Process-shared synchronization at the start of the region, followed by the data.
+    #[repr(C)]
+    struct Sync {
+        mutex: libc::pthread_mutex_t,
+        cond: libc::pthread_cond_t,
+        full: u32,
+    }
+
+    const SYNC_LEN: usize = size_of::<Sync>().next_multiple_of(64);
+
+    pub(super) struct Region {
+        ptr: *mut u8,
+        len: usize,
+        // The name is removed on drop by the creator.
+        created: Option<CString>,
+    }
+
+    impl Region {
+        pub(super) fn create(name: &str, data_len: usize) -> io::Result<Self> {
+            let name = shm_name(name);
+            let len = SYNC_LEN + data_len;
+
+            let fd = unsafe {
+                libc::shm_open(
+                    name.as_ptr(),
+                    libc::O_CREAT | libc::O_EXCL | libc::O_RDWR,
+                    0o600 as libc::c_uint,
+                )
+            };
+            if fd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let region = libc::off_t::try_from(len)
+                .map_err(|_| io::Error::from(io::ErrorKind::InvalidInput))
+                .and_then(|size| {
+                    if unsafe { libc::ftruncate(fd, size) } != 0 {
+                        return Err(io::Error::last_os_error());
+                    }
+                    unsafe { map(fd, len) }
+                });
+            unsafe { libc::close(fd) };
+            let region = match region {
+                Ok(ptr) => Self {
+                    ptr,
+                    len,
+                    created: Some(name),
+                },
+                Err(error) => {
+                    unsafe { libc::shm_unlink(name.as_ptr()) };
+                    return Err(error);
+                }
+            };
+
+            unsafe { region.init_sync() }?;
+            Ok(region)
+        }
+
+        pub(super) fn open(name: &str) -> io::Result<Self> {
+            let name = shm_name(name);
+
+            let fd = unsafe { libc::shm_open(name.as_ptr(), libc::O_RDWR, 0) };
+            if fd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let mut stat = unsafe { std::mem::zeroed::<libc::stat>() };
+            let region = if unsafe { libc::fstat(fd, &mut stat) } != 0 {
+                Err(io::Error::last_os_error())
+            } else {
+                match usize::try_from(stat.st_size) {
+                    Ok(len) if len >= SYNC_LEN => unsafe { map(fd, len) }.map(|ptr| Self {
+                        ptr,
+                        len,
+                        created: None,
+                    }),
+                    // Not (yet) truncated by the creator.
+                    _ => Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "shared memory region not initialized",
+                    )),
+                }
+            };
+            unsafe { libc::close(fd) };
+
+            region
+        }
+
+        pub(super) fn data(&self) -> *mut u8 {
+            unsafe { self.ptr.add(SYNC_LEN) }
+        }
+
+        pub(super) fn data_len(&self) -> usize {
+            self.len - SYNC_LEN
+        }
+
+        // Waits until the slot is `full` (or empty), runs `f` while holding the lock and
+        // flips the slot if it succeeded, waking the other side.
+        pub(super) fn exchange<R>(
+            &self,
+            full: bool,
+            timeout: Option<Duration>,
+            f: impl FnOnce() -> Result<R, FfiBufferError>,
+        ) -> Result<R, FfiBufferError> {
+            let sync = self.ptr.cast::<Sync>();
+            let (mutex, cond, state) = unsafe {
+                (
+                    &raw mut (*sync).mutex,
+                    &raw mut (*sync).cond,
+                    &raw mut (*sync).full,
+                )
+            };
+            let deadline = timeout.and_then(deadline);
+
+            check(unsafe { libc::pthread_mutex_lock(mutex) })?;
+            let _locked = Unlock(mutex);
+            while unsafe { state.read() } != u32::from(full) {
+                let result = match &deadline {
+                    Some(deadline) => unsafe {
+                        libc::pthread_cond_timedwait(cond, mutex, deadline)
+                    },
+                    None => unsafe { libc::pthread_cond_wait(cond, mutex) },
+                };
+                if result == libc::ETIMEDOUT {
+                    return Err(super::timed_out());
+                }
+                check(result)?;
+            }
+
+            let result = f()?;
+            unsafe {
+                state.write(u32::from(!full));
+                libc::pthread_cond_broadcast(cond);
+            }
+            Ok(result)
+        }
+
+        // Initializes the (zeroed) synchronization of a created region.
+        unsafe fn init_sync(&self) -> io::Result<()> {
+            let sync = self.ptr.cast::<Sync>();
+            unsafe {
+                let mut attr = std::mem::zeroed::<libc::pthread_mutexattr_t>();
+                check(libc::pthread_mutexattr_init(&mut attr))?;
+                let result = check(libc::pthread_mutexattr_setpshared(
+                    &mut attr,
+                    libc::PTHREAD_PROCESS_SHARED,
+                ))
+                .and_then(|()| check(libc::pthread_mutex_init(&raw mut (*sync).mutex, &attr)));
+                libc::pthread_mutexattr_destroy(&mut attr);
+                result?;
+
+                let mut attr = std::mem::zeroed::<libc::pthread_condattr_t>();
+                check(libc::pthread_condattr_init(&mut attr))?;
+                let result = check(libc::pthread_condattr_setpshared(
+                    &mut attr,
+                    libc::PTHREAD_PROCESS_SHARED,
+                ))
+                .and_then(|()| check(libc::pthread_cond_init(&raw mut (*sync).cond, &attr)));
+                libc::pthread_condattr_destroy(&mut attr);
+                result
+            }
+        }
+    }
+
+    impl Drop for Region {
+        fn drop(&mut self) {
+            unsafe { libc::munmap(self.ptr.cast(), self.len) };
+            if let Some(name) = &self.created {
+                unsafe { libc::shm_unlink(name.as_ptr()) };
+            }
+        }
+    }
+
+    struct Unlock(*mut libc::pthread_mutex_t);
+
+    impl Drop for Unlock {
+        fn drop(&mut self) {
+            unsafe { libc::pthread_mutex_unlock(self.0) };
+        }
+    }
+
+    // The name is checked, it has no NUL bytes.
+    fn shm_name(name: &str) -> CString {
+        CString::new(format!("/{name}")).expect("checked name")
+    }
+
+    unsafe fn map(fd: libc::c_int, len: usize) -> io::Result<*mut u8> {
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                fd,
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(ptr.cast())
+    }
+
+    // The `pthread` functions return the error number instead of setting `errno`.
+    fn check(result: libc::c_int) -> io::Result<()> {
+        if result != 0 {
+            return Err(io::Error::from_raw_os_error(result));
+        }
+
+        Ok(())
+    }
+
+    // Absolute `CLOCK_REALTIME` deadline of `pthread_cond_timedwait`, None if it overflows.
+    fn deadline(timeout: Duration) -> Option<libc::timespec> {
+        let deadline = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .ok()?
+            .checked_add(timeout)?;
+
+        let mut timespec = unsafe { std::mem::zeroed::<libc::timespec>() };
+        timespec.tv_sec = libc::time_t::try_from(deadline.as_secs()).ok()?;
+        timespec.tv_nsec = deadline.subsec_nanos() as _;
+        Some(timespec)
+    }
+}
+
+#[cfg(windows)]
+mod os {
+    use std::{ffi::c_void, io, time::Duration};
+
+    use crate::error::FfiBufferError;
+
+    type Handle = *mut c_void;
+
+    const INVALID_HANDLE_VALUE: Handle = -1isize as Handle;
+    const PAGE_READWRITE: u32 = 0x04;
+    const FILE_MAP_ALL_ACCESS: u32 = 0x000F_001F;
+    const EVENT_MODIFY_STATE: u32 = 0x0002;
+    const SYNCHRONIZE: u32 = 0x0010_0000;
+    const WAIT_OBJECT_0: u32 = 0;
+    const WAIT_TIMEOUT: u32 = 0x0102;
+    const INFINITE: u32 = u32::MAX;
+    const ERROR_ALREADY_EXISTS: i32 = 183;
+
+    // The `u64` length of the region at its start, followed by the data.
+    const LEN_LEN: usize = 64;
+
+    #[link(name = "kernel32")]
+    unsafe extern "system" {
+        fn CreateFileMappingW(
+            file: Handle,
+            attributes: *const c_void,
+            protect: u32,
+            size_high: u32,
+            size_low: u32,
+            name: *const u16,
+        ) -> Handle;
+        fn OpenFileMappingW(access: u32, inherit: i32, name: *const u16) -> Handle;
+        fn MapViewOfFile(
+            mapping: Handle,
+            access: u32,
+            offset_high: u32,
+            offset_low: u32,
+            len: usize,
+        ) -> *mut c_void;
+        fn UnmapViewOfFile(address: *const c_void) -> i32;
+        fn CreateEventW(
+            attributes: *const c_void,
+            manual_reset: i32,
+            initial_state: i32,
+            name: *const u16,
+        ) -> Handle;
+        fn OpenEventW(access: u32, inherit: i32, name: *const u16) -> Handle;
+        fn SetEvent(event: Handle) -> i32;
+        fn WaitForSingleObject(handle: Handle, ms: u32) -> u32;
+        fn CloseHandle(handle: Handle) -> i32;
+    }
+
+    pub(super) struct Region {
+        ptr: *mut u8,
+        len: usize,
+        mapping: Handle,
+        // Signaled while the slot is empty or full, each wait takes the slot.
+        empty: Handle,
+        full: Handle,
+    }
+
+    impl Region {
+        pub(super) fn create(name: &str, data_len: usize) -> io::Result<Self> {
+            let len = LEN_LEN + data_len;
+            let size = len as u64;
+
+            let mapping = unsafe {
+                CreateFileMappingW(
+                    INVALID_HANDLE_VALUE,
+                    std::ptr::null(),
+                    PAGE_READWRITE,
+                    (size >> 32) as u32,
+                    size as u32,
+                    wide(&format!("Local\\{name}")).as_ptr(),
+                )
+            };
+            let mapping = handle(mapping)?;
+            if io::Error::last_os_error().raw_os_error() == Some(ERROR_ALREADY_EXISTS) {
+                unsafe { CloseHandle(mapping) };
+                return Err(io::ErrorKind::AlreadyExists.into());
+            }
+            let mut region = Self {
+                ptr: std::ptr::null_mut(),
+                len,
+                mapping,
+                empty: std::ptr::null_mut(),
+                full: std::ptr::null_mut(),
+            };
+
+            region.ptr = unsafe { map(mapping, len) }?;
+            unsafe { region.ptr.cast::<u64>().write(size) };
+            region.empty = handle(unsafe {
+                CreateEventW(
+                    std::ptr::null(),
+                    0,
+                    1,
+                    wide(&event_name(name, "empty")).as_ptr(),
+                )
+            })?;
+            region.full = handle(unsafe {
+                CreateEventW(
+                    std::ptr::null(),
+                    0,
+                    0,
+                    wide(&event_name(name, "full")).as_ptr(),
+                )
+            })?;
+            Ok(region)
+        }
+
+        pub(super) fn open(name: &str) -> io::Result<Self> {
+            let mapping = unsafe {
+                OpenFileMappingW(
+                    FILE_MAP_ALL_ACCESS,
+                    0,
+                    wide(&format!("Local\\{name}")).as_ptr(),
+                )
+            };
+            let mut region = Self {
+                ptr: std::ptr::null_mut(),
+                len: 0,
+                mapping: handle(mapping)?,
+                empty: std::ptr::null_mut(),
+                full: std::ptr::null_mut(),
+            };
+
+            // The view of the length first, a longer view than the mapping fails.
+            let ptr = unsafe { map(region.mapping, LEN_LEN) }?;
+            let len = unsafe { ptr.cast::<u64>().read() };
+            unsafe { UnmapViewOfFile(ptr.cast()) };
+            let len = usize::try_from(len)
+                .ok()
+                .filter(|len| *len >= LEN_LEN)
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "shared memory region not initialized",
+                    )
+                })?;
+            region.ptr = unsafe { map(region.mapping, len) }?;
+            region.len = len;
+
+            let access = EVENT_MODIFY_STATE | SYNCHRONIZE;
+            region.empty = handle(unsafe {
+                OpenEventW(access, 0, wide(&event_name(name, "empty")).as_ptr())
+            })?;
+            region.full =
+                handle(unsafe { OpenEventW(access, 0, wide(&event_name(name, "full")).as_ptr()) })?;
+            Ok(region)
+        }
+
+        pub(super) fn data(&self) -> *mut u8 {
+            unsafe { self.ptr.add(LEN_LEN) }
+        }
+
+        pub(super) fn data_len(&self) -> usize {
+            self.len - LEN_LEN
+        }
+
+        // Waits until the slot is `full` (or empty), runs `f` while holding the slot and
+        // hands the slot to the other side if it succeeded.
+        pub(super) fn exchange<R>(
+            &self,
+            full: bool,
+            timeout: Option<Duration>,
+            f: impl FnOnce() -> Result<R, FfiBufferError>,
+        ) -> Result<R, FfiBufferError> {
+            let (wait, other) = if full {
+                (self.full, self.empty)
+            } else {
+                (self.empty, self.full)
+            };
+            // A timeout beyond `INFINITE` waits indefinitely.
+            let ms = timeout.map_or(INFINITE, |timeout| {
+                u32::try_from(timeout.as_millis()).unwrap_or(INFINITE)
+            });
+
+            match unsafe { WaitForSingleObject(wait, ms) } {
+                WAIT_OBJECT_0 => {}
+                WAIT_TIMEOUT => return Err(super::timed_out()),
+                _ => return Err(io::Error::last_os_error().into()),
+            }
+            match f() {
+                Ok(result) => {
+                    unsafe { SetEvent(other) };
+                    Ok(result)
+                }
+                Err(error) => {
+                    // The slot is kept as it was.
+                    unsafe { SetEvent(wait) };
+                    Err(error)
+                }
+            }
+        }
+    }
+
+    impl Drop for Region {
+        fn drop(&mut self) {
+            unsafe {
+                if !self.ptr.is_null() {
+                    UnmapViewOfFile(self.ptr.cast());
+                }
+                for handle in [self.empty, self.full, self.mapping] {
+                    if !handle.is_null() {
+                        CloseHandle(handle);
+                    }
+                }
+            }
+        }
+    }
+
+    fn event_name(name: &str, state: &str) -> String {
+        format!("Local\\{name}.{state}")
+    }
+
+    fn wide(name: &str) -> Vec<u16> {
+        name.encode_utf16().chain([0]).collect()
+    }
+
+    fn handle(handle: Handle) -> io::Result<Handle> {
+        if handle.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(handle)
+    }
+
+    unsafe fn map(mapping: Handle, len: usize) -> io::Result<*mut u8> {
+        let ptr = unsafe { MapViewOfFile(mapping, FILE_MAP_ALL_ACCESS, 0, 0, len) };
+        if ptr.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(ptr.cast())
+    }
+}
+
+// Shared memory is not supported.
+#[cfg(not(any(unix, windows)))]
+mod os {
+    use std::{io, time::Duration};
+
+    use crate::error::FfiBufferError;
+
+    pub(super) struct Region;
+
+    impl Region {
+        pub(super) fn create(_name: &str, _data_len: usize) -> io::Result<Self> {
+            Err(io::ErrorKind::Unsupported.into())
+        }
+
+        pub(super) fn open(_name: &str) -> io::Result<Self> {
+            Err(io::ErrorKind::Unsupported.into())
+        }
+
+        pub(super) fn data(&self) -> *mut u8 {
+            std::ptr::null_mut()
+        }
+
+        pub(super) fn data_len(&self) -> usize {
+            0
+        }
+
+        pub(super) fn exchange<R>(
+            &self,
+            _full: bool,
+            _timeout: Option<Duration>,
+            _f: impl FnOnce() -> Result<R, FfiBufferError>,
+        ) -> Result<R, FfiBufferError> {
+            Err(FfiBufferError::Io(io::ErrorKind::Unsupported.into()))
+        }
+    }
+}
@@ -0,0 +1,171 @@
+//! FFI compatible views of bytes owned by someone else.
+
+use std::str::Utf8Error;
+
+/// FFI compatible shared view of bytes owned by someone else, e.g. by the host.
+///
+/// An empty view is always represented by a null `ptr` and a `len` of 0.
+///
+/// Note: The view does not own its bytes - the owner must keep them valid while the view is used.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct FfiSliceRef {
+    pub ptr: *const u8,
+    pub len: usize,
+}
+
+impl FfiSliceRef {
+    /// The canonical empty view, a null `ptr` and a `len` of 0.
+    pub const EMPTY: Self = Self {
+        ptr: std::ptr::null(),
+        len: 0,
+    };
+
+    /// Creates a view of the given bytes.
+    pub fn from_slice(src: &[u8]) -> Self {
+        if src.is_empty() {
+            return Self::EMPTY;
+        }
+
+        Self {
+            ptr: src.as_ptr(),
+            len: src.len(),
+        }
+    }
+
+    /// Returns the viewed bytes.
+    ///
+    /// # Safety
+    ///
+    /// The viewed bytes must be valid and not modified for `'a`.
+    pub unsafe fn as_slice<'a>(&self) -> &'a [u8] {
+        if self.len == 0 {
+            return &[];
+        }
+
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
+
+    /// Returns a copy of the viewed bytes.
+    ///
+    /// # Safety
+    ///
+    /// The viewed bytes must be valid while this function is in process.
+    pub unsafe fn to_vec(&self) -> Vec<u8> {
+        unsafe { self.as_slice() }.to_vec()
+    }
+
+    /// Returns a copy of the viewed bytes as string.
+    ///
+    /// # Errors
+    ///
+    /// Returns the [`Utf8Error`] if the bytes are not valid UTF-8.
+    ///
+    /// # Safety
+    ///
+    /// The viewed bytes must be valid while this function is in process.
+    pub unsafe fn to_string_checked(&self) -> Result<String, Utf8Error> {
+        std::str::from_utf8(unsafe { self.as_slice() }).map(str::to_string)
+    }
+
+    /// Returns a copy of the viewed bytes as string, invalid UTF-8 sequences are replaced
+    /// with `U+FFFD`.
+    ///
+    /// # Safety
+    ///
+    /// The viewed bytes must be valid while this function is in process.
+    pub unsafe fn to_string_lossy(&self) -> String {
+        String::from_utf8_lossy(unsafe { self.as_slice() }).into_owned()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl Default for FfiSliceRef {
+    fn default() -> Self {
+        Self::EMPTY
+    }
+}
+
+impl From<&[u8]> for FfiSliceRef {
+    fn from(src: &[u8]) -> Self {
+        Self::from_slice(src)
+    }
+}
+
+impl From<&str> for FfiSliceRef {
+    fn from(src: &str) -> Self {
+        Self::from_slice(src.as_bytes())
+    }
+}
+
+/// FFI compatible mutable view of bytes owned by someone else, e.g. for the host to fill.
+///
+/// An empty view is always represented by a null `ptr` and a `len` of 0.
+///
+/// Note: The view does not own its bytes - the owner must keep them valid while the view is used.
+#[repr(C)]
+#[derive(Debug)]
+pub struct FfiSliceMut {
+    pub ptr: *mut u8,
+    pub len: usize,
+}
+
+impl FfiSliceMut {
+    /// The canonical empty view, a null `ptr` and a `len` of 0.
+    pub const EMPTY: Self = Self {
+        ptr: std::ptr::null_mut(),
+        len: 0,
+    };
+
+    /// Creates a view of the given bytes.
+    pub fn from_mut_slice(src: &mut [u8]) -> Self {
+        if src.is_empty() {
+            return Self::EMPTY;
+        }
+
+        Self {
+            ptr: src.as_mut_ptr(),
+            len: src.len(),
+        }
+    }
+
+    /// Returns the viewed bytes.
+    ///
+    /// # Safety
+    ///
+    /// The viewed bytes must be valid and not accessed through other references for `'a`.
+    pub unsafe fn as_mut_slice<'a>(&self) -> &'a mut [u8] {
+        if self.len == 0 {
+            return &mut [];
+        }
+
+        unsafe { std::slice::from_raw_parts_mut(self.ptr, self.len) }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl Default for FfiSliceMut {
+    fn default() -> Self {
+        Self::EMPTY
+    }
+}
+
+impl From<&mut [u8]> for FfiSliceMut {
+    fn from(src: &mut [u8]) -> Self {
+        Self::from_mut_slice(src)
+    }
+}
@@ -0,0 +1,86 @@
+//! Thread-local cache of small byte buffers (shorter than [`MAX_LEN`] bytes), so chatty
+//! string-heavy APIs skip the allocator for most of their buffers.
+//!
+//! Buffers freed by this library (e.g. [`crate::free_boxed_byte_slice_raw`] or the exported
+//! `..._free` functions) are kept per length, [`crate::new_boxed_byte_slice_buffer_raw`]
+//! reuses one of the same length first. Buffers reclaimed as `Box<[u8]>` are not cached,
+//! as their drop is rust managed.
+
+use std::{alloc::Layout, cell::RefCell, ptr::NonNull};
+
+/// Buffers shorter than this length are cached.
+pub const MAX_LEN: usize = 256;
+
+/// Maximum number of cached buffers per length and thread.
+pub const MAX_PER_LEN: usize = 16;
+
+struct Cache {
+    // Cached buffers by their length, the layout of each is `Box<[u8]>`.
+    free: [Vec<NonNull<u8>>; MAX_LEN],
+}
+
+impl Cache {
+    fn clear(&mut self) {
+        for (len, free) in self.free.iter_mut().enumerate() {
+            for ptr in free.drain(..) {
+                let layout = unsafe { Layout::from_size_align_unchecked(len, 1) };
+                unsafe { std::alloc::dealloc(ptr.as_ptr(), layout) };
+            }
+        }
+    }
+}
+
+impl Drop for Cache {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+thread_local! {
+    static CACHE: RefCell<Cache> = const {
+        RefCell::new(Cache {
+            free: [const { Vec::new() }; MAX_LEN],
+        })
+    };
+}
+
+/// Deallocates the cached buffers of the calling thread, e.g. after a burst of calls.
+pub fn clear() {
+    let _ = CACHE.try_with(|cache| {
+        if let Ok(mut cache) = cache.try_borrow_mut() {
+            cache.clear();
+        }
+    });
+}
+
+// Returns a cached buffer of `len` bytes (with stale content), `None` if there is none.
+pub(crate) fn take(len: usize) -> Option<NonNull<u8>> {
+    if len == 0 || len >= MAX_LEN {
+        return None;
+    }
+
+    CACHE
+        .try_with(|cache| cache.try_borrow_mut().ok()?.free[len].pop())
+        .ok()
+        .flatten()
+}
+
+// Caches the given buffer, dropped if it is not small or the cache is full for its length.
+pub(crate) fn recycle(bytes: Box<[u8]>) {
+    let len = bytes.len();
+    if len == 0 || len >= MAX_LEN {
+        return;
+    }
+
+    let mut bytes = Some(bytes);
+    let _ = CACHE.try_with(|cache| {
+        let Ok(mut cache) = cache.try_borrow_mut() else {
+            return;
+        };
+        let free = &mut cache.free[len];
+        if free.len() < MAX_PER_LEN {
+            let bytes = Box::leak(bytes.take().expect("not cached yet"));
+            free.push(NonNull::from(bytes).cast::<u8>());
+        }
+    });
+}
@@ -0,0 +1,150 @@
+//! Memory usage statistics of the buffers handed out by this crate.
+//!
+//! A buffer counts as live from the moment it is allocated or converted to its
+//! raw representation, until it is converted back to a rust managed value.
+//!
+//! The totals of [`BufferStats`] are only counted with the `stats` feature. The live
+//! counters of [`memory_report`] and the memory limit (see [`set_memory_limit`]) are always
+//! maintained, so the limit is enforced in every build.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static LIVE_BUFFERS: AtomicUsize = AtomicUsize::new(0);
+static LIVE_BYTES: AtomicUsize = AtomicUsize::new(0);
+static PEAK_BYTES: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATION_FAILURES: AtomicUsize = AtomicUsize::new(0);
+#[cfg(feature = "stats")]
+static TOTAL_ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+#[cfg(feature = "stats")]
+static TOTAL_FREES: AtomicUsize = AtomicUsize::new(0);
+// `usize::MAX` if there is no limit.
+static MEMORY_LIMIT: AtomicUsize = AtomicUsize::new(usize::MAX);
+
+/// FFI compatible memory usage report.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FfiMemoryReport {
+    /// Number of live buffers.
+    pub live_buffers: usize,
+    /// Sum of the lengths of all live buffers.
+    pub live_bytes: usize,
+    /// Highest value `live_bytes` had so far.
+    pub peak_bytes: usize,
+    /// Number of allocations which failed so far.
+    pub allocation_failures: usize,
+    /// Number of buffers idle in pools (see [`crate::pool`]), not included in `live_buffers`.
+    pub pooled_buffers: usize,
+    /// Sum of the sizes of all buffers idle in pools.
+    pub pooled_bytes: usize,
+}
+
+/// Returns the current memory usage report.
+pub fn memory_report() -> FfiMemoryReport {
+    let (pooled_buffers, pooled_bytes) = crate::pool::occupancy();
+    FfiMemoryReport {
+        live_buffers: LIVE_BUFFERS.load(Ordering::Relaxed),
+        live_bytes: LIVE_BYTES.load(Ordering::Relaxed),
+        peak_bytes: PEAK_BYTES.load(Ordering::Relaxed),
+        allocation_failures: ALLOCATION_FAILURES.load(Ordering::Relaxed),
+        pooled_buffers,
+        pooled_bytes,
+    }
+}
+
+/// FFI compatible snapshot of the buffer counters (`stats` feature), e.g. to detect slow
+/// leaks in long-running hosts by comparing the totals over time.
+#[cfg(feature = "stats")]
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BufferStats {
+    /// Number of live buffers.
+    pub live_buffers: usize,
+    /// Sum of the lengths of all live buffers.
+    pub live_bytes: usize,
+    /// Number of buffers which became live so far.
+    pub total_allocations: usize,
+    /// Number of buffers which were reclaimed so far.
+    pub total_frees: usize,
+    /// Highest value `live_bytes` had so far.
+    pub peak_bytes: usize,
+}
+
+#[cfg(feature = "stats")]
+impl BufferStats {
+    /// Returns the current values of the counters.
+    pub fn snapshot() -> Self {
+        Self {
+            live_buffers: LIVE_BUFFERS.load(Ordering::Relaxed),
+            live_bytes: LIVE_BYTES.load(Ordering::Relaxed),
+            total_allocations: TOTAL_ALLOCATIONS.load(Ordering::Relaxed),
+            total_frees: TOTAL_FREES.load(Ordering::Relaxed),
+            peak_bytes: PEAK_BYTES.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Sets the limit of the live bytes, `None` removes the limit (the default).
+///
+/// While set, the allocation functions of this crate fail (e.g. return null or an
+/// allocation error) instead of allocating beyond the limit, so hosts in memory constrained
+/// sandboxes get back-pressure instead of being killed.
+///
+/// Note: The limit is checked before each allocation, concurrent allocations may exceed it
+/// slightly. Buffers already live beyond a lowered limit stay valid.
+pub fn set_memory_limit(limit: Option<usize>) {
+    MEMORY_LIMIT.store(limit.unwrap_or(usize::MAX), Ordering::Relaxed);
+}
+
+/// Returns the limit of the live bytes, see [`set_memory_limit`].
+pub fn memory_limit() -> Option<usize> {
+    let limit = MEMORY_LIMIT.load(Ordering::Relaxed);
+    (limit != usize::MAX).then_some(limit)
+}
+
+/// Returns the sum of the lengths of all live buffers.
+pub fn current_usage() -> usize {
+    LIVE_BYTES.load(Ordering::Relaxed)
+}
+
+/// Returns the highest value [`current_usage`] had so far.
+pub fn peak_usage() -> usize {
+    PEAK_BYTES.load(Ordering::Relaxed)
+}
+
+// Returns true if `len` more live bytes stay within the limit.
+pub(crate) fn within_limit(len: usize) -> bool {
+    let limit = MEMORY_LIMIT.load(Ordering::Relaxed);
+    limit == usize::MAX || current_usage().saturating_add(len) <= limit
+}
+
+pub(crate) fn buffer_created(len: usize) {
+    if len == 0 {
+        return;
+    }
+
+    LIVE_BUFFERS.fetch_add(1, Ordering::Relaxed);
+    #[cfg(feature = "stats")]
+    TOTAL_ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    let live_bytes = LIVE_BYTES.fetch_add(len, Ordering::Relaxed) + len;
+    PEAK_BYTES.fetch_max(live_bytes, Ordering::Relaxed);
+}
+
+pub(crate) fn buffer_reclaimed(len: usize) {
+    if len == 0 {
+        return;
+    }
+
+    #[cfg(feature = "stats")]
+    TOTAL_FREES.fetch_add(1, Ordering::Relaxed);
+    // Saturating, so buffers not handed out by this crate can't wrap the counters.
+    let _ = LIVE_BUFFERS.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |v| {
+        Some(v.saturating_sub(1))
+    });
+    let _ = LIVE_BYTES.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |v| {
+        Some(v.saturating_sub(len))
+    });
+}
+
+pub(crate) fn allocation_failed() {
+    ALLOCATION_FAILURES.fetch_add(1, Ordering::Relaxed);
+}
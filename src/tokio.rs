@@ -0,0 +1,146 @@
+//! tokio interop, reading straight into buffers which cross the FFI boundary
+//! instead of reading into a `Vec` first and copying.
+//!
+//! The socket functions are the async variants of [`crate::net`].
+
+use std::{io::SeekFrom, net::SocketAddr, path::Path};
+
+use tokio::{
+    fs::File,
+    io::{AsyncRead, AsyncReadExt, AsyncSeekExt},
+    net::UdpSocket,
+};
+
+use crate::{
+    ByteBuffer,
+    error::FfiBufferError,
+    net::alloc_receive_buffer,
+    new_zeroed_boxed_byte_slice,
+    pool::{BufferPool, PooledBuffer},
+    truncate_boxed_byte_slice,
+    width::len_from_u64,
+};
+
+/// Reads the whole file at the given `path` into a new byte buffer.
+///
+/// The buffer is allocated once with the file size at the time it is opened.
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::Io`] if the file cannot be opened or read (e.g. if it
+/// was truncated while reading), [`FfiBufferError::CapacityOverflow`] if the file is larger
+/// than the address space, [`FfiBufferError::Alloc`] if the allocation failed.
+pub async fn read_file_into_ffi_buffer(
+    path: impl AsRef<Path>,
+) -> Result<ByteBuffer, FfiBufferError> {
+    let mut file = File::open(path).await?;
+    let len = len_from_u64(file.metadata().await?.len())?;
+
+    let mut bytes = new_zeroed_boxed_byte_slice(len)?;
+    file.read_exact(&mut bytes).await?;
+
+    Ok(ByteBuffer::from_boxed_slice(bytes))
+}
+
+/// Reads `len` bytes at the given `offset` of the file at the given `path` into a new byte buffer.
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::Io`] if the file cannot be opened or read (e.g. if it
+/// ends before `offset + len`), [`FfiBufferError::Alloc`] if the allocation failed.
+pub async fn read_file_at_into_ffi_buffer(
+    path: impl AsRef<Path>,
+    offset: u64,
+    len: usize,
+) -> Result<ByteBuffer, FfiBufferError> {
+    let mut file = File::open(path).await?;
+    file.seek(SeekFrom::Start(offset)).await?;
+
+    let mut bytes = new_zeroed_boxed_byte_slice(len)?;
+    file.read_exact(&mut bytes).await?;
+
+    Ok(ByteBuffer::from_boxed_slice(bytes))
+}
+
+/// Receives a datagram from the connected `socket` into a new byte buffer,
+/// see [`UdpSocket::recv`].
+///
+/// Bytes of a datagram longer than `max_len` are discarded.
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::InvalidArgument`] if `max_len` is 0, [`FfiBufferError::Alloc`]
+/// if the allocation failed, [`FfiBufferError::Io`] with the error of the socket.
+pub async fn recv_into_ffi_buffer(
+    socket: &UdpSocket,
+    max_len: usize,
+) -> Result<ByteBuffer, FfiBufferError> {
+    let mut bytes = alloc_receive_buffer(max_len)?;
+    let len = socket.recv(&mut bytes).await?;
+
+    let bytes = truncate_boxed_byte_slice(bytes, len);
+
+    Ok(ByteBuffer::from_boxed_slice(bytes))
+}
+
+/// Receives a datagram from the connected `socket` into a buffer acquired from the `pool`,
+/// see [`UdpSocket::recv`]. The length is set to the received bytes.
+///
+/// Bytes of a datagram longer than the buffer size of the pool are discarded.
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::Alloc`] if the allocation failed, [`FfiBufferError::Io`] with
+/// the error of the socket.
+pub async fn recv_into_pooled_buffer(
+    socket: &UdpSocket,
+    pool: &BufferPool,
+) -> Result<PooledBuffer, FfiBufferError> {
+    let mut buffer = pool.acquire(0)?;
+    let len = socket.recv(buffer.as_whole_mut_slice()).await?;
+    buffer.set_len(len)?;
+
+    Ok(buffer)
+}
+
+/// Receives a datagram from the `socket` into a new byte buffer and returns it with
+/// the address of the sender, see [`UdpSocket::recv_from`].
+///
+/// Bytes of a datagram longer than `max_len` are discarded.
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::InvalidArgument`] if `max_len` is 0, [`FfiBufferError::Alloc`]
+/// if the allocation failed, [`FfiBufferError::Io`] with the error of the socket.
+pub async fn recv_from_into_ffi_buffer(
+    socket: &UdpSocket,
+    max_len: usize,
+) -> Result<(ByteBuffer, SocketAddr), FfiBufferError> {
+    let mut bytes = alloc_receive_buffer(max_len)?;
+    let (len, addr) = socket.recv_from(&mut bytes).await?;
+
+    let bytes = truncate_boxed_byte_slice(bytes, len);
+
+    Ok((ByteBuffer::from_boxed_slice(bytes), addr))
+}
+
+/// Reads up to `max_len` bytes from the `reader` (e.g. a `TcpStream`) into a new byte buffer,
+/// see [`AsyncReadExt::read`].
+///
+/// An empty buffer is returned at the end of the stream.
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::InvalidArgument`] if `max_len` is 0, [`FfiBufferError::Alloc`]
+/// if the allocation failed, [`FfiBufferError::Io`] with the error of the reader.
+pub async fn read_into_ffi_buffer(
+    reader: &mut (impl AsyncRead + Unpin),
+    max_len: usize,
+) -> Result<ByteBuffer, FfiBufferError> {
+    let mut bytes = alloc_receive_buffer(max_len)?;
+    let len = reader.read(&mut bytes).await?;
+
+    let bytes = truncate_boxed_byte_slice(bytes, len);
+
+    Ok(ByteBuffer::from_boxed_slice(bytes))
+}
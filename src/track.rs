@@ -0,0 +1,106 @@
+//! Debug tracking of the raw buffers handed out to the host, to find leaks and double frees.
+//!
+//! Every pointer produced by [`crate::into_boxed_byte_slice_raw`] or
+//! [`crate::new_boxed_byte_slice_buffer_raw`] (and the functions built on them) is recorded
+//! with the location of its caller, and removed again when it is converted back with one of
+//! the `from_...` functions. Converting back a pointer which was never issued (or was
+//! converted back already) is logged as error and panics.
+
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    panic::Location,
+    sync::{LazyLock, Mutex},
+};
+
+use crate::logging::{self, FfiLogLevel};
+
+/// Raw buffer which was issued to the host and not converted back yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrackedBuffer {
+    pub ptr: usize,
+    pub len: usize,
+    /// Location of the call which issued the buffer.
+    pub location: &'static Location<'static>,
+}
+
+static ISSUED: LazyLock<Mutex<HashMap<usize, TrackedBuffer>>> = LazyLock::new(Default::default);
+
+/// Returns the buffers which were issued and not converted back yet, ordered by pointer.
+pub fn outstanding_buffers() -> Vec<TrackedBuffer> {
+    let mut outstanding: Vec<_> = ISSUED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .values()
+        .copied()
+        .collect();
+    outstanding.sort_unstable_by_key(|buffer| buffer.ptr);
+    outstanding
+}
+
+/// Asserts that all issued buffers are converted back, e.g. at the end of a test.
+///
+/// # Panics
+///
+/// This function will panic with the outstanding buffers (and where they were issued)
+/// if there are any.
+#[track_caller]
+pub fn assert_no_leaks() {
+    let outstanding = outstanding_buffers();
+    if outstanding.is_empty() {
+        return;
+    }
+
+    let mut message = format!("{} buffers leaked:", outstanding.len());
+    for buffer in &outstanding {
+        let _ = write!(
+            message,
+            "\n  {:#x} ({} bytes) issued at {}",
+            buffer.ptr, buffer.len, buffer.location
+        );
+    }
+    panic!("{message}");
+}
+
+// Initializes the registry of the issued buffers, see `crate::lifecycle::init`.
+pub(crate) fn init() {
+    LazyLock::force(&ISSUED);
+}
+
+pub(crate) fn issue(ptr: *const u8, len: usize, location: &'static Location<'static>) {
+    let buffer = TrackedBuffer {
+        ptr: ptr as usize,
+        len,
+        location,
+    };
+    ISSUED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(buffer.ptr, buffer);
+}
+
+#[track_caller]
+pub(crate) fn reclaim(ptr: *const u8, len: usize) {
+    // Removed before panicking, so the lock is not poisoned.
+    let issued = ISSUED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&(ptr as usize));
+
+    let message = match issued {
+        Some(buffer) if buffer.len == len => return,
+        Some(buffer) => format!(
+            "buffer {ptr:p} converted back with {len} bytes at {}, but issued with {} bytes at {}",
+            Location::caller(),
+            buffer.len,
+            buffer.location
+        ),
+        None => format!(
+            "buffer {ptr:p} ({len} bytes) converted back at {}, but never issued or converted back already",
+            Location::caller()
+        ),
+    };
+
+    logging::log(FfiLogLevel::Error, &message);
+    panic!("{message}");
+}
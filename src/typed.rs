@@ -0,0 +1,178 @@
+//! Typed boxed slices `Box<[T]>` (e.g. `Box<[u16]>`, `Box<[f32]>`) across FFI,
+//! the element counterpart of the byte slice functions.
+
+use std::{alloc::Layout, mem::ManuallyDrop};
+
+use crate::{
+    audit::{self, FfiAuditEvent},
+    checked,
+    error::FfiBufferError,
+    oom, stats,
+};
+
+/// Marker for plain old data element types, which can cross the FFI boundary as is.
+///
+/// # Safety
+///
+/// The type must not be zero sized, must not contain padding, pointers or references
+/// and every bit pattern (including all zeros) must be a valid value, e.g. a `#[repr(C)]` struct
+/// of integers without padding.
+///
+/// The functions of this module reject zero sized element types (e.g. `[u8; 0]`, which is
+/// `FfiPod` as an array) at compile time:
+///
+/// ```compile_fail
+/// let ptr = ffi_byte_buffer::new_boxed_slice_raw::<[u8; 0]>(4);
+/// ```
+pub unsafe trait FfiPod: Copy + 'static {}
+
+macro_rules! impl_ffi_pod {
+    ($($ty:ty),*) => {
+        $(unsafe impl FfiPod for $ty {})*
+    };
+}
+
+impl_ffi_pod!(
+    u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64
+);
+
+unsafe impl<T: FfiPod, const N: usize> FfiPod for [T; N] {}
+
+/// Allocates a new zeroed boxed slice (layout `Box<[T]>`) with `len` elements
+/// and returns the pointer to the slice.
+///
+/// Returns null if `len` is 0, the size overflows or the allocation failed.
+///
+/// # Safety
+///
+/// Later at some point the slice must be converted back with [`from_boxed_slice_raw`]
+/// and the same `len`.
+pub fn new_boxed_slice_raw<T: FfiPod>(len: usize) -> *mut T {
+    assert_not_zero_sized::<T>();
+    if len == 0 {
+        return std::ptr::null_mut();
+    }
+
+    let Ok(layout) = Layout::array::<T>(len) else {
+        stats::allocation_failed();
+        return std::ptr::null_mut();
+    };
+
+    let ptr = oom::allocate(layout, true);
+    if !ptr.is_null() {
+        stats::buffer_created(layout.size());
+        audit::record(
+            FfiAuditEvent::Allocate,
+            ptr,
+            layout.size(),
+            "new_boxed_slice_raw",
+        );
+    }
+
+    ptr.cast()
+}
+
+/// Converts the given boxed slice into its raw parts `(ptr, len)`, `len` is the element count.
+///
+/// The elements will not be dropped until the slice is converted back with
+/// [`from_boxed_slice_raw`].
+pub fn into_boxed_slice_raw<T: FfiPod>(src: Box<[T]>) -> (*const T, usize) {
+    assert_not_zero_sized::<T>();
+    if src.is_empty() {
+        return (std::ptr::null(), 0);
+    }
+
+    let src = ManuallyDrop::new(src);
+    let size = size_of_val::<[T]>(&src);
+    stats::buffer_created(size);
+    audit::record(
+        FfiAuditEvent::Export,
+        src.as_ptr().cast(),
+        size,
+        "into_boxed_slice_raw",
+    );
+    checked::tag(src.as_ptr().cast(), size);
+
+    (src.as_ptr(), src.len())
+}
+
+/// Converts the given raw parts back to a rust managed boxed slice.
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::CapacityOverflow`] if the size of `len` elements overflows
+/// (the slice can't have been created by this library then), [`FfiBufferError::Corrupted`]
+/// if the integrity checks detected a corruption (the slice is freed then, see [`crate::checked`]).
+///
+/// # Safety
+///
+/// The slice must have been created with [`into_boxed_slice_raw`] or [`new_boxed_slice_raw`]
+/// with the same element type `T` and `len`, and must not be used afterwards.
+pub unsafe fn from_boxed_slice_raw<T: FfiPod>(
+    ptr: *mut T,
+    len: usize,
+) -> Result<Box<[T]>, FfiBufferError> {
+    assert_not_zero_sized::<T>();
+    if len == 0 {
+        return Ok(Box::default());
+    }
+
+    let size = len
+        .checked_mul(size_of::<T>())
+        .ok_or(FfiBufferError::CapacityOverflow)?;
+    let verified = checked::verify(ptr.cast(), size, "from_boxed_slice_raw");
+    stats::buffer_reclaimed(size);
+    audit::record(
+        FfiAuditEvent::Import,
+        ptr.cast(),
+        size,
+        "from_boxed_slice_raw",
+    );
+
+    let slice_raw = std::ptr::slice_from_raw_parts_mut(ptr, len);
+    let slice = unsafe { Box::from_raw(slice_raw) };
+
+    verified.map(|()| slice)
+}
+
+// Fails the build for a zero sized `T` (instantiated with it), which would reach the
+// allocator with a zero sized layout.
+#[inline(always)]
+const fn assert_not_zero_sized<T>() {
+    const {
+        assert!(
+            size_of::<T>() != 0,
+            "FfiPod element types must not be zero sized"
+        )
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn array_elements_round_trip() {
+        let ptr = new_boxed_slice_raw::<[u8; 3]>(2);
+        assert!(!ptr.is_null());
+        let mut slice = unsafe { from_boxed_slice_raw(ptr, 2) }.unwrap();
+        assert_eq!(&*slice, &[[0; 3]; 2]);
+
+        slice[1] = [1, 2, 3];
+        let (ptr, len) = into_boxed_slice_raw(slice);
+        let slice = unsafe { from_boxed_slice_raw(ptr.cast_mut(), len) }.unwrap();
+        assert_eq!(&*slice, &[[0, 0, 0], [1, 2, 3]]);
+    }
+
+    #[test]
+    fn empty_slice_is_null() {
+        assert!(new_boxed_slice_raw::<[u16; 2]>(0).is_null());
+        let (ptr, len) = into_boxed_slice_raw::<u32>(Box::default());
+        assert!(ptr.is_null() && len == 0);
+        assert!(
+            unsafe { from_boxed_slice_raw::<u32>(std::ptr::null_mut(), 0) }
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,189 @@
+//! Unity/IL2CPP friendly `extern "C"` functions.
+//!
+//! IL2CPP's marshaler can't handle structs returned by value, so every function
+//! returns an [`FfiStatus`] and passes results through out-params. Buffers are
+//! owned through opaque [`UnityBuffer`] handles (`IntPtr` on the C# side),
+//! which must be released with [`ffi_unity_buffer_free`].
+//!
+//! Strings are exchanged as UTF-16 (C# `string`/`char[]`), the buffers hold them as UTF-8.
+
+use crate::{
+    error::{Error, FfiBufferError, FfiStatus},
+    slice_ref, stats,
+    unwind::ffi_fn,
+};
+
+/// Opaque handle of a rust owned buffer.
+pub struct UnityBuffer {
+    bytes: Box<[u8]>,
+}
+
+ffi_fn! {
+    /// Allocates a new zeroed buffer with the given `len` and writes its handle to `out_handle`.
+    ///
+    /// # Safety
+    ///
+    /// The given `out_handle` must be null or valid for writes.
+    pub unsafe fn ffi_unity_buffer_new(
+        len: usize,
+        out_handle: *mut *mut UnityBuffer,
+    ) -> FfiStatus {
+        unsafe { write_handle(out_handle, vec![0; len].into_boxed_slice()) }
+    }
+}
+
+ffi_fn! {
+    /// Allocates a new buffer with a copy of the given bytes and writes its handle to `out_handle`.
+    ///
+    /// # Safety
+    ///
+    /// The given bytes must be valid while this function is in process,
+    /// a null pointer is only valid with a length of 0.
+    /// The given `out_handle` must be null or valid for writes.
+    pub unsafe fn ffi_unity_buffer_from_bytes(
+        ptr: *const u8,
+        len: usize,
+        out_handle: *mut *mut UnityBuffer,
+    ) -> FfiStatus {
+        let Some(bytes) = (unsafe { slice_ref(ptr, len) }) else {
+            return FfiBufferError::InvalidArgument("null `ptr` with a non-zero length").report();
+        };
+
+        unsafe { write_handle(out_handle, Box::from(bytes)) }
+    }
+}
+
+ffi_fn! {
+    /// Allocates a new buffer with the UTF-8 encoding of the given UTF-16 string
+    /// and writes its handle to `out_handle`.
+    ///
+    /// Returns [`FfiStatus::InvalidEncoding`] if the string is not valid UTF-16.
+    ///
+    /// # Safety
+    ///
+    /// The given UTF-16 units (`len` is the number of units, not bytes) must be valid
+    /// while this function is in process, a null pointer is only valid with a length of 0.
+    /// The given `out_handle` must be null or valid for writes.
+    pub unsafe fn ffi_unity_buffer_from_utf16(
+        ptr: *const u16,
+        len: usize,
+        out_handle: *mut *mut UnityBuffer,
+    ) -> FfiStatus {
+        let Some(units) = (unsafe { slice_ref(ptr, len) }) else {
+            return FfiBufferError::InvalidArgument("null `ptr` with a non-zero length").report();
+        };
+
+        let Ok(string) = String::from_utf16(units) else {
+            return Error::new(FfiStatus::InvalidEncoding, "invalid UTF-16").report();
+        };
+
+        unsafe { write_handle(out_handle, string.into_bytes().into_boxed_slice()) }
+    }
+}
+
+ffi_fn! {
+    /// Writes the pointer to and the length of the bytes of the given buffer to
+    /// `out_ptr` and `out_len`.
+    ///
+    /// The bytes are valid (and can be written to) until the buffer is released.
+    ///
+    /// # Safety
+    ///
+    /// The given `handle` must be null or valid (not released).
+    /// The given `out_ptr` and `out_len` must be null or valid for writes.
+    pub unsafe fn ffi_unity_buffer_data(
+        handle: *mut UnityBuffer,
+        out_ptr: *mut *mut u8,
+        out_len: *mut usize,
+    ) -> FfiStatus {
+        let Some(buffer) = (unsafe { handle.as_mut() }) else {
+            return FfiBufferError::InvalidArgument("null `handle`").report();
+        };
+
+        if out_ptr.is_null() || out_len.is_null() {
+            return FfiBufferError::InvalidArgument("null `out_ptr` or `out_len`").report();
+        }
+
+        unsafe {
+            out_ptr.write(buffer.bytes.as_mut_ptr());
+            out_len.write(buffer.bytes.len());
+        }
+
+        FfiStatus::Ok
+    }
+}
+
+ffi_fn! {
+    /// Copies the UTF-16 encoding of the string of the given buffer into `dst`
+    /// (two-call pattern) and writes the number of required UTF-16 units to `out_required`.
+    ///
+    /// Call with a null `dst` (or a too small `dst_len`) to query the required size,
+    /// [`FfiStatus::BufferTooSmall`] is returned then and nothing is copied.
+    /// Returns [`FfiStatus::InvalidEncoding`] if the buffer is not valid UTF-8.
+    ///
+    /// # Safety
+    ///
+    /// The given `handle` must be null or valid (not released).
+    /// The given `dst` must be null or valid for writes of `dst_len` UTF-16 units.
+    /// The given `out_required` must be null or valid for writes.
+    pub unsafe fn ffi_unity_buffer_to_utf16(
+        handle: *const UnityBuffer,
+        dst: *mut u16,
+        dst_len: usize,
+        out_required: *mut usize,
+    ) -> FfiStatus {
+        let Some(buffer) = (unsafe { handle.as_ref() }) else {
+            return FfiBufferError::InvalidArgument("null `handle`").report();
+        };
+
+        if out_required.is_null() {
+            return FfiBufferError::InvalidArgument("null `out_required`").report();
+        }
+
+        let Ok(string) = std::str::from_utf8(&buffer.bytes) else {
+            return Error::new(FfiStatus::InvalidEncoding, "invalid UTF-8").report();
+        };
+
+        let required = string.encode_utf16().count();
+        unsafe { out_required.write(required) };
+
+        if dst.is_null() || dst_len < required {
+            return Error::new(FfiStatus::BufferTooSmall, format!("{required} units required"))
+                .report();
+        }
+
+        for (i, unit) in string.encode_utf16().enumerate() {
+            unsafe { dst.add(i).write(unit) };
+        }
+
+        FfiStatus::Ok
+    }
+}
+
+ffi_fn! {
+    /// Releases the given buffer, a null handle is ignored.
+    ///
+    /// # Safety
+    ///
+    /// The given `handle` must be null or valid (not released) and must not be used afterwards.
+    pub unsafe fn ffi_unity_buffer_free(handle: *mut UnityBuffer) {
+        if handle.is_null() {
+            return;
+        }
+
+        let buffer = unsafe { Box::from_raw(handle) };
+        stats::buffer_reclaimed(buffer.bytes.len());
+    }
+}
+
+unsafe fn write_handle(out_handle: *mut *mut UnityBuffer, bytes: Box<[u8]>) -> FfiStatus {
+    if out_handle.is_null() {
+        return FfiBufferError::InvalidArgument("null `out_handle`").report();
+    }
+
+    stats::buffer_created(bytes.len());
+    let handle = Box::into_raw(Box::new(UnityBuffer { bytes }));
+    unsafe { out_handle.write(handle) };
+
+    FfiStatus::Ok
+}
@@ -0,0 +1,236 @@
+//! Unreal Engine interop, `extern "C"` functions shaped for adoption into
+//! `TArray<uint8>`/`FString` (see `include/ffi_byte_buffer_unreal.h`).
+//!
+//! With a registered allocator (`FMemory::Malloc`/`FMemory::Free`) every buffer
+//! is allocated through the engine, so its memory tracking stays accurate.
+//! Without one the global rust allocator is used.
+
+use std::{ffi::c_void, sync::RwLock};
+
+use crate::{
+    error::{FfiBufferError, FfiStatus},
+    slice_ref, stats,
+    unwind::{PanicFallback, ffi_fn},
+};
+
+/// Host allocation function, e.g. `FMemory::Malloc(Count, Alignment)`.
+pub type FfiUnrealMalloc = unsafe extern "C" fn(count: usize, alignment: u32) -> *mut c_void;
+
+/// Host deallocation function, e.g. `FMemory::Free(Original)`.
+pub type FfiUnrealFree = unsafe extern "C" fn(original: *mut c_void);
+
+/// FFI compatible buffer, `num` matches the `int32` element count of `TArray`.
+///
+/// An empty buffer is always represented by a null `data` and a `num` of 0.
+///
+/// Note: The buffer must be released with [`ffi_unreal_buffer_free`].
+#[repr(C)]
+#[derive(Debug)]
+pub struct FfiUnrealBuffer {
+    pub data: *mut u8,
+    pub num: i32,
+    /// The host deallocation function the buffer was allocated for,
+    /// null if allocated with the global rust allocator.
+    pub free: Option<FfiUnrealFree>,
+}
+
+impl Default for FfiUnrealBuffer {
+    fn default() -> Self {
+        Self::EMPTY
+    }
+}
+
+impl PanicFallback for FfiUnrealBuffer {
+    fn panic_fallback() -> Self {
+        Self::EMPTY
+    }
+}
+
+impl FfiUnrealBuffer {
+    /// The canonical empty buffer, a null `data` and a `num` of 0.
+    pub const EMPTY: Self = Self {
+        data: std::ptr::null_mut(),
+        num: 0,
+        free: None,
+    };
+
+    /// Returns the bytes of the buffer.
+    ///
+    /// # Safety
+    ///
+    /// The buffer must be valid (not released) while the returned reference is used.
+    pub unsafe fn as_slice(&self) -> &[u8] {
+        if self.num <= 0 {
+            return &[];
+        }
+
+        unsafe { std::slice::from_raw_parts(self.data, self.num as usize) }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Allocator {
+    malloc: FfiUnrealMalloc,
+    free: FfiUnrealFree,
+}
+
+static ALLOCATOR: RwLock<Option<Allocator>> = RwLock::new(None);
+
+/// Returns a new buffer with a copy of the given bytes, allocated with the
+/// registered host allocator (if any).
+///
+/// Returns `None` if `bytes` is longer than `i32::MAX` or the allocation failed.
+pub fn unreal_buffer_from_slice(bytes: &[u8]) -> Option<FfiUnrealBuffer> {
+    let num = i32::try_from(bytes.len()).ok()?;
+    if num == 0 {
+        return Some(FfiUnrealBuffer::EMPTY);
+    }
+
+    let allocator = *ALLOCATOR.read().unwrap_or_else(|e| e.into_inner());
+
+    let buffer = match allocator {
+        Some(allocator) => {
+            // Alignment 0 selects the default alignment of `FMemory::Malloc`.
+            let data = unsafe { (allocator.malloc)(bytes.len(), 0) }.cast::<u8>();
+            if data.is_null() {
+                stats::allocation_failed();
+                return None;
+            }
+
+            unsafe { std::ptr::copy_nonoverlapping(bytes.as_ptr(), data, bytes.len()) };
+
+            FfiUnrealBuffer {
+                data,
+                num,
+                free: Some(allocator.free),
+            }
+        }
+        None => FfiUnrealBuffer {
+            data: Box::into_raw(Box::<[u8]>::from(bytes)).cast::<u8>(),
+            num,
+            free: None,
+        },
+    };
+
+    stats::buffer_created(bytes.len());
+    Some(buffer)
+}
+
+ffi_fn! {
+    /// Registers the host allocator used for all buffers allocated afterwards,
+    /// null for both functions restores the global rust allocator.
+    ///
+    /// Already allocated buffers keep their deallocation function.
+    ///
+    /// Returns [`FfiStatus::InvalidArgument`] if only one of the functions is null.
+    ///
+    /// # Safety
+    ///
+    /// The given functions must be callable from any thread until the process exits.
+    pub unsafe fn ffi_unreal_set_allocator(
+        malloc: Option<FfiUnrealMalloc>,
+        free: Option<FfiUnrealFree>,
+    ) -> FfiStatus {
+        let allocator = match (malloc, free) {
+            (Some(malloc), Some(free)) => Some(Allocator { malloc, free }),
+            (None, None) => None,
+            _ => return FfiBufferError::InvalidArgument("only one null function").report(),
+        };
+
+        *ALLOCATOR.write().unwrap_or_else(|e| e.into_inner()) = allocator;
+
+        FfiStatus::Ok
+    }
+}
+
+ffi_fn! {
+    /// Writes a new buffer with a copy of the given bytes to `out`.
+    ///
+    /// # Safety
+    ///
+    /// The given bytes must be valid while this function is in process,
+    /// a null pointer is only valid with a `num` of 0.
+    /// The given `out` must be null or valid for writes.
+    pub unsafe fn ffi_unreal_buffer_from_bytes(
+        data: *const u8,
+        num: i32,
+        out: *mut FfiUnrealBuffer,
+    ) -> FfiStatus {
+        let Ok(len) = usize::try_from(num) else {
+            return FfiBufferError::InvalidArgument("negative `num`").report();
+        };
+
+        match unsafe { slice_ref(data, len) } {
+            Some(bytes) => unsafe { write_buffer(out, bytes) },
+            None => FfiBufferError::InvalidArgument("null `data` with a non-zero `num`").report(),
+        }
+    }
+}
+
+ffi_fn! {
+    /// Writes a new buffer with the UTF-8 encoding of the given `TCHAR` (UTF-16) string
+    /// to `out`, e.g. from `*FString` and `FString::Len()`.
+    ///
+    /// Invalid UTF-16 sequences are replaced with `U+FFFD`.
+    ///
+    /// # Safety
+    ///
+    /// The given characters must be valid while this function is in process,
+    /// a null pointer is only valid with a `len` of 0.
+    /// The given `out` must be null or valid for writes.
+    pub unsafe fn ffi_unreal_buffer_from_tchar(
+        chars: *const u16,
+        len: i32,
+        out: *mut FfiUnrealBuffer,
+    ) -> FfiStatus {
+        let Ok(len) = usize::try_from(len) else {
+            return FfiBufferError::InvalidArgument("negative `len`").report();
+        };
+
+        let Some(chars) = (unsafe { slice_ref(chars, len) }) else {
+            return FfiBufferError::InvalidArgument("null `chars` with a non-zero length").report();
+        };
+
+        let string = String::from_utf16_lossy(chars);
+        unsafe { write_buffer(out, string.as_bytes()) }
+    }
+}
+
+ffi_fn! {
+    /// Releases the given buffer with the deallocation function it was allocated for.
+    ///
+    /// # Safety
+    ///
+    /// The buffer must have been created by one of the `ffi_unreal_...` functions
+    /// and must not be used afterwards.
+    pub unsafe fn ffi_unreal_buffer_free(buffer: FfiUnrealBuffer) {
+        if buffer.num <= 0 || buffer.data.is_null() {
+            return;
+        }
+
+        let len = buffer.num as usize;
+        stats::buffer_reclaimed(len);
+
+        match buffer.free {
+            Some(free) => unsafe { free(buffer.data.cast()) },
+            None => {
+                let slice_raw = std::ptr::slice_from_raw_parts_mut(buffer.data, len);
+                drop(unsafe { Box::from_raw(slice_raw) });
+            }
+        }
+    }
+}
+
+unsafe fn write_buffer(out: *mut FfiUnrealBuffer, bytes: &[u8]) -> FfiStatus {
+    if out.is_null() {
+        return FfiBufferError::InvalidArgument("null `out`").report();
+    }
+
+    let Some(buffer) = unreal_buffer_from_slice(bytes) else {
+        return FfiBufferError::InvalidArgument("the allocation failed").report();
+    };
+
+    unsafe { out.write(buffer) };
+
+    FfiStatus::Ok
+}
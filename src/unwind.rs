@@ -0,0 +1,245 @@
+//! Unwind policy of the exported functions, deciding what happens if one of them panics.
+//!
+//! The exported functions use the `extern "C"` ABI, with the `c-unwind` feature the
+//! `extern "C-unwind"` ABI, so a panic can unwind into hosts built with
+//! unwinding-compatible toolchains (see [`FfiUnwindPolicy::Propagate`]).
+
+use std::{
+    any::Any,
+    panic::{self, AssertUnwindSafe},
+    sync::atomic::{AtomicI32, Ordering},
+};
+
+use crate::{
+    ByteBuffer, OwnedVecBuffer,
+    error::{Error, FfiStatus, set_last_error},
+    string_into_byte_buffer,
+};
+
+/// FFI compatible policy what happens if an exported function panics.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum FfiUnwindPolicy {
+    /// The process is aborted.
+    Abort = 0,
+    /// The panic is caught and stored as last error (see [`crate::error`]), the function
+    /// returns [`FfiStatus::Panic`] (also as raw `i32` status code, otherwise an empty/zero value
+    /// if it does not return a status).
+    #[default]
+    ConvertToStatus = 1,
+    /// The panic unwinds into the host - only with the `c-unwind` feature,
+    /// otherwise the process is aborted when the panic reaches the `extern "C"` boundary.
+    Propagate = 2,
+}
+
+static POLICY: AtomicI32 = AtomicI32::new(FfiUnwindPolicy::ConvertToStatus as i32);
+
+/// Sets the global unwind policy, the default is [`FfiUnwindPolicy::ConvertToStatus`].
+pub fn set_unwind_policy(policy: FfiUnwindPolicy) {
+    POLICY.store(policy as i32, Ordering::Relaxed);
+}
+
+/// Returns the global unwind policy.
+pub fn unwind_policy() -> FfiUnwindPolicy {
+    match POLICY.load(Ordering::Relaxed) {
+        0 => FfiUnwindPolicy::Abort,
+        2 => FfiUnwindPolicy::Propagate,
+        _ => FfiUnwindPolicy::ConvertToStatus,
+    }
+}
+
+/// Value returned by an exported function if it panicked (see [`FfiUnwindPolicy::ConvertToStatus`]).
+pub trait PanicFallback {
+    fn panic_fallback() -> Self;
+}
+
+impl PanicFallback for () {
+    fn panic_fallback() -> Self {}
+}
+
+impl PanicFallback for bool {
+    fn panic_fallback() -> Self {
+        false
+    }
+}
+
+// `i32` results are raw status codes (e.g. `ffi_last_error_code`), a panic must not read as Ok.
+impl PanicFallback for i32 {
+    fn panic_fallback() -> Self {
+        FfiStatus::Panic as i32
+    }
+}
+
+impl PanicFallback for u32 {
+    fn panic_fallback() -> Self {
+        0
+    }
+}
+
+impl PanicFallback for u64 {
+    fn panic_fallback() -> Self {
+        0
+    }
+}
+
+impl PanicFallback for usize {
+    fn panic_fallback() -> Self {
+        0
+    }
+}
+
+impl PanicFallback for FfiStatus {
+    fn panic_fallback() -> Self {
+        FfiStatus::Panic
+    }
+}
+
+impl PanicFallback for ByteBuffer {
+    fn panic_fallback() -> Self {
+        ByteBuffer::EMPTY
+    }
+}
+
+impl PanicFallback for OwnedVecBuffer {
+    fn panic_fallback() -> Self {
+        OwnedVecBuffer::EMPTY
+    }
+}
+
+/// Runs the body of an exported function, handling a panic with the global unwind policy.
+///
+/// Binding crates can use it for their own exported functions.
+pub fn guard<R: PanicFallback>(body: impl FnOnce() -> R) -> R {
+    let payload = match panic::catch_unwind(AssertUnwindSafe(body)) {
+        Ok(result) => return result,
+        Err(payload) => payload,
+    };
+
+    match unwind_policy() {
+        FfiUnwindPolicy::Abort => std::process::abort(),
+        FfiUnwindPolicy::Propagate => panic::resume_unwind(payload),
+        FfiUnwindPolicy::ConvertToStatus => {
+            set_last_error(Error::new(FfiStatus::Panic, panic_message(&*payload)));
+
+            R::panic_fallback()
+        }
+    }
+}
+
+/// Runs the body of an exported function returning a status, a panic is converted into
+/// [`FfiStatus::Panic`] and its message is written to `message_out` as UTF-8 string buffer
+/// (and stored as last error).
+///
+/// Unlike [`guard`] the panic never unwinds into the host, [`FfiUnwindPolicy::Propagate`]
+/// is handled like [`FfiUnwindPolicy::ConvertToStatus`] - only [`FfiUnwindPolicy::Abort`]
+/// is honored. Binding crates can use it for their own exported functions.
+///
+/// Note: The message buffer must be released by the host with `ffi_string_free`
+/// (`export` feature) or converted back with [`crate::string_from_byte_buffer`].
+///
+/// # Safety
+///
+/// The given `message_out` must be null (the message is only stored as last error then)
+/// or valid for writes.
+pub unsafe fn ffi_guard(
+    message_out: *mut ByteBuffer,
+    body: impl FnOnce() -> FfiStatus,
+) -> FfiStatus {
+    let payload = match panic::catch_unwind(AssertUnwindSafe(body)) {
+        Ok(status) => return status,
+        Err(payload) => payload,
+    };
+
+    if unwind_policy() == FfiUnwindPolicy::Abort {
+        std::process::abort();
+    }
+
+    let message = panic_message(&*payload);
+    set_last_error(Error::new(FfiStatus::Panic, message));
+    if !message_out.is_null() {
+        unsafe { message_out.write(string_into_byte_buffer(message.to_string())) };
+    }
+
+    FfiStatus::Panic
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic payload")
+}
+
+/// Defines an exported function with a stable symbol name, using the `extern "C"` ABI
+/// (`extern "C-unwind"` with the `c-unwind` feature) and guarding its body (see [`guard`]).
+///
+/// The last error of the calling thread is cleared on entry, unless the function is
+/// prefixed with `@keep_last_error`.
+#[cfg(any(
+    feature = "export",
+    feature = "julia",
+    feature = "libuv",
+    feature = "unity",
+    feature = "unreal"
+))]
+macro_rules! ffi_fn {
+    // The last error of the calling thread is kept, e.g. by the functions reading it.
+    (@keep_last_error $($item:tt)*) => {
+        $crate::unwind::ffi_fn!(@define {} $($item)*);
+    };
+    (
+        @define {$($enter:tt)*}
+        $(#[$meta:meta])*
+        pub unsafe fn $name:ident($($arg:ident: $ty:ty),* $(,)?) $(-> $ret:ty)? $body:block
+    ) => {
+        $(#[$meta])*
+        #[cfg(not(feature = "c-unwind"))]
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn $name($($arg: $ty),*) $(-> $ret)? {
+            $($enter)*
+            $crate::unwind::guard(|| $body)
+        }
+
+        $(#[$meta])*
+        #[cfg(feature = "c-unwind")]
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C-unwind" fn $name($($arg: $ty),*) $(-> $ret)? {
+            $($enter)*
+            $crate::unwind::guard(|| $body)
+        }
+    };
+    (
+        @define {$($enter:tt)*}
+        $(#[$meta:meta])*
+        pub fn $name:ident($($arg:ident: $ty:ty),* $(,)?) $(-> $ret:ty)? $body:block
+    ) => {
+        $(#[$meta])*
+        #[cfg(not(feature = "c-unwind"))]
+        #[unsafe(no_mangle)]
+        pub extern "C" fn $name($($arg: $ty),*) $(-> $ret)? {
+            $($enter)*
+            $crate::unwind::guard(|| $body)
+        }
+
+        $(#[$meta])*
+        #[cfg(feature = "c-unwind")]
+        #[unsafe(no_mangle)]
+        pub extern "C-unwind" fn $name($($arg: $ty),*) $(-> $ret)? {
+            $($enter)*
+            $crate::unwind::guard(|| $body)
+        }
+    };
+    ($($item:tt)*) => {
+        $crate::unwind::ffi_fn!(@define { $crate::error::clear_last_error(); } $($item)*);
+    };
+}
+
+#[cfg(any(
+    feature = "export",
+    feature = "julia",
+    feature = "libuv",
+    feature = "unity",
+    feature = "unreal"
+))]
+pub(crate) use ffi_fn;
@@ -0,0 +1,98 @@
+//! Export of buffers for GPU uploads (e.g. `glBufferSubData`, `vkCmdUpdateBuffer`),
+//! aligned as requested by the host and pinned until the host releases them.
+
+use std::{
+    alloc::Layout,
+    collections::HashMap,
+    sync::{
+        LazyLock, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use crate::{
+    audit::{self, FfiAuditEvent},
+    oom, stats,
+};
+
+/// FFI compatible buffer pinned for an upload, valid until released with [`release_after_upload`].
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct FfiUploadBuffer {
+    /// Handle to release the buffer with, never 0.
+    pub handle: u64,
+    /// Pointer to the bytes, aligned as requested - null if `len` is 0.
+    pub ptr: *const u8,
+    pub len: usize,
+}
+
+struct Pinned {
+    ptr: *mut u8,
+    layout: Layout,
+}
+
+// The allocation is owned by the registry, the host only reads from it.
+unsafe impl Send for Pinned {}
+
+impl Drop for Pinned {
+    fn drop(&mut self) {
+        if self.layout.size() != 0 {
+            stats::buffer_reclaimed(self.layout.size());
+            audit::record(
+                FfiAuditEvent::Free,
+                self.ptr,
+                self.layout.size(),
+                "release_after_upload",
+            );
+            unsafe { std::alloc::dealloc(self.ptr, self.layout) };
+        }
+    }
+}
+
+static NEXT_HANDLE: AtomicU64 = AtomicU64::new(1);
+static PINNED: LazyLock<Mutex<HashMap<u64, Pinned>>> = LazyLock::new(Default::default);
+
+/// Copies the given bytes into a new allocation with the given `alignment`,
+/// which stays pinned (not moved or freed) until [`release_after_upload`] is called.
+///
+/// Returns `None` if `alignment` is not a power of two or the allocation failed.
+pub fn pin_for_upload(src: &[u8], alignment: usize) -> Option<FfiUploadBuffer> {
+    let layout = Layout::from_size_align(src.len(), alignment).ok()?;
+
+    let ptr = if src.is_empty() {
+        std::ptr::null_mut()
+    } else {
+        let ptr = oom::allocate(layout, false);
+        if ptr.is_null() {
+            return None;
+        }
+
+        unsafe { ptr.copy_from_nonoverlapping(src.as_ptr(), src.len()) };
+        stats::buffer_created(src.len());
+        audit::record(FfiAuditEvent::Export, ptr, src.len(), "pin_for_upload");
+        ptr
+    };
+
+    let handle = NEXT_HANDLE.fetch_add(1, Ordering::Relaxed);
+    PINNED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(handle, Pinned { ptr, layout });
+
+    Some(FfiUploadBuffer {
+        handle,
+        ptr,
+        len: src.len(),
+    })
+}
+
+/// Releases the buffer with the given handle, once the host finished the upload.
+///
+/// Returns false if the handle is unknown (e.g. already released).
+pub fn release_after_upload(handle: u64) -> bool {
+    PINNED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&handle)
+        .is_some()
+}
@@ -0,0 +1,182 @@
+//! io_uring registered (fixed) buffers, allocated page-aligned so the kernel can
+//! read straight into memory which later crosses the FFI boundary.
+
+use std::{
+    alloc::{Layout, dealloc},
+    io,
+};
+
+use io_uring::Submitter;
+
+use crate::{
+    error::FfiBufferError,
+    logging::{self, FfiLogLevel},
+    oom, stats,
+};
+
+/// Set of page-aligned buffers of equal length, registered as io_uring fixed buffers.
+///
+/// The index of a buffer is its registration index (`buf_index` of `ReadFixed`/`WriteFixed`).
+/// Free indices are tracked, so buffers can be acquired and released while registered.
+///
+/// Note: The set must be unregistered before it is dropped, otherwise the buffers are leaked
+/// (the kernel may still write to them).
+#[derive(Debug)]
+pub struct RegisteredBuffers {
+    iovecs: Vec<libc::iovec>,
+    layout: Layout,
+    free: Vec<u16>,
+    registered: bool,
+}
+
+// The buffers are owned by the set, the raw pointers of the `iovec`s are not shared.
+unsafe impl Send for RegisteredBuffers {}
+
+impl RegisteredBuffers {
+    /// Allocates `count` zeroed buffers with the given `len` (rounded up to the page size).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FfiBufferError::InvalidArgument`] if `count` exceeds the io_uring limit of
+    /// `u16::MAX` buffers or `len` is 0, [`FfiBufferError::Alloc`] if an allocation failed.
+    pub fn new(count: usize, len: usize) -> Result<Self, FfiBufferError> {
+        if count > usize::from(u16::MAX) {
+            return Err(FfiBufferError::InvalidArgument(
+                "more than u16::MAX buffers",
+            ));
+        }
+        if len == 0 {
+            return Err(FfiBufferError::InvalidArgument("buffer length of 0"));
+        }
+
+        let page_size = usize::try_from(unsafe { libc::sysconf(libc::_SC_PAGESIZE) })
+            .map_err(|_| io::Error::last_os_error())?;
+        let layout = Layout::from_size_align(len, page_size)
+            .map_err(|_| FfiBufferError::CapacityOverflow)?
+            .pad_to_align();
+
+        let mut buffers = Self {
+            iovecs: Vec::with_capacity(count),
+            layout,
+            free: (0..count as u16).rev().collect(),
+            registered: false,
+        };
+
+        for _ in 0..count {
+            let ptr = oom::allocate(layout, true);
+            if ptr.is_null() {
+                return Err(FfiBufferError::Alloc { len: layout.size() });
+            }
+
+            stats::buffer_created(layout.size());
+            buffers.iovecs.push(libc::iovec {
+                iov_base: ptr.cast(),
+                iov_len: layout.size(),
+            });
+        }
+
+        Ok(buffers)
+    }
+
+    /// Registers the buffers as fixed buffers of the ring of the given `submitter`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FfiBufferError::Io`] with the error of `io_uring_register`.
+    pub fn register(&mut self, submitter: &Submitter<'_>) -> Result<(), FfiBufferError> {
+        // The buffers stay valid until they are unregistered, `Drop` leaks them otherwise.
+        unsafe { submitter.register_buffers(&self.iovecs)? };
+        self.registered = true;
+
+        Ok(())
+    }
+
+    /// Unregisters the buffers from the ring of the given `submitter`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FfiBufferError::Io`] with the error of `io_uring_register`.
+    pub fn unregister(&mut self, submitter: &Submitter<'_>) -> Result<(), FfiBufferError> {
+        submitter.unregister_buffers()?;
+        self.registered = false;
+
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.iovecs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.iovecs.is_empty()
+    }
+
+    /// Returns the length of each buffer (rounded up to the page size).
+    pub fn buffer_len(&self) -> usize {
+        self.layout.size()
+    }
+
+    /// Acquires a free buffer and returns its registration index,
+    /// `None` if all buffers are in use.
+    pub fn acquire(&mut self) -> Option<u16> {
+        self.free.pop()
+    }
+
+    /// Releases the buffer with the given index, so it can be acquired again.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FfiBufferError::OutOfBounds`] if the index is out of range,
+    /// [`FfiBufferError::Registry`] if the buffer is not acquired.
+    pub fn release(&mut self, index: u16) -> Result<(), FfiBufferError> {
+        if usize::from(index) >= self.len() {
+            return Err(FfiBufferError::OutOfBounds {
+                index: usize::from(index),
+                len: self.len(),
+            });
+        }
+        if self.free.contains(&index) {
+            return Err(FfiBufferError::Registry("buffer is not acquired"));
+        }
+
+        self.free.push(index);
+
+        Ok(())
+    }
+
+    /// Returns the pointer to the buffer with the given index, `None` if out of range.
+    ///
+    /// The pointer is valid (and can be handed to the host) until the set is dropped.
+    pub fn buffer_ptr(&self, index: u16) -> Option<*mut u8> {
+        self.iovecs
+            .get(usize::from(index))
+            .map(|iovec| iovec.iov_base.cast())
+    }
+
+    /// Returns the buffer with the given index, `None` if out of range.
+    ///
+    /// # Safety
+    ///
+    /// No io_uring operation may write to the buffer while the returned reference is used.
+    pub unsafe fn buffer(&self, index: u16) -> Option<&[u8]> {
+        let ptr = self.buffer_ptr(index)?;
+        Some(unsafe { std::slice::from_raw_parts(ptr, self.buffer_len()) })
+    }
+}
+
+impl Drop for RegisteredBuffers {
+    fn drop(&mut self) {
+        if self.registered {
+            logging::log(
+                FfiLogLevel::Error,
+                "registered buffers dropped without unregistering, the buffers are leaked",
+            );
+            return;
+        }
+
+        for iovec in &self.iovecs {
+            stats::buffer_reclaimed(iovec.iov_len);
+            unsafe { dealloc(iovec.iov_base.cast(), self.layout) };
+        }
+    }
+}
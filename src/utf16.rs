@@ -0,0 +1,51 @@
+//! UTF-16 strings as boxed `u16` slices `Box<[u16]>`, for Windows/C#/Java hosts.
+//!
+//! Lengths are in UTF-16 code units, not bytes.
+
+use std::string::FromUtf16Error;
+
+use crate::{from_boxed_slice_raw, into_boxed_slice_raw};
+
+/// Converts the given string into a UTF-16 boxed slice and returns its raw parts `(ptr, len)`.
+///
+/// The units must be converted back with one of the `..._from_boxed_utf16_raw` functions.
+pub fn string_into_boxed_utf16_raw(src: String) -> (*const u16, usize) {
+    into_boxed_slice_raw(src.encode_utf16().collect())
+}
+
+/// Converts the given UTF-16 boxed slice back into a string, invalid UTF-16 sequences
+/// (e.g. unpaired surrogates) are replaced with `U+FFFD`.
+///
+/// Note: An empty string is returned if the size of `length` units overflows,
+/// see [`from_boxed_slice_raw`].
+///
+/// # Safety
+///
+/// The units must have been created with [`string_into_boxed_utf16_raw`]
+/// (or have the layout `Box<[u16]>`) and must not be used afterwards.
+pub unsafe fn string_from_boxed_utf16_raw(units_ptr: *mut u16, length: usize) -> String {
+    let units = unsafe { from_boxed_slice_raw(units_ptr, length) }.unwrap_or_default();
+    String::from_utf16_lossy(&units)
+}
+
+/// Converts the given UTF-16 boxed slice back into a string.
+///
+/// # Errors
+///
+/// Returns the reclaimed units with the [`FromUtf16Error`] if they are not valid UTF-16,
+/// so the caller can recover them.
+///
+/// Note: An empty string is returned if the size of `length` units overflows,
+/// see [`from_boxed_slice_raw`].
+///
+/// # Safety
+///
+/// The units must have been created with [`string_into_boxed_utf16_raw`]
+/// (or have the layout `Box<[u16]>`) and must not be used afterwards.
+pub unsafe fn try_string_from_boxed_utf16_raw(
+    units_ptr: *mut u16,
+    length: usize,
+) -> Result<String, (Box<[u16]>, FromUtf16Error)> {
+    let units = unsafe { from_boxed_slice_raw(units_ptr, length) }.unwrap_or_default();
+    String::from_utf16(&units).map_err(|error| (units, error))
+}
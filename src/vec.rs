@@ -0,0 +1,121 @@
+//! FFI compatible representation of a `Vec<u8>`, which keeps its capacity, so the original
+//! allocation is reclaimed exactly instead of being shrunk to fit first.
+
+use std::mem::ManuallyDrop;
+
+use crate::{
+    audit::{self, FfiAuditEvent},
+    stats,
+};
+
+/// FFI compatible representation of a `Vec<u8>` with its capacity.
+///
+/// The layout is shared with the C side (see `include/ffi_byte_buffer.h`). The bytes
+/// `ptr[len..cap]` are allocated but uninitialized, they must not be read.
+///
+/// A buffer without allocation is always represented by a null `ptr`, a `len` and a `cap` of 0.
+///
+/// Note: The buffer does not drop its bytes - lifetime is not rust managed,
+/// it must be converted back with [`OwnedVecBuffer::into_vec`] at some point.
+#[repr(C)]
+#[derive(Debug)]
+pub struct OwnedVecBuffer {
+    pub ptr: *mut u8,
+    pub len: usize,
+    pub cap: usize,
+}
+
+impl OwnedVecBuffer {
+    /// The canonical empty buffer, a null `ptr`, a `len` and a `cap` of 0.
+    pub const EMPTY: Self = Self {
+        ptr: std::ptr::null_mut(),
+        len: 0,
+        cap: 0,
+    };
+
+    /// Converts the given vector into a buffer, without reallocating.
+    #[cfg_attr(any(feature = "debug-track", feature = "tracing"), track_caller)]
+    pub fn from_vec(src: Vec<u8>) -> Self {
+        let (ptr, len, cap) = vec_into_raw_parts(src);
+        Self { ptr, len, cap }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.cap
+    }
+
+    /// Converts the buffer back to a rust managed vector with the original allocation.
+    ///
+    /// # Safety
+    ///
+    /// See [`vec_from_raw_parts`], the buffer must not be used afterwards.
+    pub unsafe fn into_vec(self) -> Vec<u8> {
+        unsafe { vec_from_raw_parts(self.ptr, self.len, self.cap) }
+    }
+}
+
+impl Default for OwnedVecBuffer {
+    fn default() -> Self {
+        Self::EMPTY
+    }
+}
+
+impl From<Vec<u8>> for OwnedVecBuffer {
+    fn from(src: Vec<u8>) -> Self {
+        Self::from_vec(src)
+    }
+}
+
+/// Decomposes the given vector into its raw parts `(ptr, len, cap)`, to be passed to the host
+/// (the same as the unstable `Vec::into_raw_parts`). The allocation is not shrunk.
+///
+/// A vector without allocation (a capacity of 0) returns a null pointer.
+///
+/// Note: The vector is not dropped - it must be reconstructed with [`vec_from_raw_parts`]
+/// at some point.
+#[cfg_attr(any(feature = "debug-track", feature = "tracing"), track_caller)]
+pub fn vec_into_raw_parts(src: Vec<u8>) -> (*mut u8, usize, usize) {
+    if src.capacity() == 0 {
+        return (std::ptr::null_mut(), 0, 0);
+    }
+
+    let mut src = ManuallyDrop::new(src);
+    let (ptr, len, cap) = (src.as_mut_ptr(), src.len(), src.capacity());
+    stats::buffer_created(cap);
+    audit::record(FfiAuditEvent::Export, ptr, cap, "vec_into_raw_parts");
+    #[cfg(feature = "debug-track")]
+    crate::track::issue(ptr, cap, std::panic::Location::caller());
+
+    (ptr, len, cap)
+}
+
+/// Reconstructs a vector from the given raw parts, with the original allocation
+/// of exactly `cap` bytes.
+///
+/// Note: A null `ptr` or a `cap` of 0 results in an empty vector.
+///
+/// # Safety
+///
+/// The raw parts must have been returned by [`vec_into_raw_parts`] (the host may have
+/// changed `len` to at most `cap`, after initializing the bytes up to `len`)
+/// and must not be used afterwards.
+pub unsafe fn vec_from_raw_parts(ptr: *mut u8, len: usize, cap: usize) -> Vec<u8> {
+    if ptr.is_null() || cap == 0 {
+        return Vec::new();
+    }
+
+    #[cfg(feature = "debug-track")]
+    crate::track::reclaim(ptr, cap);
+    stats::buffer_reclaimed(cap);
+    audit::record(FfiAuditEvent::Import, ptr, cap, "vec_from_raw_parts");
+
+    unsafe { Vec::from_raw_parts(ptr, len, cap) }
+}
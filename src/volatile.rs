@@ -0,0 +1,26 @@
+//! Copies from/into foreign memory which may change behind the compiler's back,
+//! e.g. mapped device registers or memory concurrently written by another core.
+
+/// Copies `len` bytes from the given foreign memory into a new boxed byte slice,
+/// reading each byte with a volatile read.
+///
+/// # Safety
+///
+/// The pointer must be valid for `len` byte reads, a null pointer is only valid with a length of 0.
+pub unsafe fn volatile_copy_from_foreign(ptr: *const u8, len: usize) -> Box<[u8]> {
+    (0..len)
+        .map(|i| unsafe { ptr.add(i).read_volatile() })
+        .collect()
+}
+
+/// Copies the given bytes into the foreign memory, writing each byte with a volatile write.
+///
+/// # Safety
+///
+/// The pointer must be valid for `src.len()` byte writes,
+/// a null pointer is only valid with an empty `src`.
+pub unsafe fn volatile_copy_into_foreign(dst: *mut u8, src: &[u8]) {
+    for (i, byte) in src.iter().enumerate() {
+        unsafe { dst.add(i).write_volatile(*byte) };
+    }
+}
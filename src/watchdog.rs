@@ -0,0 +1,283 @@
+//! Watchdog flagging exported buffers which are not reclaimed within a given duration.
+//! Disabled by default, see [`enable`].
+
+use std::{
+    collections::HashMap,
+    ffi::c_void,
+    fmt::{self, Write as _},
+    fs::File,
+    io::Write as _,
+    path::Path,
+    sync::{
+        LazyLock, Mutex, TryLockError,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    audit::FfiAuditEvent,
+    error::FfiBufferError,
+    logging::{self, FfiLogLevel},
+};
+
+/// Exported buffer which was not reclaimed within the checked duration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StaleBuffer {
+    pub ptr: usize,
+    pub len: usize,
+    /// Name of the function which exported the buffer.
+    pub label: &'static str,
+    pub age: Duration,
+}
+
+/// Host callback receiving one line of a live buffer dump, see [`dump_live_buffers`].
+///
+/// - `ctx` - the context given to the dump
+/// - `line_ptr` - pointer to the UTF-8 line bytes, ending with `\n` (without NUL terminator)
+/// - `line_len` - length of the line bytes
+///
+/// Note: The line bytes are only valid during the call.
+pub type FfiDumpCallback =
+    unsafe extern "C" fn(ctx: *mut c_void, line_ptr: *const u8, line_len: usize);
+
+struct Exported {
+    len: usize,
+    label: &'static str,
+    since: Instant,
+    // A stale buffer is flagged once only.
+    flagged: bool,
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static EXPORTED: LazyLock<Mutex<HashMap<usize, Exported>>> = LazyLock::new(Default::default);
+// Incremented to stop a running watchdog thread.
+static THREAD_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Enables or disables the tracking of exported buffers, disabling forgets the tracked buffers.
+///
+/// Only buffers exported while enabled are tracked.
+pub fn enable(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+    if !enabled {
+        EXPORTED.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+}
+
+/// Returns the exported buffers, which are not reclaimed for at least `max_age`
+/// and were not returned by a previous check before.
+///
+/// Each returned buffer is logged as warning with its label (see [`crate::logging`]).
+pub fn check_stale(max_age: Duration) -> Vec<StaleBuffer> {
+    let now = Instant::now();
+
+    let mut stale = Vec::new();
+    for (&ptr, exported) in EXPORTED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter_mut()
+    {
+        let age = now.duration_since(exported.since);
+        if exported.flagged || age < max_age {
+            continue;
+        }
+
+        exported.flagged = true;
+        stale.push(StaleBuffer {
+            ptr,
+            len: exported.len,
+            label: exported.label,
+            age,
+        });
+    }
+
+    for buffer in &stale {
+        logging::log(
+            FfiLogLevel::Warn,
+            &format!(
+                "buffer {:#x} ({} bytes) exported by {} not reclaimed for {:?}",
+                buffer.ptr, buffer.len, buffer.label, buffer.age
+            ),
+        );
+    }
+
+    stale
+}
+
+/// Enables the tracking and starts a background thread, which calls [`check_stale`]
+/// with the given `max_age` each `interval` until [`stop`] is called.
+///
+/// A previously started thread is stopped.
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::Io`] if the thread can't be spawned.
+pub fn start(interval: Duration, max_age: Duration) -> Result<(), FfiBufferError> {
+    enable(true);
+
+    let generation = THREAD_GENERATION.fetch_add(1, Ordering::Relaxed) + 1;
+    thread::Builder::new()
+        .name("ffi-byte-buffer-watchdog".into())
+        .spawn(move || {
+            loop {
+                thread::sleep(interval);
+                if THREAD_GENERATION.load(Ordering::Relaxed) != generation {
+                    break;
+                }
+
+                check_stale(max_age);
+            }
+        })?;
+
+    Ok(())
+}
+
+/// Stops the background thread started by [`start`] (after its current sleep),
+/// the tracking stays enabled.
+pub fn stop() {
+    THREAD_GENERATION.fetch_add(1, Ordering::Relaxed);
+}
+
+// Returns the live buffers `(ptr, len, label)` ordered by pointer, e.g. for the leak report.
+pub(crate) fn live_buffers() -> Vec<(usize, usize, &'static str)> {
+    let mut live: Vec<_> = EXPORTED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .map(|(&ptr, exported)| (ptr, exported.len, exported.label))
+        .collect();
+    live.sort_unstable_by_key(|&(ptr, ..)| ptr);
+    live
+}
+
+// Returns the length recorded for the exported buffer at the given pointer, None if it is
+// not tracked (e.g. exported while the tracking was disabled).
+#[cfg(feature = "export")]
+pub(crate) fn exported_len(ptr: *const u8) -> Option<usize> {
+    EXPORTED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&(ptr as usize))
+        .map(|exported| exported.len)
+}
+
+// Initializes the registry of the exported buffers, see `crate::lifecycle::init`.
+pub(crate) fn init() {
+    LazyLock::force(&EXPORTED);
+}
+
+/// Writes one line per live (exported and not reclaimed) buffer to `write` and returns
+/// the number of buffers. A line reads `<ptr> <len> <age in ms> <label>\n`.
+///
+/// Safe to call from a crash handler: the lines are formatted on the stack without allocation
+/// and the registry is not waited for. `None` is returned (and nothing is written) if the
+/// registry is locked, e.g. by the crashed thread.
+///
+/// Note: Only buffers exported while the tracking is enabled are live, see [`enable`].
+pub fn dump_live_buffers(mut write: impl FnMut(&[u8])) -> Option<usize> {
+    let exported = match EXPORTED.try_lock() {
+        Ok(exported) => exported,
+        Err(TryLockError::Poisoned(e)) => e.into_inner(),
+        Err(TryLockError::WouldBlock) => return None,
+    };
+
+    let now = Instant::now();
+    for (&ptr, exported) in exported.iter() {
+        let mut line = Line::default();
+        // The error of a too long (truncated) line is ignored.
+        let _ = writeln!(
+            line,
+            "{ptr:#018x} {} {} {}",
+            exported.len,
+            now.duration_since(exported.since).as_millis(),
+            exported.label
+        );
+        write(line.as_bytes());
+    }
+
+    Some(exported.len())
+}
+
+/// Writes the live buffers to a new file at the given `path` and returns their number,
+/// see [`dump_live_buffers`].
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::Io`] if the file can't be created or written,
+/// [`FfiBufferError::Registry`] if the registry is locked.
+pub fn dump_live_buffers_to_path(path: impl AsRef<Path>) -> Result<usize, FfiBufferError> {
+    let mut file = File::create(path)?;
+
+    let mut result = Ok(());
+    let count = dump_live_buffers(|line| {
+        if result.is_ok() {
+            result = file.write_all(line);
+        }
+    })
+    .ok_or(FfiBufferError::Registry("live buffer registry is locked"))?;
+    result?;
+
+    Ok(count)
+}
+
+// Fixed size line of a live buffer dump, truncated (with a final line break) if too long.
+struct Line {
+    bytes: [u8; 256],
+    len: usize,
+}
+
+impl Default for Line {
+    fn default() -> Self {
+        Self {
+            bytes: [0; 256],
+            len: 0,
+        }
+    }
+}
+
+impl Line {
+    fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+impl fmt::Write for Line {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let available = self.bytes.len() - self.len;
+        if s.len() <= available {
+            self.bytes[self.len..self.len + s.len()].copy_from_slice(s.as_bytes());
+            self.len += s.len();
+            return Ok(());
+        }
+
+        self.bytes[self.len..].copy_from_slice(&s.as_bytes()[..available]);
+        self.len = self.bytes.len();
+        self.bytes[self.len - 1] = b'\n';
+        Err(fmt::Error)
+    }
+}
+
+pub(crate) fn observe(event: FfiAuditEvent, ptr: *const u8, len: usize, label: &'static str) {
+    if !ENABLED.load(Ordering::Relaxed) || len == 0 {
+        return;
+    }
+
+    let mut exported = EXPORTED.lock().unwrap_or_else(|e| e.into_inner());
+    match event {
+        FfiAuditEvent::Allocate | FfiAuditEvent::Export => {
+            exported.insert(
+                ptr as usize,
+                Exported {
+                    len,
+                    label,
+                    since: Instant::now(),
+                    flagged: false,
+                },
+            );
+        }
+        FfiAuditEvent::Import | FfiAuditEvent::Free => {
+            exported.remove(&(ptr as usize));
+        }
+    }
+}
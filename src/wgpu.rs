@@ -0,0 +1,85 @@
+//! wgpu interop, so frame data can be written by the host straight into GPU staging buffers.
+
+use std::alloc::{Layout, dealloc};
+
+use wgpu::{BufferViewMut, COPY_BUFFER_ALIGNMENT, MAP_ALIGNMENT};
+
+use crate::{ByteBuffer, FfiSliceMut, error::FfiBufferError, oom, stats};
+
+/// Returns the given length rounded up to the size alignment wgpu requires for copies
+/// and mapped buffers ([`COPY_BUFFER_ALIGNMENT`]), `None` if it overflows.
+pub fn staging_len(len: usize) -> Option<usize> {
+    len.checked_next_multiple_of(COPY_BUFFER_ALIGNMENT as usize)
+}
+
+/// Allocates a new zeroed byte buffer which satisfies wgpu's mapped buffer requirements:
+/// the length is rounded up with [`staging_len`] and the bytes are aligned to [`MAP_ALIGNMENT`].
+///
+/// The buffer can be passed to the host to be filled and then to `Queue::write_buffer`.
+///
+/// Note: The buffer must be freed with [`free_staging_byte_buffer`],
+/// it has a different layout than a boxed byte slice.
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::CapacityOverflow`] if the rounded length exceeds `isize::MAX`,
+/// [`FfiBufferError::Alloc`] if the allocation failed.
+pub fn new_staging_byte_buffer(len: usize) -> Result<ByteBuffer, FfiBufferError> {
+    if len == 0 {
+        return Ok(ByteBuffer::EMPTY);
+    }
+
+    let layout = staging_layout(len).ok_or(FfiBufferError::CapacityOverflow)?;
+    let ptr = oom::allocate(layout, true);
+    if ptr.is_null() {
+        return Err(FfiBufferError::Alloc { len: layout.size() });
+    }
+
+    stats::buffer_created(layout.size());
+    Ok(ByteBuffer {
+        ptr,
+        len: layout.size(),
+    })
+}
+
+/// Frees the given byte buffer, created with [`new_staging_byte_buffer`].
+///
+/// # Safety
+///
+/// The buffer must be created with [`new_staging_byte_buffer`] and not freed before.
+pub unsafe fn free_staging_byte_buffer(buffer: ByteBuffer) {
+    if buffer.len == 0 {
+        return;
+    }
+
+    // The length is rounded already, so the layout is the one of the allocation.
+    let Some(layout) = staging_layout(buffer.len) else {
+        return;
+    };
+
+    stats::buffer_reclaimed(buffer.len);
+    unsafe { dealloc(buffer.ptr, layout) };
+}
+
+/// Wraps the given mapped staging buffer range as mutable view, for the host to fill.
+///
+/// The view is valid until the given `view` is dropped, which must happen before the
+/// GPU buffer is unmapped.
+///
+/// Note: Mapped memory may be write-combining - the host should only write to the view,
+/// reading from it may be very slow.
+pub fn mapped_slice_mut(view: &mut BufferViewMut) -> FfiSliceMut {
+    let mut slice = view.slice(..);
+    if slice.is_empty() {
+        return FfiSliceMut::EMPTY;
+    }
+
+    FfiSliceMut {
+        ptr: slice.as_raw_element_ptr().as_ptr(),
+        len: slice.len(),
+    }
+}
+
+fn staging_layout(len: usize) -> Option<Layout> {
+    Layout::from_size_align(staging_len(len)?, MAP_ALIGNMENT as usize).ok()
+}
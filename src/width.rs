@@ -0,0 +1,181 @@
+//! Fixed width representations of a byte buffer, independent of the pointer width.
+//!
+//! [`ByteBuffer`] uses `usize`, so its layout differs between e.g. wasm32 and wasm64 (memory64).
+//! Hosts and guests with different pointer widths exchange [`ByteBuffer32`] or [`ByteBuffer64`]
+//! instead and convert with the checked conversions below.
+
+use crate::{ByteBuffer, error::FfiBufferError};
+
+/// Byte buffer with 32 bit address and length, the layout of [`ByteBuffer`] on wasm32.
+///
+/// An empty buffer is always represented by a `ptr` and a `len` of 0.
+///
+/// Note: Like [`ByteBuffer`] the buffer does not drop its bytes. A guest address is an
+/// offset into the guest memory, not a host pointer.
+#[repr(C)]
+#[derive(Debug)]
+pub struct ByteBuffer32 {
+    pub ptr: u32,
+    pub len: u32,
+}
+
+/// Byte buffer with 64 bit address and length, the layout of [`ByteBuffer`] on wasm64.
+///
+/// An empty buffer is always represented by a `ptr` and a `len` of 0.
+///
+/// Note: Like [`ByteBuffer`] the buffer does not drop its bytes. A guest address is an
+/// offset into the guest memory, not a host pointer.
+#[repr(C)]
+#[derive(Debug)]
+pub struct ByteBuffer64 {
+    pub ptr: u64,
+    pub len: u64,
+}
+
+impl ByteBuffer32 {
+    /// The canonical empty buffer, a `ptr` and a `len` of 0.
+    pub const EMPTY: Self = Self { ptr: 0, len: 0 };
+
+    /// Returns the length as `usize`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FfiBufferError::CapacityOverflow`] if the length exceeds the pointer width.
+    pub fn len_usize(&self) -> Result<usize, FfiBufferError> {
+        usize::try_from(self.len).map_err(|_| FfiBufferError::CapacityOverflow)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl ByteBuffer64 {
+    /// The canonical empty buffer, a `ptr` and a `len` of 0.
+    pub const EMPTY: Self = Self { ptr: 0, len: 0 };
+
+    /// Returns the length as `usize`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FfiBufferError::CapacityOverflow`] if the length exceeds the pointer width,
+    /// e.g. a wasm64 length on wasm32.
+    pub fn len_usize(&self) -> Result<usize, FfiBufferError> {
+        usize::try_from(self.len).map_err(|_| FfiBufferError::CapacityOverflow)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl Default for ByteBuffer32 {
+    fn default() -> Self {
+        Self::EMPTY
+    }
+}
+
+impl Default for ByteBuffer64 {
+    fn default() -> Self {
+        Self::EMPTY
+    }
+}
+
+/// Lossless on all targets with a pointer width of up to 64 bits.
+impl From<ByteBuffer> for ByteBuffer64 {
+    fn from(src: ByteBuffer) -> Self {
+        Self {
+            ptr: src.ptr.expose_provenance() as u64,
+            len: src.len as u64,
+        }
+    }
+}
+
+impl From<ByteBuffer32> for ByteBuffer64 {
+    fn from(src: ByteBuffer32) -> Self {
+        Self {
+            ptr: u64::from(src.ptr),
+            len: u64::from(src.len),
+        }
+    }
+}
+
+/// Returns the buffer unchanged if its address or length exceeds 32 bits.
+impl TryFrom<ByteBuffer> for ByteBuffer32 {
+    type Error = ByteBuffer;
+
+    fn try_from(src: ByteBuffer) -> Result<Self, Self::Error> {
+        match (
+            u32::try_from(src.ptr.expose_provenance()),
+            u32::try_from(src.len),
+        ) {
+            (Ok(ptr), Ok(len)) => Ok(Self { ptr, len }),
+            _ => Err(src),
+        }
+    }
+}
+
+/// Returns the buffer unchanged if its address or length exceeds 32 bits.
+impl TryFrom<ByteBuffer64> for ByteBuffer32 {
+    type Error = ByteBuffer64;
+
+    fn try_from(src: ByteBuffer64) -> Result<Self, Self::Error> {
+        match (u32::try_from(src.ptr), u32::try_from(src.len)) {
+            (Ok(ptr), Ok(len)) => Ok(Self { ptr, len }),
+            _ => Err(src),
+        }
+    }
+}
+
+/// Returns the buffer unchanged if its address or length exceeds the pointer width.
+///
+/// Note: Only meaningful for buffers of the own address space, not for guest offsets.
+impl TryFrom<ByteBuffer32> for ByteBuffer {
+    type Error = ByteBuffer32;
+
+    fn try_from(src: ByteBuffer32) -> Result<Self, Self::Error> {
+        match (usize::try_from(src.ptr), usize::try_from(src.len)) {
+            (Ok(ptr), Ok(len)) => Ok(Self {
+                ptr: std::ptr::with_exposed_provenance_mut(ptr),
+                len,
+            }),
+            _ => Err(src),
+        }
+    }
+}
+
+/// Returns the buffer unchanged if its address or length exceeds the pointer width,
+/// e.g. a wasm64 buffer on wasm32.
+///
+/// Note: Only meaningful for buffers of the own address space, not for guest offsets.
+impl TryFrom<ByteBuffer64> for ByteBuffer {
+    type Error = ByteBuffer64;
+
+    fn try_from(src: ByteBuffer64) -> Result<Self, Self::Error> {
+        match (usize::try_from(src.ptr), usize::try_from(src.len)) {
+            (Ok(ptr), Ok(len)) => Ok(Self {
+                ptr: std::ptr::with_exposed_provenance_mut(ptr),
+                len,
+            }),
+            _ => Err(src),
+        }
+    }
+}
+
+/// Converts the given 64 bit length, e.g. of a wasm64 guest, to `usize`.
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::CapacityOverflow`] if the length exceeds the pointer width.
+pub fn len_from_u64(len: u64) -> Result<usize, FfiBufferError> {
+    usize::try_from(len).map_err(|_| FfiBufferError::CapacityOverflow)
+}
+
+/// Converts the given length to 32 bit, e.g. for a wasm32 guest.
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::CapacityOverflow`] if the length exceeds 32 bits.
+pub fn len_to_u32(len: usize) -> Result<u32, FfiBufferError> {
+    u32::try_from(len).map_err(|_| FfiBufferError::CapacityOverflow)
+}
@@ -0,0 +1,114 @@
+//! Windows interop, converting buffers to `WSABUF` arrays and keeping them
+//! pinned until an overlapped operation (e.g. `WSASend`/`WSARecv`) completed.
+
+use std::{ffi::c_void, pin::Pin};
+
+use crate::error::FfiBufferError;
+
+/// FFI compatible `WSABUF`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WsaBuf {
+    pub len: u32,
+    pub buf: *mut u8,
+}
+
+/// FFI compatible `OVERLAPPED`.
+#[repr(C)]
+#[derive(Debug, Default)]
+pub struct Overlapped {
+    pub internal: usize,
+    pub internal_high: usize,
+    pub offset: u32,
+    pub offset_high: u32,
+    pub h_event: *mut c_void,
+}
+
+/// Returns a `WSABUF` for each of the given buffers.
+///
+/// Note: The returned `WSABUF`s point into the given buffers, they must not be
+/// used after the buffers were dropped or moved.
+///
+/// # Errors
+///
+/// Returns [`FfiBufferError::CapacityOverflow`] if a buffer is longer than `u32::MAX`.
+pub fn wsabufs_for(buffers: &mut [Box<[u8]>]) -> Result<Vec<WsaBuf>, FfiBufferError> {
+    buffers
+        .iter_mut()
+        .map(|buffer| {
+            Ok(WsaBuf {
+                len: u32::try_from(buffer.len()).map_err(|_| FfiBufferError::CapacityOverflow)?,
+                buf: buffer.as_mut_ptr(),
+            })
+        })
+        .collect()
+}
+
+/// Buffers of an overlapped operation, pinned until the operation completed.
+///
+/// The `OVERLAPPED` is the first field, so the `LPOVERLAPPED` reported on completion
+/// (completion routine or `GetQueuedCompletionStatus`) can be converted back with
+/// [`OverlappedIo::from_overlapped`].
+#[repr(C)]
+#[derive(Debug)]
+pub struct OverlappedIo {
+    overlapped: Overlapped,
+    wsabufs: Box<[WsaBuf]>,
+    buffers: Vec<Box<[u8]>>,
+}
+
+impl OverlappedIo {
+    /// # Errors
+    ///
+    /// Returns [`FfiBufferError::CapacityOverflow`] if there are more than `u32::MAX` buffers
+    /// or a buffer is longer than `u32::MAX`.
+    pub fn new(mut buffers: Vec<Box<[u8]>>) -> Result<Pin<Box<Self>>, FfiBufferError> {
+        if u32::try_from(buffers.len()).is_err() {
+            return Err(FfiBufferError::CapacityOverflow);
+        }
+        let wsabufs = wsabufs_for(&mut buffers)?.into_boxed_slice();
+
+        Ok(Box::pin(Self {
+            overlapped: Overlapped::default(),
+            wsabufs,
+            buffers,
+        }))
+    }
+
+    /// Starts the overlapped operation - the returned pointers are passed to e.g. `WSASend`
+    /// as `lpBuffers`, `dwBufferCount` and `lpOverlapped`.
+    ///
+    /// The buffers stay pinned (leaked) until they are converted back with
+    /// [`OverlappedIo::from_overlapped`] after the operation completed.
+    pub fn start(self: Pin<Box<Self>>) -> (*mut WsaBuf, u32, *mut Overlapped) {
+        // The buffers don't need structural pinning, only the leaked allocation must not move.
+        let io = Box::leak(unsafe { Pin::into_inner_unchecked(self) });
+
+        // The count was checked in `new`.
+        let count = io.wsabufs.len() as u32;
+        (io.wsabufs.as_mut_ptr(), count, &mut io.overlapped)
+    }
+
+    /// Converts the `OVERLAPPED` of a completed operation back to its buffers.
+    ///
+    /// # Safety
+    ///
+    /// The given `overlapped` must have been returned by [`OverlappedIo::start`],
+    /// the operation must have completed and the pointers must not be used afterwards.
+    pub unsafe fn from_overlapped(overlapped: *mut Overlapped) -> Box<Self> {
+        unsafe { Box::from_raw(overlapped.cast::<Self>()) }
+    }
+
+    pub fn overlapped(&self) -> &Overlapped {
+        &self.overlapped
+    }
+
+    pub fn buffers(&self) -> &[Box<[u8]>] {
+        &self.buffers
+    }
+
+    /// Returns the buffers, e.g. after `WSARecv` filled them.
+    pub fn into_buffers(self) -> Vec<Box<[u8]>> {
+        self.buffers
+    }
+}
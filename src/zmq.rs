@@ -0,0 +1,99 @@
+//! ZeroMQ interop, handing buffers to and adopting buffers from `zmq_msg_t`
+//! without copying.
+
+use std::ffi::c_void;
+
+use zmq_sys::{
+    zmq_errno, zmq_msg_close, zmq_msg_data, zmq_msg_init, zmq_msg_init_data, zmq_msg_move,
+    zmq_msg_size, zmq_msg_t,
+};
+
+use crate::{ForeignBuffer, stats};
+
+/// Initializes `msg` with the given bytes without copying.
+///
+/// Ownership of the bytes is passed to libzmq, which drops them (through this crate)
+/// once the message is sent or closed.
+///
+/// # Errors
+///
+/// Returns the `zmq_errno()` if the message could not be initialized, the bytes
+/// are dropped then.
+///
+/// # Safety
+///
+/// The given `msg` must be valid for writes and not initialized
+/// (or already closed) - it must be sent or closed afterwards.
+pub unsafe fn init_zmq_msg_with_boxed_slice(
+    msg: *mut zmq_msg_t,
+    src: Box<[u8]>,
+) -> Result<(), i32> {
+    if src.is_empty() {
+        return match unsafe { zmq_msg_init(msg) } {
+            0 => Ok(()),
+            _ => Err(unsafe { zmq_errno() }),
+        };
+    }
+
+    let len = src.len();
+    let data = Box::into_raw(src).cast::<u8>();
+
+    // The length is passed as hint, to reclaim the boxed slice in the free function.
+    let result = unsafe {
+        zmq_msg_init_data(
+            msg,
+            data.cast(),
+            len,
+            Some(free_boxed_slice),
+            len as *mut c_void,
+        )
+    };
+
+    if result != 0 {
+        let errno = unsafe { zmq_errno() };
+        drop(unsafe { Box::from_raw(std::ptr::slice_from_raw_parts_mut(data, len)) });
+        return Err(errno);
+    }
+
+    stats::buffer_created(len);
+    Ok(())
+}
+
+/// Adopts the given (received) message as foreign buffer without copying,
+/// the message is closed when the buffer is dropped.
+///
+/// The content of `msg` is moved into the buffer, `msg` is left empty (but must be
+/// closed or reused as usual).
+///
+/// # Errors
+///
+/// Returns the `zmq_errno()` if the message could not be moved.
+///
+/// # Safety
+///
+/// The given `msg` must be valid and initialized.
+pub unsafe fn adopt_zmq_msg(msg: *mut zmq_msg_t) -> Result<ForeignBuffer, i32> {
+    // Boxed, as the bytes of small messages are stored inline in the message itself.
+    let mut owned = Box::new(zmq_msg_t { __: [0; 64] });
+
+    unsafe {
+        if zmq_msg_init(&mut *owned) != 0 || zmq_msg_move(&mut *owned, msg) != 0 {
+            return Err(zmq_errno());
+        }
+
+        let ptr = zmq_msg_data(&mut *owned).cast::<u8>().cast_const();
+        let len = zmq_msg_size(&*owned);
+
+        Ok(ForeignBuffer::new(ptr, len, move || {
+            zmq_msg_close(&mut *owned);
+        }))
+    }
+}
+
+unsafe extern "C" fn free_boxed_slice(data: *mut c_void, hint: *mut c_void) {
+    let len = hint as usize;
+    stats::buffer_reclaimed(len);
+
+    let slice_raw = std::ptr::slice_from_raw_parts_mut(data.cast::<u8>(), len);
+    drop(unsafe { Box::from_raw(slice_raw) });
+}